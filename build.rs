@@ -0,0 +1,794 @@
+//! Generates the static lookup tables from the CSV files in `data/`:
+//!
+//! - `icao-allocations.csv`: the ICAO 24-bit allocation table. It is the single
+//!   source of truth for address allocations: `src/icao.rs` includes the generated
+//!   table, and the `Parser` resolves hex addresses through it.
+//! - `airlines.csv`: the airline designator table used by `src/airlines.rs`.
+//! - `aircraft-types.csv`: the ICAO Doc 8643 type designator table used by
+//!   `src/types.rs`.
+//! - `military-callsigns.csv`: the military callsign prefix table used by
+//!   `src/military.rs`.
+//! - `mmsi-mid.csv`: the ITU Maritime Identification Digits used by `src/mmsi.rs`.
+//! - `countries.csv`: English short and official country names used by
+//!   `src/country.rs` (the `country-meta` feature).
+//! - `historical-allocations.csv` and `historical-marks.csv`: dated former
+//!   holders of address blocks and registration marks, used by `src/history.rs`.
+//! - `nationality-marks.csv`: the nationality mark each state paints on its
+//!   aircraft, used by `src/marks.rs`.
+//! - `faa-carrier-series.csv`: N-number suffixes US air carriers reserve for
+//!   their fleets, used by `src/registration.rs`.
+//! - `icao-regions.csv`: the ICAO air navigation region of each state, used by
+//!   `src/region.rs`.
+//!
+//! With the `grpc` feature it also generates the tonic service of
+//! `proto/flydent.proto` for `src/grpc.rs`. The messages are written by hand in
+//! `src/grpc.rs`, so no `protoc` is needed.
+//!
+//! With the `ffi` feature it regenerates the C header `include/flydent.h` from
+//! `src/ffi.rs` with cbindgen. The header is checked in for C callers that do
+//! not build the crate themselves.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const ALLOCATIONS_CSV: &str = "data/icao-allocations.csv";
+const AIRLINES_CSV: &str = "data/airlines.csv";
+const AIRCRAFT_TYPES_CSV: &str = "data/aircraft-types.csv";
+const MILITARY_CALLSIGNS_CSV: &str = "data/military-callsigns.csv";
+const MMSI_MID_CSV: &str = "data/mmsi-mid.csv";
+const COUNTRIES_CSV: &str = "data/countries.csv";
+const HISTORICAL_ALLOCATIONS_CSV: &str = "data/historical-allocations.csv";
+const HISTORICAL_MARKS_CSV: &str = "data/historical-marks.csv";
+const REGIONS_CSV: &str = "data/icao-regions.csv";
+const NATIONALITY_MARKS_CSV: &str = "data/nationality-marks.csv";
+const FAA_CARRIER_SERIES_CSV: &str = "data/faa-carrier-series.csv";
+/// Variants of `military::Branch` accepted in the `branch` column.
+const MILITARY_BRANCHES: &[&str] = &["AirForce", "Army", "Navy", "MarineCorps", "CoastGuard", "Multinational"];
+/// Variants of `icao::SpecialUse` accepted in the `special_use` column.
+const SPECIAL_USE_KINDS: &[&str] = &["Temporary", "FlightSafety"];
+/// Codes accepted in the `region` column and the `region::Region` variants they
+/// map to.
+const REGIONS: &[(&str, &str)] = &[
+    ("AFI", "Afi"),
+    ("APAC", "Apac"),
+    ("EUR/NAT", "EurNat"),
+    ("MID", "Mid"),
+    ("NACC", "Nacc"),
+    ("SAM", "Sam"),
+];
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current_field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                if in_quotes && chars.peek() == Some(&'"') {
+                    // Escaped quote
+                    current_field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = !in_quotes;
+                }
+            }
+            ',' if !in_quotes => {
+                fields.push(current_field.trim().to_string());
+                current_field.clear();
+            }
+            _ => current_field.push(ch),
+        }
+    }
+    fields.push(current_field.trim().to_string());
+    fields
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let out_dir = env::var("OUT_DIR").unwrap();
+    generate_allocations(Path::new(&out_dir));
+    generate_airlines(Path::new(&out_dir));
+    generate_aircraft_types(Path::new(&out_dir));
+    generate_military_callsigns(Path::new(&out_dir));
+    generate_mmsi_mids(Path::new(&out_dir));
+    generate_countries(Path::new(&out_dir));
+    generate_history(Path::new(&out_dir));
+    generate_regions(Path::new(&out_dir));
+    generate_nationality_marks(Path::new(&out_dir));
+    generate_faa_carrier_series(Path::new(&out_dir));
+    #[cfg(feature = "grpc")]
+    generate_grpc_service(Path::new(&out_dir));
+    #[cfg(feature = "ffi")]
+    generate_c_header(Path::new(&out_dir));
+}
+
+fn generate_allocations(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", ALLOCATIONS_CSV);
+
+    let csv = fs::read_to_string(ALLOCATIONS_CSV).expect("failed to read allocation data");
+    let mut allocations = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() < 4 {
+            panic!("{}:{}: expected prefix,iso2,special_use,name[,note]", ALLOCATIONS_CSV, line_no + 1);
+        }
+        let (prefix, iso2, special_use, name) = (&fields[0], &fields[1], &fields[2], &fields[3]);
+
+        if prefix.is_empty() || prefix.len() > 24 || !prefix.chars().all(|c| c == '0' || c == '1') {
+            panic!("{}:{}: invalid binary prefix {:?}", ALLOCATIONS_CSV, line_no + 1, prefix);
+        }
+        // A row is either a country allocation or an ICAO special-use block, never both
+        let entry = match (iso2.as_str(), special_use.as_str()) {
+            ("", kind) if SPECIAL_USE_KINDS.contains(&kind) => {
+                format!("special({:?}, SpecialUse::{}, {:?})", prefix, kind, name)
+            }
+            (code, "") if code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()) => {
+                format!("alloc({:?}, {:?}, {:?})", prefix, code, name)
+            }
+            _ => panic!(
+                "{}:{}: expected an ISO2 code or a special-use kind, got {:?}/{:?}",
+                ALLOCATIONS_CSV,
+                line_no + 1,
+                iso2,
+                special_use
+            ),
+        };
+        if !seen.insert(prefix.clone()) {
+            panic!("{}:{}: duplicate prefix {}", ALLOCATIONS_CSV, line_no + 1, prefix);
+        }
+
+        allocations.push((prefix.clone(), entry));
+    }
+
+    // Longest prefixes first, so the first match is always the most specific one
+    allocations.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const ICAO_ALLOCATIONS: &[Allocation] = &[\n",
+        ALLOCATIONS_CSV
+    );
+    for (_, entry) in &allocations {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("icao_allocations.rs"), generated)
+        .expect("failed to write generated allocation table");
+}
+
+fn generate_airlines(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", AIRLINES_CSV);
+
+    let csv = fs::read_to_string(AIRLINES_CSV).expect("failed to read airline data");
+    let mut airlines = Vec::new();
+    let mut seen_icao = HashSet::new();
+    let mut seen_iata = HashSet::new();
+    let mut seen_callsigns = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 5 {
+            panic!("{}:{}: expected icao,iata,name,iso2,callsign", AIRLINES_CSV, line_no + 1);
+        }
+        let (icao, iata, name, iso2, callsign) = (&fields[0], &fields[1], &fields[2], &fields[3], &fields[4]);
+
+        if icao.len() != 3 || !icao.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ICAO designator {:?}", AIRLINES_CSV, line_no + 1, icao);
+        }
+        if !iata.is_empty()
+            && (iata.len() != 2 || !iata.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()))
+        {
+            panic!("{}:{}: invalid IATA designator {:?}", AIRLINES_CSV, line_no + 1, iata);
+        }
+        if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ISO2 code {:?}", AIRLINES_CSV, line_no + 1, iso2);
+        }
+        if !seen_icao.insert(icao.clone()) {
+            panic!("{}:{}: duplicate ICAO designator {}", AIRLINES_CSV, line_no + 1, icao);
+        }
+        if !iata.is_empty() && !seen_iata.insert(iata.clone()) {
+            panic!("{}:{}: duplicate IATA designator {}", AIRLINES_CSV, line_no + 1, iata);
+        }
+        // Telephony lookups ignore spaces and hyphens, so callsigns must be unique without them
+        let callsign_key: String = callsign.chars().filter(|&c| c != ' ' && c != '-').collect();
+        if callsign_key.is_empty() || callsign != &callsign.to_ascii_uppercase() {
+            panic!("{}:{}: invalid callsign {:?}", AIRLINES_CSV, line_no + 1, callsign);
+        }
+        if !seen_callsigns.insert(callsign_key) {
+            panic!("{}:{}: duplicate callsign {}", AIRLINES_CSV, line_no + 1, callsign);
+        }
+
+        let iata = if iata.is_empty() { "None".to_string() } else { format!("Some({:?})", iata) };
+        airlines.push((
+            icao.clone(),
+            format!(
+                "Airline {{ icao: {:?}, iata: {}, name: {:?}, iso2: {:?}, callsign: {:?} }}",
+                icao, iata, name, iso2, callsign
+            ),
+        ));
+    }
+
+    // Sorted by ICAO designator for binary search
+    airlines.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const AIRLINES: &[Airline] = &[\n",
+        AIRLINES_CSV
+    );
+    for (_, entry) in &airlines {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("airlines.rs"), generated).expect("failed to write generated airline table");
+}
+
+/// Doc 8643 description letters and the `types` enum variants they map to.
+const AIRCRAFT_CLASSES: &[(char, &str)] = &[
+    ('L', "LandPlane"),
+    ('S', "SeaPlane"),
+    ('A', "Amphibian"),
+    ('H', "Helicopter"),
+    ('G', "Gyrocopter"),
+    ('T', "Tiltrotor"),
+];
+/// Wake turbulence category letters and the `types::WakeCategory` variants.
+const WAKE_CATEGORIES: &[(&str, &str)] = &[("L", "Light"), ("M", "Medium"), ("H", "Heavy"), ("J", "Super")];
+const ENGINE_TYPES: &[(char, &str)] = &[
+    ('J', "Jet"),
+    ('T', "Turboprop"),
+    ('P', "Piston"),
+    ('E', "Electric"),
+    ('R', "Rocket"),
+];
+
+fn generate_aircraft_types(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", AIRCRAFT_TYPES_CSV);
+
+    let csv = fs::read_to_string(AIRCRAFT_TYPES_CSV).expect("failed to read aircraft type data");
+    let mut types = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 5 {
+            panic!("{}:{}: expected designator,manufacturer,model,description,wtc", AIRCRAFT_TYPES_CSV, line_no + 1);
+        }
+        let (designator, manufacturer, model, description, wtc) =
+            (&fields[0], &fields[1], &fields[2], &fields[3], &fields[4]);
+
+        if !(2..=4).contains(&designator.len())
+            || !designator.starts_with(|c: char| c.is_ascii_uppercase())
+            || !designator.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            panic!("{}:{}: invalid type designator {:?}", AIRCRAFT_TYPES_CSV, line_no + 1, designator);
+        }
+        if !seen.insert(designator.clone()) {
+            panic!("{}:{}: duplicate type designator {}", AIRCRAFT_TYPES_CSV, line_no + 1, designator);
+        }
+
+        // Description: aircraft class, engine count, engine type, e.g. "L2J"
+        let chars: Vec<char> = description.chars().collect();
+        let class = AIRCRAFT_CLASSES.iter().find(|(c, _)| chars.first() == Some(c));
+        let engine_type = ENGINE_TYPES.iter().find(|(c, _)| chars.get(2) == Some(c));
+        let engine_count = chars.get(1).and_then(|c| c.to_digit(10)).filter(|n| (1..=8).contains(n));
+        let (Some((_, class)), Some(engine_count), Some((_, engine_type)), 3) =
+            (class, engine_count, engine_type, chars.len())
+        else {
+            panic!("{}:{}: invalid description {:?}", AIRCRAFT_TYPES_CSV, line_no + 1, description);
+        };
+        let Some((_, wake)) = WAKE_CATEGORIES.iter().find(|(code, _)| code == wtc) else {
+            panic!("{}:{}: invalid wake turbulence category {:?}", AIRCRAFT_TYPES_CSV, line_no + 1, wtc);
+        };
+
+        types.push((
+            designator.clone(),
+            format!(
+                "AircraftType {{ designator: {:?}, manufacturer: {:?}, model: {:?}, class: AircraftClass::{}, \
+                 engine_count: {}, engine_type: EngineType::{}, wake: WakeCategory::{} }}",
+                designator, manufacturer, model, class, engine_count, engine_type, wake
+            ),
+        ));
+    }
+
+    // Sorted by designator for binary search
+    types.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const AIRCRAFT_TYPES: &[AircraftType] = &[\n",
+        AIRCRAFT_TYPES_CSV
+    );
+    for (_, entry) in &types {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("aircraft_types.rs"), generated).expect("failed to write generated aircraft type table");
+}
+
+fn generate_military_callsigns(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", MILITARY_CALLSIGNS_CSV);
+
+    let csv = fs::read_to_string(MILITARY_CALLSIGNS_CSV).expect("failed to read military callsign data");
+    let mut prefixes = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 4 {
+            panic!("{}:{}: expected prefix,operator,branch,iso2", MILITARY_CALLSIGNS_CSV, line_no + 1);
+        }
+        let (prefix, operator, branch, iso2) = (&fields[0], &fields[1], &fields[2], &fields[3]);
+
+        if !(2..=6).contains(&prefix.len()) || !prefix.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid prefix {:?}", MILITARY_CALLSIGNS_CSV, line_no + 1, prefix);
+        }
+        if !MILITARY_BRANCHES.contains(&branch.as_str()) {
+            panic!("{}:{}: unknown branch {:?}", MILITARY_CALLSIGNS_CSV, line_no + 1, branch);
+        }
+        // Multinational operators such as NATO have no country
+        let iso2 = match iso2.as_str() {
+            "" if branch == "Multinational" => "None".to_string(),
+            code if code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()) => format!("Some({:?})", code),
+            _ => panic!("{}:{}: invalid ISO2 code {:?}", MILITARY_CALLSIGNS_CSV, line_no + 1, iso2),
+        };
+        if !seen.insert(prefix.clone()) {
+            panic!("{}:{}: duplicate prefix {}", MILITARY_CALLSIGNS_CSV, line_no + 1, prefix);
+        }
+
+        prefixes.push((
+            prefix.clone(),
+            format!(
+                "MilitaryPrefix {{ prefix: {:?}, operator: {:?}, branch: Branch::{}, iso2: {} }}",
+                prefix, operator, branch, iso2
+            ),
+        ));
+    }
+
+    // Longest prefixes first, so the first match is always the most specific one
+    prefixes.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const MILITARY_PREFIXES: &[MilitaryPrefix] = &[\n",
+        MILITARY_CALLSIGNS_CSV
+    );
+    for (_, entry) in &prefixes {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("military_callsigns.rs"), generated)
+        .expect("failed to write generated military callsign table");
+}
+
+fn generate_mmsi_mids(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", MMSI_MID_CSV);
+
+    let csv = fs::read_to_string(MMSI_MID_CSV).expect("failed to read MID data");
+    let mut mids = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 3 {
+            panic!("{}:{}: expected mid,iso2,name", MMSI_MID_CSV, line_no + 1);
+        }
+        let (mid, iso2, name) = (&fields[0], &fields[1], &fields[2]);
+
+        // MIDs are three digits starting with the region digit 2-7
+        let mid: u16 = match mid.parse() {
+            Ok(value) if (200..800).contains(&value) => value,
+            _ => panic!("{}:{}: invalid MID {:?}", MMSI_MID_CSV, line_no + 1, mid),
+        };
+        if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ISO2 code {:?}", MMSI_MID_CSV, line_no + 1, iso2);
+        }
+        if !seen.insert(mid) {
+            panic!("{}:{}: duplicate MID {}", MMSI_MID_CSV, line_no + 1, mid);
+        }
+
+        mids.push((mid, format!("Mid {{ mid: {}, iso2: {:?}, name: {:?} }}", mid, iso2, name)));
+    }
+
+    // Sorted by MID for binary search
+    mids.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const MIDS: &[Mid] = &[\n",
+        MMSI_MID_CSV
+    );
+    for (_, entry) in &mids {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("mmsi_mids.rs"), generated).expect("failed to write generated MID table");
+}
+
+fn generate_countries(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", COUNTRIES_CSV);
+
+    let csv = fs::read_to_string(COUNTRIES_CSV).expect("failed to read country data");
+    let mut countries = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 3 {
+            panic!("{}:{}: expected iso2,name,official_name", COUNTRIES_CSV, line_no + 1);
+        }
+        let (iso2, name, official_name) = (&fields[0], &fields[1], &fields[2]);
+
+        if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ISO2 code {:?}", COUNTRIES_CSV, line_no + 1, iso2);
+        }
+        if name.is_empty() || official_name.is_empty() {
+            panic!("{}:{}: missing name for {}", COUNTRIES_CSV, line_no + 1, iso2);
+        }
+        if !seen.insert(iso2.clone()) {
+            panic!("{}:{}: duplicate ISO2 code {}", COUNTRIES_CSV, line_no + 1, iso2);
+        }
+
+        countries.push((
+            iso2.clone(),
+            format!(
+                "Country {{ iso2: {:?}, name: {:?}, official_name: {:?} }}",
+                iso2, name, official_name
+            ),
+        ));
+    }
+
+    // Sorted by ISO2 code for binary search
+    countries.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const COUNTRIES: &[Country] = &[\n",
+        COUNTRIES_CSV
+    );
+    for (_, entry) in &countries {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("countries.rs"), generated).expect("failed to write generated country table");
+}
+
+/// `(year, month, day)`, ordered chronologically.
+type Ymd = (u16, u8, u8);
+
+/// Parse an optional `YYYY-MM-DD` date into a `history::Date` expression.
+fn date_expr(csv: &str, line_no: usize, date: &str) -> (Option<Ymd>, String) {
+    if date.is_empty() {
+        return (None, "None".to_string());
+    }
+    let parts: Vec<&str> = date.split('-').collect();
+    let parsed = match parts.as_slice() {
+        [year, month, day] if year.len() == 4 && month.len() == 2 && day.len() == 2 => {
+            match (year.parse::<u16>(), month.parse::<u8>(), day.parse::<u8>()) {
+                (Ok(year), Ok(month), Ok(day)) if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+                    Some((year, month, day))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let Some((year, month, day)) = parsed else {
+        panic!("{}:{}: invalid date {:?}, expected YYYY-MM-DD", csv, line_no, date);
+    };
+    (
+        Some((year, month, day)),
+        format!("Some(Date {{ year: {}, month: {}, day: {} }})", year, month, day),
+    )
+}
+
+/// Read a history CSV into `(key, entry)` pairs, checking that periods of the
+/// same key do not overlap.
+fn read_history(csv_path: &str, valid_key: fn(&str) -> bool) -> Vec<(String, String)> {
+    println!("cargo:rerun-if-changed={}", csv_path);
+
+    let csv = fs::read_to_string(csv_path).expect("failed to read historical data");
+    let mut periods: Vec<(String, Option<Ymd>, Option<Ymd>, usize)> = Vec::new();
+    let mut entries = Vec::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 5 {
+            panic!("{}:{}: expected prefix,code,name,from,until", csv_path, line_no + 1);
+        }
+        let (prefix, code, name) = (&fields[0], &fields[1], &fields[2]);
+
+        if !valid_key(prefix) {
+            panic!("{}:{}: invalid prefix {:?}", csv_path, line_no + 1, prefix);
+        }
+        if !(2..=4).contains(&code.len()) || !code.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid country code {:?}", csv_path, line_no + 1, code);
+        }
+        let (from, from_expr) = date_expr(csv_path, line_no + 1, &fields[3]);
+        let (until, until_expr) = date_expr(csv_path, line_no + 1, &fields[4]);
+        if until.is_none() {
+            panic!("{}:{}: historical periods need an end date", csv_path, line_no + 1);
+        }
+        if from.is_some() && from >= until {
+            panic!("{}:{}: period ends before it starts", csv_path, line_no + 1);
+        }
+        // None sorts first, i.e. as "since the beginning"
+        for (other, other_from, other_until, other_line) in &periods {
+            if other == prefix && from < *other_until && *other_from < until {
+                panic!("{}:{}: period overlaps line {}", csv_path, line_no + 1, other_line);
+            }
+        }
+        periods.push((prefix.clone(), from, until, line_no + 1));
+
+        entries.push((
+            prefix.clone(),
+            format!(
+                "holder: Holder {{ code: {:?}, name: {:?} }}, from: {}, until: {}",
+                code, name, from_expr, until_expr
+            ),
+        ));
+    }
+    entries
+}
+
+fn generate_history(out_dir: &Path) {
+    let allocations = read_history(HISTORICAL_ALLOCATIONS_CSV, |prefix| {
+        !prefix.is_empty() && prefix.len() <= 24 && prefix.chars().all(|c| c == '0' || c == '1')
+    });
+    let marks = read_history(HISTORICAL_MARKS_CSV, |prefix| {
+        !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    });
+
+    let mut generated = format!(
+        "// @generated by build.rs from {} and {}. Do not edit.\n\
+         const HISTORICAL_ALLOCATIONS: &[Period<(u32, u32)>] = &[\n",
+        HISTORICAL_ALLOCATIONS_CSV, HISTORICAL_MARKS_CSV
+    );
+    for (prefix, entry) in &allocations {
+        let bits = prefix.len() as u32;
+        let start = u32::from_str_radix(prefix, 2).unwrap() << (24 - bits);
+        let end = start + ((1u32 << (24 - bits)) - 1);
+        generated.push_str(&format!("    Period {{ key: (0x{:06X}, 0x{:06X}), {} }},\n", start, end, entry));
+    }
+    generated.push_str("];\n\nconst HISTORICAL_MARKS: &[Period<&str>] = &[\n");
+    for (prefix, entry) in &marks {
+        generated.push_str(&format!("    Period {{ key: {:?}, {} }},\n", prefix, entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("history.rs"), generated).expect("failed to write generated history table");
+}
+
+fn generate_regions(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", REGIONS_CSV);
+
+    let csv = fs::read_to_string(REGIONS_CSV).expect("failed to read region data");
+    let mut regions = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 2 {
+            panic!("{}:{}: expected iso2,region", REGIONS_CSV, line_no + 1);
+        }
+        let (iso2, region) = (&fields[0], &fields[1]);
+
+        if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ISO2 code {:?}", REGIONS_CSV, line_no + 1, iso2);
+        }
+        let Some((_, variant)) = REGIONS.iter().find(|(code, _)| code == region) else {
+            panic!("{}:{}: unknown region {:?}", REGIONS_CSV, line_no + 1, region);
+        };
+        if !seen.insert(iso2.clone()) {
+            panic!("{}:{}: duplicate ISO2 code {}", REGIONS_CSV, line_no + 1, iso2);
+        }
+
+        regions.push((iso2.clone(), format!("({:?}, Region::{})", iso2, variant)));
+    }
+
+    // Sorted by ISO2 code for binary search
+    regions.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const STATE_REGIONS: &[(&str, Region)] = &[\n",
+        REGIONS_CSV
+    );
+    for (_, entry) in &regions {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("regions.rs"), generated).expect("failed to write generated region table");
+}
+
+fn generate_nationality_marks(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", NATIONALITY_MARKS_CSV);
+
+    let csv = fs::read_to_string(NATIONALITY_MARKS_CSV).expect("failed to read nationality mark data");
+    let mut marks = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 2 {
+            panic!("{}:{}: expected iso2,mark", NATIONALITY_MARKS_CSV, line_no + 1);
+        }
+        let (iso2, mark) = (&fields[0], &fields[1]);
+
+        if iso2.len() != 2 || !iso2.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid ISO2 code {:?}", NATIONALITY_MARKS_CSV, line_no + 1, iso2);
+        }
+        if mark.is_empty() || !mark.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-') {
+            panic!("{}:{}: invalid mark {:?}", NATIONALITY_MARKS_CSV, line_no + 1, mark);
+        }
+        if !seen.insert(iso2.clone()) {
+            panic!("{}:{}: duplicate ISO2 code {}", NATIONALITY_MARKS_CSV, line_no + 1, iso2);
+        }
+
+        marks.push((iso2.clone(), format!("({:?}, {:?})", iso2, mark)));
+    }
+
+    // Sorted by ISO2 code for binary search
+    marks.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const NATIONALITY_MARKS: &[(&str, &str)] = &[\n",
+        NATIONALITY_MARKS_CSV
+    );
+    for (_, entry) in &marks {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("nationality_marks.rs"), generated).expect("failed to write generated nationality mark table");
+}
+
+fn generate_faa_carrier_series(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", FAA_CARRIER_SERIES_CSV);
+
+    let csv = fs::read_to_string(FAA_CARRIER_SERIES_CSV).expect("failed to read FAA carrier series data");
+    let mut series = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line_no, line) in csv.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = parse_csv_line(line);
+        if fields.len() != 2 {
+            panic!("{}:{}: expected suffix,airline", FAA_CARRIER_SERIES_CSV, line_no + 1);
+        }
+        let (suffix, airline) = (&fields[0], &fields[1]);
+
+        // Two N-number letters, which never include I or O
+        if suffix.len() != 2 || !suffix.chars().all(|c| c.is_ascii_uppercase() && c != 'I' && c != 'O') {
+            panic!("{}:{}: invalid suffix {:?}", FAA_CARRIER_SERIES_CSV, line_no + 1, suffix);
+        }
+        if airline.len() != 3 || !airline.chars().all(|c| c.is_ascii_uppercase()) {
+            panic!("{}:{}: invalid airline designator {:?}", FAA_CARRIER_SERIES_CSV, line_no + 1, airline);
+        }
+        if !seen.insert(suffix.clone()) {
+            panic!("{}:{}: duplicate suffix {}", FAA_CARRIER_SERIES_CSV, line_no + 1, suffix);
+        }
+
+        series.push((suffix.clone(), format!("({:?}, {:?})", suffix, airline)));
+    }
+
+    // Sorted by suffix for binary search
+    series.sort();
+
+    let mut generated = format!(
+        "// @generated by build.rs from {}. Do not edit.\n\
+         const CARRIER_SERIES: &[(&str, &str)] = &[\n",
+        FAA_CARRIER_SERIES_CSV
+    );
+    for (_, entry) in &series {
+        generated.push_str(&format!("    {},\n", entry));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_dir.join("faa_carrier_series.rs"), generated).expect("failed to write generated FAA carrier series table");
+}
+
+/// Generate the `flydent.v1.Flydent` service of `proto/flydent.proto`.
+#[cfg(feature = "grpc")]
+fn generate_grpc_service(out_dir: &Path) {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    println!("cargo:rerun-if-changed=proto/flydent.proto");
+
+    let method = |name: &str, route_name: &str, input: &str, output: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("super::{}", input))
+            .output_type(format!("super::{}", output))
+            .codec_path("tonic_prost::ProstCodec")
+            .build()
+    };
+    let service = Service::builder()
+        .name("Flydent")
+        .package("flydent.v1")
+        .method(method("identify", "Identify", "IdentifyRequest", "IdentifyResponse"))
+        .method(method("convert", "Convert", "ConvertRequest", "ConvertResponse"))
+        .method(method("batch_classify", "BatchClassify", "BatchClassifyRequest", "BatchClassifyResponse"))
+        .build();
+
+    Builder::new().build_transport(false).out_dir(out_dir).compile(&[service]);
+}
+
+/// Generate the C header of `src/ffi.rs` into `OUT_DIR`, where the `ffi` tests
+/// compare it with the checked-in `include/flydent.h`. The checked-in header is
+/// only rewritten when `FLYDENT_REGENERATE_HEADER` is set.
+#[cfg(feature = "ffi")]
+fn generate_c_header(out_dir: &Path) {
+    const FFI_RS: &str = "src/ffi.rs";
+    const HEADER: &str = "include/flydent.h";
+    println!("cargo:rerun-if-changed={}", FFI_RS);
+    println!("cargo:rerun-if-env-changed=FLYDENT_REGENERATE_HEADER");
+
+    let bindings = cbindgen::Builder::new()
+        .with_src(FFI_RS)
+        .with_language(cbindgen::Language::C)
+        .with_include_guard("FLYDENT_H")
+        .with_cpp_compat(true)
+        .with_documentation(true)
+        .with_autogen_warning("/* Generated by build.rs from src/ffi.rs with cbindgen. Do not edit. */")
+        .generate()
+        .expect("failed to generate the C header");
+    bindings.write_to_file(out_dir.join("flydent.h"));
+    if env::var_os("FLYDENT_REGENERATE_HEADER").is_some() {
+        bindings.write_to_file(HEADER);
+    }
+}
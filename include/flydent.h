@@ -0,0 +1,119 @@
+#ifndef FLYDENT_H
+#define FLYDENT_H
+
+/* Generated by build.rs from src/ffi.rs with cbindgen. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Success.
+ */
+#define FLYDENT_OK 0
+
+/**
+ * The input is well-formed but cannot be identified or converted.
+ */
+#define FLYDENT_NOT_FOUND -1
+
+/**
+ * A pointer is NULL, a string is not UTF-8, or the input is malformed.
+ */
+#define FLYDENT_INVALID_ARGUMENT -2
+
+/**
+ * The output buffer cannot hold the result and its NUL terminator.
+ */
+#define FLYDENT_BUFFER_TOO_SMALL -3
+
+/**
+ * Country or organization of a registration.
+ */
+typedef struct FlydentEntity {
+  /**
+   * Nation, or organization name
+   */
+  char *name;
+  char *description;
+  /**
+   * ISO 3166-1 alpha-2 code, empty for organizations
+   */
+  char iso2[3];
+  /**
+   * ISO 3166-1 alpha-3 code, empty for organizations
+   */
+  char iso3[4];
+  char *canonical_callsign;
+  bool organization;
+} FlydentEntity;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Country or organization of a registration, or NULL if it cannot be
+ * identified. `strict` requires the dash between prefix and suffix.
+ *
+ * # Safety
+ *
+ * `registration` must be NULL or a NUL-terminated string. The result must be
+ * released with `flydent_entity_free`.
+ */
+struct FlydentEntity *flydent_parse_registration(const char *registration, bool strict);
+
+/**
+ * Release an entity returned by `flydent_parse_registration`. NULL is
+ * ignored.
+ *
+ * # Safety
+ *
+ * `entity` must be NULL or a pointer returned by `flydent_parse_registration`
+ * that has not been freed yet.
+ */
+void flydent_entity_free(struct FlydentEntity *entity);
+
+/**
+ * Write the ISO2 code of the state an ICAO address is allocated to into
+ * `iso2`, a buffer of at least 3 bytes.
+ *
+ * Answers `FLYDENT_NOT_FOUND` for special-use blocks and unallocated
+ * addresses, and `FLYDENT_INVALID_ARGUMENT` for addresses above `0xFFFFFF`.
+ *
+ * # Safety
+ *
+ * `iso2` must be NULL or point to at least 3 writable bytes.
+ */
+int32_t flydent_hex_to_country(uint32_t address, char *iso2);
+
+/**
+ * Convert a registration to its ICAO address, for countries with an
+ * algorithmic mapping, e.g. `"N8437D"` to `0xAB8E4F`.
+ *
+ * # Safety
+ *
+ * `registration` must be NULL or a NUL-terminated string, and `address` NULL
+ * or writable.
+ */
+int32_t flydent_registration_to_icao(const char *registration, uint32_t *address);
+
+/**
+ * Write the registration of an ICAO address into `buffer` of `len` bytes,
+ * NUL-terminated, for countries with an algorithmic mapping.
+ *
+ * Answers the registration's length on success, or a negative error code.
+ * 16 bytes hold any registration.
+ *
+ * # Safety
+ *
+ * `buffer` must be NULL or point to at least `len` writable bytes.
+ */
+int32_t flydent_icao_to_registration(uint32_t address, char *buffer, uintptr_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FLYDENT_H */
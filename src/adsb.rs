@@ -0,0 +1,158 @@
+//! ADS-B Identification
+//!
+//! Parses the 8-character aircraft identification field of ADS-B
+//! identification messages (DO-260). The field is space-padded and carries
+//! either a flight callsign ("BAW2276 ") or, for most general aviation, the
+//! registration without its hyphen ("DABYA   ").
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use crate::airlines::{self, FlightCallsign, FlightCallsignError};
+use crate::{EntityResult, Parser};
+
+/// Error returned for identification fields that cannot have been transmitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum AdsbIdentError {
+    /// The field is all padding
+    Empty,
+    /// A byte outside the DO-260 character set (A-Z, 0-9, space)
+    IllegalCharacter(u8),
+    /// A space followed by further characters; padding may only trail
+    EmbeddedSpace,
+}
+
+impl fmt::Display for AdsbIdentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdsbIdentError::Empty => write!(f, "identification is empty"),
+            AdsbIdentError::IllegalCharacter(byte) => {
+                write!(f, "illegal character in identification: {:#04x}", byte)
+            }
+            AdsbIdentError::EmbeddedSpace => write!(f, "space inside identification"),
+        }
+    }
+}
+
+impl core::error::Error for AdsbIdentError {}
+
+/// What an identification field carries.
+#[derive(Debug, Clone)]
+pub enum AdsbIdent {
+    /// Flight callsign of an airline in the airline table
+    Flight(FlightCallsign),
+    /// Flight callsign format (e.g. "XYZ123") with an unlisted designator
+    UnlistedFlight(String),
+    /// Registration; the canonical callsign restores the hyphen, e.g. "D-ABYA"
+    Registration(EntityResult),
+    /// Neither a flight callsign nor a registration, e.g. "TEST1234"
+    Unknown(String),
+}
+
+impl AdsbIdent {
+    /// The identification as transmitted, without padding.
+    pub fn ident(&self) -> String {
+        match self {
+            AdsbIdent::Flight(flight) => format!("{}{}", flight.airline.icao, flight.flight_number),
+            AdsbIdent::Registration(entity) => entity.canonical_callsign().replace('-', ""),
+            AdsbIdent::UnlistedFlight(ident) | AdsbIdent::Unknown(ident) => ident.clone(),
+        }
+    }
+}
+
+/// Parse an ADS-B identification field.
+///
+/// Trailing padding is trimmed. A field in flight callsign format is a flight;
+/// otherwise the field is matched against the registration formats.
+///
+/// # Examples
+/// ```
+/// use flydent::adsb::{parse_adsb_ident, AdsbIdent, AdsbIdentError};
+///
+/// match parse_adsb_ident(b"BAW2276 ").unwrap() {
+///     AdsbIdent::Flight(flight) => assert_eq!(flight.airline.name, "British Airways"),
+///     other => panic!("{:?}", other),
+/// }
+///
+/// match parse_adsb_ident(b"DABYA   ").unwrap() {
+///     AdsbIdent::Registration(entity) => assert_eq!(entity.canonical_callsign(), "D-ABYA"),
+///     other => panic!("{:?}", other),
+/// }
+///
+/// assert_eq!(parse_adsb_ident(b"BAW 2276").unwrap_err(), AdsbIdentError::EmbeddedSpace);
+/// ```
+pub fn parse_adsb_ident(field: &[u8; 8]) -> Result<AdsbIdent, AdsbIdentError> {
+    if let Some(&byte) = field
+        .iter()
+        .find(|&&byte| !(byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b' '))
+    {
+        return Err(AdsbIdentError::IllegalCharacter(byte));
+    }
+
+    let len = field.iter().rposition(|&byte| byte != b' ').map_or(0, |last| last + 1);
+    if len == 0 {
+        return Err(AdsbIdentError::Empty);
+    }
+    if field[..len].contains(&b' ') {
+        return Err(AdsbIdentError::EmbeddedSpace);
+    }
+    // Only ASCII letters and digits remain
+    let ident = String::from_utf8_lossy(&field[..len]).into_owned();
+
+    match airlines::parse_flight_callsign(&ident) {
+        Ok(flight) => return Ok(AdsbIdent::Flight(flight)),
+        Err(FlightCallsignError::UnknownAirline(_)) => return Ok(AdsbIdent::UnlistedFlight(ident)),
+        Err(FlightCallsignError::InvalidFormat) => {}
+    }
+
+    Ok(match Parser::new().parse(&ident, false, false) {
+        Some(entity) => AdsbIdent::Registration(entity),
+        None => AdsbIdent::Unknown(ident),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(field: &[u8; 8]) -> Option<String> {
+        match parse_adsb_ident(field).unwrap() {
+            AdsbIdent::Registration(entity) => Some(entity.canonical_callsign().clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn registrations() {
+        assert_eq!(registration(b"DABYA   ").as_deref(), Some("D-ABYA"));
+        assert_eq!(registration(b"GEUUA   ").as_deref(), Some("G-EUUA"));
+        assert_eq!(registration(b"N628TS  ").as_deref(), Some("N628TS"));
+        assert_eq!(registration(b"VHOQA   ").as_deref(), Some("VH-OQA"));
+    }
+
+    #[test]
+    fn flights() {
+        match parse_adsb_ident(b"KLM1234 ").unwrap() {
+            AdsbIdent::Flight(flight) => {
+                assert_eq!(flight.airline.icao, "KLM");
+                assert_eq!(flight.flight_number, "1234");
+            }
+            other => panic!("{:?}", other),
+        }
+        let unlisted = parse_adsb_ident(b"XYZ123  ").unwrap();
+        assert!(matches!(&unlisted, AdsbIdent::UnlistedFlight(ident) if ident == "XYZ123"));
+        assert_eq!(unlisted.ident(), "XYZ123");
+        assert_eq!(parse_adsb_ident(b"DABYA   ").unwrap().ident(), "DABYA");
+    }
+
+    #[test]
+    fn rejects_malformed_fields() {
+        let error = |field: &[u8; 8]| parse_adsb_ident(field).unwrap_err();
+        assert_eq!(error(b"        "), AdsbIdentError::Empty);
+        assert_eq!(error(b" DABYA  "), AdsbIdentError::EmbeddedSpace);
+        assert_eq!(error(b"dabya   "), AdsbIdentError::IllegalCharacter(b'd'));
+        assert_eq!(error(b"D-ABYA  "), AdsbIdentError::IllegalCharacter(b'-'));
+        assert_eq!(error(b"DABYA\0\0\0"), AdsbIdentError::IllegalCharacter(0));
+    }
+}
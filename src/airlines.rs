@@ -0,0 +1,236 @@
+//! Airline Designators
+//!
+//! Maps ICAO three-letter and IATA two-character airline designators and
+//! radiotelephony callsigns ("SPEEDBIRD") to the operator's name and country, and
+//! splits transmitted flight callsigns such as "BAW2276" into operator and flight
+//! number. The table is generated by `build.rs` from `data/airlines.csv`.
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// An airline and its designators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Airline {
+    /// ICAO three-letter designator, e.g. `"DLH"`
+    pub icao: &'static str,
+    /// IATA two-character designator, e.g. `"LH"`, if the airline has one
+    pub iata: Option<&'static str>,
+    /// Name of the airline
+    pub name: &'static str,
+    /// ISO 3166-1 alpha-2 code of the country the airline is based in
+    pub iso2: &'static str,
+    /// Radiotelephony callsign, e.g. `"SPEEDBIRD"` for British Airways
+    pub callsign: &'static str,
+}
+
+// Airlines generated by `build.rs` from `data/airlines.csv`, sorted by ICAO
+// designator.
+include!(concat!(env!("OUT_DIR"), "/airlines.rs"));
+
+/// Look up an airline by its ICAO three-letter designator (case-insensitive).
+///
+/// # Examples
+/// ```
+/// use flydent::airlines::lookup_icao;
+///
+/// let airline = lookup_icao("DLH").unwrap();
+/// assert_eq!(airline.name, "Lufthansa");
+/// assert_eq!(airline.iso2, "DE");
+/// assert_eq!(airline.callsign, "LUFTHANSA");
+/// assert!(lookup_icao("ZZZ").is_none());
+/// ```
+pub fn lookup_icao(designator: &str) -> Option<&'static Airline> {
+    let designator = designator.trim().to_ascii_uppercase();
+    AIRLINES
+        .binary_search_by(|airline| airline.icao.cmp(&designator))
+        .ok()
+        .map(|idx| &AIRLINES[idx])
+}
+
+/// Look up an airline by its IATA two-character designator (case-insensitive).
+///
+/// # Examples
+/// ```
+/// use flydent::airlines::lookup_iata;
+///
+/// assert_eq!(lookup_iata("LH").unwrap().icao, "DLH");
+/// assert_eq!(lookup_iata("6e").unwrap().name, "IndiGo");
+/// ```
+pub fn lookup_iata(designator: &str) -> Option<&'static Airline> {
+    let designator = designator.trim();
+    AIRLINES
+        .iter()
+        .find(|airline| airline.iata.is_some_and(|iata| iata.eq_ignore_ascii_case(designator)))
+}
+
+/// Look up an airline by its radiotelephony callsign, e.g. `"SPEEDBIRD"`.
+///
+/// Matching ignores case, spaces and hyphens, so spoken or transcribed forms such
+/// as "Speedbird" or "Bee Line" are found.
+///
+/// # Examples
+/// ```
+/// use flydent::airlines::lookup_telephony;
+///
+/// assert_eq!(lookup_telephony("Speedbird").unwrap().icao, "BAW");
+/// assert_eq!(lookup_telephony("bee line").unwrap().iata, Some("SN"));
+/// assert!(lookup_telephony("NOBODY").is_none());
+/// ```
+pub fn lookup_telephony(callsign: &str) -> Option<&'static Airline> {
+    let wanted = telephony_key(callsign);
+    if wanted.is_empty() {
+        return None;
+    }
+    AIRLINES.iter().find(|airline| telephony_key(airline.callsign) == wanted)
+}
+
+/// Uppercase a telephony callsign and drop spaces and hyphens.
+fn telephony_key(callsign: &str) -> String {
+    callsign
+        .chars()
+        .filter(|&c| c != ' ' && c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// All airlines in the table, sorted by ICAO designator.
+pub fn airlines() -> &'static [Airline] {
+    AIRLINES
+}
+
+/// A flight callsign split into its operator and flight number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlightCallsign {
+    /// The operating airline
+    pub airline: &'static Airline,
+    /// Flight number and suffix following the designator, e.g. `"2276"` or `"56K"`
+    pub flight_number: String,
+}
+
+/// Error returned when a callsign is not a flight callsign of a known airline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum FlightCallsignError {
+    /// Not three letters followed by one to four characters starting with a
+    /// digit; registrations such as "N8437D" or "DAIMA" fail this way
+    InvalidFormat,
+    /// Well-formed, but the designator is not in the airline table
+    UnknownAirline(String),
+}
+
+impl fmt::Display for FlightCallsignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlightCallsignError::InvalidFormat => write!(f, "not a flight callsign"),
+            FlightCallsignError::UnknownAirline(designator) => {
+                write!(f, "unknown airline designator: {}", designator)
+            }
+        }
+    }
+}
+
+impl core::error::Error for FlightCallsignError {}
+
+/// Split a transmitted flight callsign into the operator's ICAO designator and
+/// the flight number, and resolve the operator in the airline table.
+///
+/// The callsign must be three letters followed by one to four letters or digits,
+/// the first of which is a digit. Case and surrounding whitespace are ignored.
+///
+/// # Examples
+/// ```
+/// use flydent::airlines::{parse_flight_callsign, FlightCallsignError};
+///
+/// let flight = parse_flight_callsign("BAW2276").unwrap();
+/// assert_eq!(flight.airline.name, "British Airways");
+/// assert_eq!(flight.flight_number, "2276");
+///
+/// assert_eq!(parse_flight_callsign("N8437D"), Err(FlightCallsignError::InvalidFormat));
+/// assert_eq!(
+///     parse_flight_callsign("XYZ123"),
+///     Err(FlightCallsignError::UnknownAirline("XYZ".to_string()))
+/// );
+/// ```
+pub fn parse_flight_callsign(callsign: &str) -> Result<FlightCallsign, FlightCallsignError> {
+    let callsign = callsign.trim().to_ascii_uppercase();
+    if !callsign.is_ascii() || !(4..=7).contains(&callsign.len()) {
+        return Err(FlightCallsignError::InvalidFormat);
+    }
+
+    let (designator, flight_number) = callsign.split_at(3);
+    let well_formed = designator.chars().all(|c| c.is_ascii_uppercase())
+        && flight_number.starts_with(|c: char| c.is_ascii_digit())
+        && flight_number.chars().all(|c| c.is_ascii_alphanumeric());
+    if !well_formed {
+        return Err(FlightCallsignError::InvalidFormat);
+    }
+
+    let airline = lookup_icao(designator)
+        .ok_or_else(|| FlightCallsignError::UnknownAirline(designator.to_string()))?;
+    Ok(FlightCallsign {
+        airline,
+        flight_number: flight_number.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_by_icao() {
+        assert!(AIRLINES.windows(2).all(|pair| pair[0].icao < pair[1].icao));
+    }
+
+    #[test]
+    fn lookups_agree() {
+        for airline in airlines() {
+            assert_eq!(lookup_icao(airline.icao), Some(airline));
+            if let Some(iata) = airline.iata {
+                assert_eq!(lookup_iata(iata), Some(airline));
+            }
+            assert_eq!(lookup_telephony(airline.callsign), Some(airline));
+        }
+    }
+
+    #[test]
+    fn known_airlines() {
+        let baw = lookup_icao("baw").unwrap();
+        assert_eq!(baw.iata, Some("BA"));
+        assert_eq!(baw.iso2, "GB");
+        assert_eq!(baw.callsign, "SPEEDBIRD");
+
+        assert_eq!(lookup_iata("AA").unwrap().icao, "AAL");
+        assert_eq!(lookup_icao(" UAE ").unwrap().name, "Emirates");
+        assert!(lookup_icao("").is_none());
+        assert!(lookup_iata("ZZ").is_none());
+    }
+
+    #[test]
+    fn flight_callsigns() {
+        let cases = [("BAW2276", "BAW", "2276"), ("ezy83vr", "EZY", "83VR"), (" DLH4 ", "DLH", "4"), ("UAL1", "UAL", "1")];
+        for (callsign, designator, flight_number) in cases {
+            let flight = parse_flight_callsign(callsign).unwrap();
+            assert_eq!(flight.airline.icao, designator, "{}", callsign);
+            assert_eq!(flight.flight_number, flight_number, "{}", callsign);
+        }
+
+        for callsign in ["", "BAW", "BAW12345", "BAWK12", "BA2276", "DAIMA", "HL7630", "N8437D", "BAW-22", "BAWé1"] {
+            assert_eq!(parse_flight_callsign(callsign), Err(FlightCallsignError::InvalidFormat), "{}", callsign);
+        }
+        assert_eq!(
+            parse_flight_callsign("QQQ1").unwrap_err().to_string(),
+            "unknown airline designator: QQQ"
+        );
+    }
+
+    #[test]
+    fn telephony_lookup() {
+        assert_eq!(lookup_telephony("SPEEDBIRD").unwrap().name, "British Airways");
+        assert_eq!(lookup_telephony("Shamrock").unwrap().icao, "EIN");
+        assert_eq!(lookup_telephony("Frontier-Flight").unwrap().icao, "FFT");
+        assert_eq!(lookup_telephony("NORSHUTTLE").unwrap().icao, "NAX");
+        assert!(lookup_telephony("").is_none());
+        assert!(lookup_telephony(" - ").is_none());
+    }
+}
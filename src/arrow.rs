@@ -0,0 +1,366 @@
+//! Arrow Columnar API
+//!
+//! Enriches whole Arrow columns at once, for DataFusion and Polars pipelines
+//! that would otherwise cross into the library row by row. Enabled with the
+//! `arrow` feature. Addresses come as `UInt32Array`, hex addresses and
+//! registrations as string arrays (`StringArray` or `LargeStringArray`).
+//!
+//! Every function returns an array of the input's length. Null inputs, and
+//! inputs that cannot be classified or converted, yield nulls. `enrich` applies
+//! them to a column of a `RecordBatch`.
+
+use crate::icao::{self, Icao24};
+use crate::registration;
+use crate::{EntityResult, Parser};
+use arrow_array::{
+    Array, ArrayRef, GenericStringArray, LargeStringArray, OffsetSizeTrait, RecordBatch, StringArray, UInt32Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use std::sync::Arc;
+
+fn hex_addresses<O: OffsetSizeTrait>(hexes: &GenericStringArray<O>) -> Vec<Option<u32>> {
+    hexes
+        .iter()
+        .map(|hex| Icao24::parse_hex(hex?).ok().map(Icao24::value))
+        .collect()
+}
+
+/// Addresses of a column with nulls replaced by a value no lookup matches.
+fn addresses_or_invalid(addresses: &UInt32Array) -> Vec<u32> {
+    addresses
+        .iter()
+        .map(|address| address.unwrap_or(u32::MAX))
+        .collect()
+}
+
+/// ISO2 code of the country each ICAO address is allocated to.
+///
+/// # Examples
+/// ```
+/// use arrow_array::{Array, UInt32Array};
+/// use flydent::arrow::countries_of_addresses;
+///
+/// let countries = countries_of_addresses(&UInt32Array::from(vec![Some(0xAB8E4F), None, Some(0xF00000)]));
+/// assert_eq!(countries.value(0), "US");
+/// assert!(countries.is_null(1) && countries.is_null(2));
+/// ```
+pub fn countries_of_addresses(addresses: &UInt32Array) -> StringArray {
+    icao::classify_many(&addresses_or_invalid(addresses)).into_iter().collect()
+}
+
+/// ISO2 code of the country each hex ICAO address is allocated to, in any
+/// notation `Icao24::parse_hex` accepts.
+pub fn countries_of_hexes<O: OffsetSizeTrait>(hexes: &GenericStringArray<O>) -> StringArray {
+    hex_addresses(hexes)
+        .into_iter()
+        .map(|address| icao::icao_u32_to_country(address?))
+        .collect()
+}
+
+/// ISO2 code of the country of each registration. Registrations of
+/// international organizations yield nulls.
+///
+/// # Examples
+/// ```
+/// use arrow_array::{Array, StringArray};
+/// use flydent::arrow::countries_of_registrations;
+///
+/// let countries = countries_of_registrations(&StringArray::from(vec!["PH-BHA", "4Y-ABC", "ZZZZZZZ"]));
+/// assert_eq!(countries.value(0), "NL");
+/// assert!(countries.is_null(1) && countries.is_null(2));
+/// ```
+pub fn countries_of_registrations<O: OffsetSizeTrait>(registrations: &GenericStringArray<O>) -> StringArray {
+    let parser = Parser::new();
+    registrations
+        .iter()
+        .map(|registration| match parser.parse_simple(registration?)? {
+            EntityResult::Country { iso2, .. } => Some(iso2),
+            EntityResult::Organization { .. } => None,
+        })
+        .collect()
+}
+
+/// Registration derived from each ICAO address, for countries with an
+/// algorithmic mapping.
+///
+/// # Examples
+/// ```
+/// use arrow_array::UInt32Array;
+/// use flydent::arrow::registrations_of_addresses;
+///
+/// let registrations = registrations_of_addresses(&UInt32Array::from(vec![0xAB8E4F, 0x3C65A1]));
+/// assert_eq!(registrations.value(1), "D-AIMA");
+/// ```
+pub fn registrations_of_addresses(addresses: &UInt32Array) -> StringArray {
+    registration::registrations_for(&addresses_or_invalid(addresses)).into_iter().collect()
+}
+
+/// Registration derived from each hex ICAO address.
+pub fn registrations_of_hexes<O: OffsetSizeTrait>(hexes: &GenericStringArray<O>) -> StringArray {
+    let addresses: Vec<u32> = hex_addresses(hexes)
+        .into_iter()
+        .map(|address| address.unwrap_or(u32::MAX))
+        .collect();
+    registration::registrations_for(&addresses).into_iter().collect()
+}
+
+/// ICAO address derived from each registration, for countries with an
+/// algorithmic mapping.
+///
+/// # Examples
+/// ```
+/// use arrow_array::{Array, StringArray};
+/// use flydent::arrow::addresses_of_registrations;
+///
+/// let addresses = addresses_of_registrations(&StringArray::from(vec![Some("N8437D"), None, Some("G-EUPT")]));
+/// assert_eq!(addresses.value(0), 0xAB8E4F);
+/// assert!(addresses.is_null(1) && addresses.is_null(2));
+/// ```
+pub fn addresses_of_registrations<O: OffsetSizeTrait>(registrations: &GenericStringArray<O>) -> UInt32Array {
+    registrations
+        .iter()
+        .map(|registration| {
+            let bytes = registration::registration_to_icao(registration?).ok()?;
+            Some(Icao24::from_bytes(bytes).value())
+        })
+        .collect()
+}
+
+/// What the identifier column passed to `enrich` holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierKind {
+    /// ICAO addresses, as `UInt32` or hex strings
+    Address,
+    /// Registrations, as strings
+    Registration,
+}
+
+impl IdentifierKind {
+    /// Names of the columns `enrich` appends, in order.
+    pub fn added_columns(&self) -> [&'static str; 3] {
+        match self {
+            IdentifierKind::Address => ["iso2", "country", "registration"],
+            IdentifierKind::Registration => ["iso2", "country", "hex"],
+        }
+    }
+}
+
+/// Schema of the batches `enrich` returns for batches of `schema`: the same
+/// fields followed by the nullable string columns of `kind.added_columns()`.
+///
+/// Fails if `column` is missing or has an unsupported type, or if `schema`
+/// already has a field named like an added column.
+pub fn enriched_schema(schema: &Schema, column: &str, kind: IdentifierKind) -> Result<Schema, ArrowError> {
+    let data_type = schema.field_with_name(column)?.data_type();
+    let supported = match kind {
+        IdentifierKind::Address => matches!(data_type, DataType::UInt32 | DataType::Utf8 | DataType::LargeUtf8),
+        IdentifierKind::Registration => matches!(data_type, DataType::Utf8 | DataType::LargeUtf8),
+    };
+    if !supported {
+        return Err(ArrowError::SchemaError(format!(
+            "column {:?} has unsupported type {} for {:?}",
+            column, data_type, kind
+        )));
+    }
+
+    let mut fields: Vec<Field> = schema.fields().iter().map(|field| field.as_ref().clone()).collect();
+    for name in kind.added_columns() {
+        if schema.field_with_name(name).is_ok() {
+            return Err(ArrowError::SchemaError(format!("column {:?} already exists", name)));
+        }
+        fields.push(Field::new(name, DataType::Utf8, true));
+    }
+    Ok(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+fn strings(array: &dyn Array) -> Vec<Option<&str>> {
+    if let Some(strings) = array.as_any().downcast_ref::<StringArray>() {
+        strings.iter().collect()
+    } else if let Some(strings) = array.as_any().downcast_ref::<LargeStringArray>() {
+        strings.iter().collect()
+    } else {
+        vec![None; array.len()]
+    }
+}
+
+/// Append the country and the converted identifier of each row of `column`
+/// to `batch`, as described by `enriched_schema`.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use arrow_array::{Array, RecordBatch, StringArray};
+/// use flydent::arrow::{enrich, IdentifierKind};
+///
+/// let hexes = StringArray::from(vec![Some("3C65A1"), Some("F00000"), None]);
+/// let batch = RecordBatch::try_from_iter([("hex", Arc::new(hexes) as _)]).unwrap();
+/// let enriched = enrich(&batch, "hex", IdentifierKind::Address).unwrap();
+///
+/// let registrations = enriched.column_by_name("registration").unwrap();
+/// let registrations = registrations.as_any().downcast_ref::<StringArray>().unwrap();
+/// assert_eq!(registrations.value(0), "D-AIMA");
+/// assert!(registrations.is_null(1) && registrations.is_null(2));
+/// ```
+pub fn enrich(batch: &RecordBatch, column: &str, kind: IdentifierKind) -> Result<RecordBatch, ArrowError> {
+    let schema = enriched_schema(&batch.schema(), column, kind)?;
+    let values = batch
+        .column_by_name(column)
+        .ok_or_else(|| ArrowError::SchemaError(format!("missing column {:?}", column)))?;
+
+    let added: [StringArray; 3] = match kind {
+        IdentifierKind::Address => {
+            let addresses: Vec<u32> = match values.as_any().downcast_ref::<UInt32Array>() {
+                Some(addresses) => addresses_or_invalid(addresses),
+                None => strings(values)
+                    .into_iter()
+                    .map(|hex| hex.and_then(|hex| Icao24::parse_hex(hex).ok()).map_or(u32::MAX, Icao24::value))
+                    .collect(),
+            };
+            let blocks: Vec<_> = addresses
+                .iter()
+                .map(|&address| icao::lookup(address).filter(|block| !block.is_special()))
+                .collect();
+            [
+                blocks.iter().map(|block| block.and_then(|block| block.iso2)).collect(),
+                blocks.iter().map(|block| block.map(|block| block.name)).collect(),
+                registration::registrations_for(&addresses).into_iter().collect(),
+            ]
+        }
+        IdentifierKind::Registration => {
+            let parser = Parser::new();
+            let registrations = strings(values);
+            let entities: Vec<_> = registrations
+                .iter()
+                .map(|registration| parser.parse_simple((*registration)?))
+                .collect();
+            [
+                entities
+                    .iter()
+                    .map(|entity| match entity {
+                        Some(EntityResult::Country { iso2, .. }) => Some(iso2.as_str()),
+                        _ => None,
+                    })
+                    .collect(),
+                entities
+                    .iter()
+                    .map(|entity| match entity {
+                        Some(EntityResult::Country { nation: name, .. } | EntityResult::Organization { name, .. }) => {
+                            Some(name.as_str())
+                        }
+                        None => None,
+                    })
+                    .collect(),
+                registrations
+                    .iter()
+                    .map(|registration| {
+                        let bytes = registration::registration_to_icao((*registration)?).ok()?;
+                        Some(Icao24::from_bytes(bytes).to_string())
+                    })
+                    .collect(),
+            ]
+        }
+    };
+
+    let mut columns = batch.columns().to_vec();
+    columns.extend(added.into_iter().map(|array| Arc::new(array) as ArrayRef));
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(array: &StringArray) -> Vec<Option<&str>> {
+        array.iter().collect()
+    }
+
+    #[test]
+    fn address_columns() {
+        let addresses = UInt32Array::from(vec![Some(0xAB8E4F), Some(0x3C65A1), None, Some(0x1000000), Some(0x394400)]);
+        assert_eq!(
+            values(&countries_of_addresses(&addresses)),
+            vec![Some("US"), Some("DE"), None, None, Some("FR")]
+        );
+        assert_eq!(
+            values(&registrations_of_addresses(&addresses)),
+            vec![Some("N8437D"), Some("D-AIMA"), None, None, None]
+        );
+    }
+
+    #[test]
+    fn hex_columns() {
+        let hexes = StringArray::from(vec![Some("ab8e4f"), Some("3C:65:A1"), None, Some("XYZ")]);
+        assert_eq!(values(&countries_of_hexes(&hexes)), vec![Some("US"), Some("DE"), None, None]);
+        assert_eq!(
+            values(&registrations_of_hexes(&hexes)),
+            vec![Some("N8437D"), Some("D-AIMA"), None, None]
+        );
+    }
+
+    #[test]
+    fn registration_columns() {
+        let registrations = LargeStringArray::from(vec![Some("N8437D"), Some("PH-BHA"), None]);
+        assert_eq!(values(&countries_of_registrations(&registrations)), vec![Some("US"), Some("NL"), None]);
+        let addresses = addresses_of_registrations(&registrations);
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses.value(0), 0xAB8E4F);
+        assert!(addresses.is_null(1) && addresses.is_null(2));
+    }
+
+    #[test]
+    fn columns_match_row_lookups() {
+        let addresses: Vec<u32> = (0..=0xFFFFFF).step_by(0x1357).collect();
+        let column = UInt32Array::from(addresses.clone());
+        let countries = countries_of_addresses(&column);
+        let registrations = registrations_of_addresses(&column);
+        for (index, &address) in addresses.iter().enumerate() {
+            assert_eq!(countries.is_valid(index).then(|| countries.value(index)), icao::icao_u32_to_country(address));
+            let expected = registration::icao_to_registration(Icao24::new(address).unwrap().to_bytes()).ok();
+            assert_eq!(registrations.is_valid(index).then(|| registrations.value(index).to_string()), expected);
+        }
+    }
+
+    fn column<'a>(batch: &'a RecordBatch, name: &str) -> Vec<Option<&'a str>> {
+        strings(batch.column_by_name(name).unwrap())
+    }
+
+    #[test]
+    fn enrich_addresses() {
+        let addresses = UInt32Array::from(vec![Some(0xAB8E4F), Some(0x394400), Some(0xF00000), None]);
+        let batch = RecordBatch::try_from_iter([("icao", Arc::new(addresses) as ArrayRef)]).unwrap();
+        let enriched = enrich(&batch, "icao", IdentifierKind::Address).unwrap();
+        assert_eq!(enriched.num_columns(), 4);
+        assert_eq!(column(&enriched, "iso2"), vec![Some("US"), Some("FR"), None, None]);
+        assert_eq!(column(&enriched, "country"), vec![Some("United States"), Some("France"), None, None]);
+        assert_eq!(column(&enriched, "registration"), vec![Some("N8437D"), None, None, None]);
+    }
+
+    #[test]
+    fn enrich_registrations() {
+        let registrations = LargeStringArray::from(vec![Some("D-AIMA"), Some("4Y-ABC"), Some("ZZZZZZZ"), None]);
+        let batch = RecordBatch::try_from_iter([("reg", Arc::new(registrations) as ArrayRef)]).unwrap();
+        let enriched = enrich(&batch, "reg", IdentifierKind::Registration).unwrap();
+        assert_eq!(column(&enriched, "iso2"), vec![Some("DE"), None, None, None]);
+        assert_eq!(
+            column(&enriched, "country"),
+            vec![Some("Germany"), Some("International Civil Aviation Organization"), None, None]
+        );
+        assert_eq!(column(&enriched, "hex"), vec![Some("3C65A1"), None, None, None]);
+    }
+
+    #[test]
+    fn enriched_schema_errors() {
+        let schema = Schema::new(vec![
+            Field::new("hex", DataType::Utf8, true),
+            Field::new("tail", DataType::Utf8, true),
+            Field::new("count", DataType::Int64, false),
+        ]);
+        assert!(enriched_schema(&schema, "missing", IdentifierKind::Address).is_err());
+        assert!(enriched_schema(&schema, "count", IdentifierKind::Address).is_err());
+        // Would add a second "hex" column
+        assert!(enriched_schema(&schema, "tail", IdentifierKind::Registration).is_err());
+        let enriched = enriched_schema(&schema, "hex", IdentifierKind::Address).unwrap();
+        assert_eq!(enriched.fields().len(), 6);
+        assert_eq!(enriched.field(5).name(), "registration");
+    }
+}
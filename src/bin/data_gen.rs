@@ -0,0 +1,509 @@
+//! Regenerate the processed ITU tables embedded by flydent.
+//!
+//! Inputs, all in the data directory:
+//!
+//! - `processed_itu_countries.csv` and `processed_itu_organizations.csv`: the
+//!   ITU call sign series table (holder, description, prefixes, suffix blocks).
+//! - `icao-allocations.csv`: the ICAO 24-bit address allocations.
+//! - `processed_itu_countries_regex.csv` and
+//!   `processed_itu_organizations_regex.csv`: the current output. The regex and
+//!   priority of a row whose prefixes and suffix blocks are unchanged are
+//!   carried over, since many were tuned by hand; new or changed rows get a
+//!   regex derived from their suffix blocks.
+//!
+//! The ICAO columns of general country rows are always recomputed from the
+//! allocation table. Every run validates the result and prints a summary of
+//! what differs from the current output.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const COUNTRIES_CSV: &str = "processed_itu_countries.csv";
+const ORGANIZATIONS_CSV: &str = "processed_itu_organizations.csv";
+const COUNTRIES_REGEX_CSV: &str = "processed_itu_countries_regex.csv";
+const ORGANIZATIONS_REGEX_CSV: &str = "processed_itu_organizations_regex.csv";
+const ALLOCATIONS_CSV: &str = "icao-allocations.csv";
+
+const COUNTRIES_HEADER: &str =
+    "nation,description,priority,iso codes,callsign,suffix,regex,icao24bit_from,icao24bit_to,icao24bit_prefix";
+const ORGANIZATIONS_HEADER: &str =
+    "name,description,priority,callsign,suffix,regex,icao24bit_from,icao24bit_to,icao24bit_prefix";
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} [--data <DIR>] [--check | --write]", program);
+    eprintln!();
+    eprintln!("Rebuilds the processed ITU regex tables and prints what changed.");
+    eprintln!("    --data <DIR>   Data directory (default: data)");
+    eprintln!("    --check        Exit with status 1 if the tables are out of date");
+    eprintln!("    --write        Overwrite the tables with the regenerated rows");
+    process::exit(1);
+}
+
+/// One row of a regex table. Organizations have no ISO codes.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    name: String,
+    description: String,
+    priority: String,
+    iso_codes: Option<Vec<String>>,
+    callsigns: Vec<String>,
+    suffixes: Vec<String>,
+    regex: String,
+    icao24bit_from: String,
+    icao24bit_to: String,
+    icao24bit_prefixes: Vec<String>,
+}
+
+impl Row {
+    /// Identity of a row across versions.
+    fn key(&self) -> (String, String, Vec<String>) {
+        (self.name.clone(), self.description.clone(), self.callsigns.clone())
+    }
+
+    fn is_general(&self) -> bool {
+        self.description == "general"
+    }
+
+    fn to_csv(&self) -> String {
+        let mut fields = vec![self.name.clone(), self.description.clone(), self.priority.clone()];
+        if let Some(iso_codes) = &self.iso_codes {
+            fields.push(python_list(iso_codes));
+        }
+        fields.extend([
+            python_list(&self.callsigns),
+            python_list(&self.suffixes),
+            self.regex.clone(),
+            self.icao24bit_from.clone(),
+            self.icao24bit_to.clone(),
+            python_list(&self.icao24bit_prefixes),
+        ]);
+        fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+    }
+
+    /// Names of the fields that differ from `other`.
+    fn changed_fields(&self, other: &Row) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.priority != other.priority {
+            changed.push("priority");
+        }
+        if self.iso_codes != other.iso_codes {
+            changed.push("iso codes");
+        }
+        if self.suffixes != other.suffixes {
+            changed.push("suffix");
+        }
+        if self.regex != other.regex {
+            changed.push("regex");
+        }
+        if self.icao24bit_from != other.icao24bit_from || self.icao24bit_to != other.icao24bit_to {
+            changed.push("icao24bit range");
+        }
+        if self.icao24bit_prefixes != other.icao24bit_prefixes {
+            changed.push("icao24bit_prefix");
+        }
+        changed
+    }
+}
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current_field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                if in_quotes && chars.peek() == Some(&'"') {
+                    current_field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = !in_quotes;
+                }
+            }
+            ',' if !in_quotes => {
+                fields.push(current_field.trim().to_string());
+                current_field.clear();
+            }
+            _ => current_field.push(ch),
+        }
+    }
+    fields.push(current_field.trim().to_string());
+    fields
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Parse a Python list literal such as `['PA', 'PB']`.
+fn parse_python_list(field: &str) -> Vec<String> {
+    field
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|item| item.trim().trim_matches('\'').to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Format a Python list literal; an empty list is an empty field.
+fn python_list(items: &[String]) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    let quoted: Vec<String> = items.iter().map(|item| format!("'{}'", item)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// Read the data lines of a CSV file, with their 1-based line numbers.
+fn read_csv(path: &Path) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let csv = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(csv
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_no, line)| (line_no + 1, parse_csv_line(line)))
+        .collect())
+}
+
+/// Read a regex table; `countries` selects the column layout.
+fn read_regex_table(path: &Path, countries: bool) -> Result<Vec<Row>, String> {
+    let columns = if countries { 10 } else { 9 };
+    read_csv(path)?
+        .into_iter()
+        .map(|(line_no, fields)| {
+            if fields.len() != columns {
+                return Err(format!("{}:{}: expected {} columns", path.display(), line_no, columns));
+            }
+            let mut fields = fields.into_iter();
+            let mut next = || fields.next().unwrap();
+            Ok(Row {
+                name: next(),
+                description: next(),
+                priority: next(),
+                iso_codes: countries.then(|| parse_python_list(&next())),
+                callsigns: parse_python_list(&next()),
+                suffixes: parse_python_list(&next()),
+                regex: next(),
+                icao24bit_from: next(),
+                icao24bit_to: next(),
+                icao24bit_prefixes: parse_python_list(&next()),
+            })
+        })
+        .collect()
+}
+
+/// Read the ITU series table into rows with empty derived columns.
+fn read_itu_table(path: &Path, countries: bool) -> Result<Vec<(usize, Row)>, String> {
+    let columns = if countries { 5 } else { 4 };
+    read_csv(path)?
+        .into_iter()
+        .map(|(line_no, fields)| {
+            if fields.len() != columns {
+                return Err(format!("{}:{}: expected {} columns", path.display(), line_no, columns));
+            }
+            let mut fields = fields.into_iter();
+            let mut next = || fields.next().unwrap();
+            let row = Row {
+                name: next(),
+                description: next(),
+                priority: String::new(),
+                iso_codes: countries.then(|| parse_python_list(&next())),
+                callsigns: parse_python_list(&next()),
+                suffixes: parse_python_list(&next()),
+                regex: String::new(),
+                icao24bit_from: String::new(),
+                icao24bit_to: String::new(),
+                icao24bit_prefixes: Vec::new(),
+            };
+            Ok((line_no, row))
+        })
+        .collect()
+}
+
+/// Address ranges allocated to each ISO2 code.
+fn read_allocations(path: &Path) -> Result<HashMap<String, Vec<(u32, u32)>>, String> {
+    let mut allocations: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+    for (line_no, fields) in read_csv(path)? {
+        let (prefix, iso2) = match fields.as_slice() {
+            [prefix, iso2, ..] => (prefix, iso2),
+            _ => return Err(format!("{}:{}: expected prefix,iso2,...", path.display(), line_no)),
+        };
+        if iso2.is_empty() {
+            continue;
+        }
+        if prefix.is_empty() || prefix.len() > 24 || !prefix.chars().all(|c| c == '0' || c == '1') {
+            return Err(format!("{}:{}: invalid binary prefix {:?}", path.display(), line_no, prefix));
+        }
+        let bits = prefix.len() as u32;
+        let start = u32::from_str_radix(prefix, 2).unwrap() << (24 - bits);
+        let end = start + ((1u32 << (24 - bits)) - 1);
+        allocations.entry(iso2.clone()).or_default().push((start, end));
+    }
+    for ranges in allocations.values_mut() {
+        ranges.sort();
+    }
+    Ok(allocations)
+}
+
+/// The hex prefixes exactly covering a range, using the fewest digits possible.
+fn hex_prefixes(start: u32, end: u32) -> Vec<String> {
+    for digits in 1..=6u32 {
+        let block = 16u32.pow(6 - digits);
+        if start.is_multiple_of(block) && (end + 1).is_multiple_of(block) {
+            return (start / block..(end + 1) / block)
+                .map(|index| format!("{:06X}", index * block)[..digits as usize].to_string())
+                .collect();
+        }
+    }
+    unreachable!("every range is a whole number of single addresses")
+}
+
+/// Character class of one suffix position, e.g. `[A-Z]` for `A`..`Z`.
+fn position_class(from: char, to: char) -> Option<String> {
+    if from == '*' || to == '*' {
+        return Some("[A-Z0-9]".to_string());
+    }
+    if from == to {
+        return Some(from.to_string());
+    }
+    let same_kind = (from.is_ascii_digit() && to.is_ascii_digit())
+        || (from.is_ascii_uppercase() && to.is_ascii_uppercase());
+    (same_kind && from < to).then(|| format!("[{}-{}]", from, to))
+}
+
+/// Regex for one suffix block such as `AAA-ZZZ`, `0001-9999` or `B**`.
+///
+/// Blocks whose bounds differ in length cannot be derived mechanically.
+fn suffix_regex(block: &str) -> Option<String> {
+    let (from, to) = match block.split_once('-') {
+        Some((from, to)) if !to.contains('-') => (from, to),
+        Some(_) => return None,
+        None => (block, block),
+    };
+    if from.is_empty() || from.len() != to.len() {
+        return None;
+    }
+
+    let classes: Vec<String> = from
+        .chars()
+        .zip(to.chars())
+        .map(|(from, to)| position_class(from, to))
+        .collect::<Option<_>>()?;
+
+    // Collapse runs of the same class: [A-Z][A-Z][A-Z] -> [A-Z]{3}
+    let mut regex = String::new();
+    let mut index = 0;
+    while index < classes.len() {
+        let run = classes[index..].iter().take_while(|class| **class == classes[index]).count();
+        regex.push_str(&classes[index]);
+        if run > 1 && classes[index].starts_with('[') {
+            write!(regex, "{{{}}}", run).unwrap();
+            index += run;
+        } else {
+            index += 1;
+        }
+    }
+    Some(regex)
+}
+
+/// Regex for a whole row. General rows also accept the loose 1-4 character
+/// suffixes seen in practice.
+fn row_regex(row: &Row) -> Option<String> {
+    let mut alternatives: Vec<String> = row.suffixes.iter().map(|block| suffix_regex(block)).collect::<Option<_>>()?;
+    if row.is_general() {
+        alternatives.push("[A-Z0-9]{1,4}".to_string());
+    }
+    Some(format!(
+        "^({})(-{{0,1}}({})){{0,1}}$",
+        row.callsigns.join("|"),
+        alternatives.join("|")
+    ))
+}
+
+/// Build a regex table from the ITU table, carrying over curated columns from
+/// the current table. Problems are appended to `errors`.
+fn generate(
+    source: &Path,
+    itu_rows: Vec<(usize, Row)>,
+    current: &[Row],
+    allocations: &HashMap<String, Vec<(u32, u32)>>,
+    errors: &mut Vec<String>,
+) -> Vec<Row> {
+    let mut curated: HashMap<_, &Row> = HashMap::new();
+    for row in current {
+        curated.entry((row.key(), row.suffixes.clone())).or_insert(row);
+    }
+
+    let mut rows = Vec::new();
+    for (line_no, mut row) in itu_rows {
+        let location = format!("{}:{}", source.display(), line_no);
+
+        if row.callsigns.is_empty() {
+            errors.push(format!("{}: no callsign prefixes", location));
+        }
+        if let Some(iso_codes) = &row.iso_codes {
+            let valid = matches!(iso_codes.as_slice(), [iso2, iso3]
+                if iso2.len() == 2 && iso3.len() == 3
+                    && iso2.chars().chain(iso3.chars()).all(|c| c.is_ascii_uppercase()));
+            if !valid {
+                errors.push(format!("{}: invalid ISO codes {:?}", location, iso_codes));
+            }
+        }
+
+        match curated.get(&(row.key(), row.suffixes.clone())) {
+            Some(previous) => {
+                row.priority = previous.priority.clone();
+                row.regex = previous.regex.clone();
+            }
+            None => {
+                row.priority = if row.is_general() { "0" } else { "1" }.to_string();
+                match row_regex(&row) {
+                    Some(regex) => row.regex = regex,
+                    None => errors.push(format!(
+                        "{}: cannot derive a regex for suffixes {:?}; add the row with a regex to the regex table",
+                        location, row.suffixes
+                    )),
+                }
+            }
+        }
+        if !row.regex.is_empty() {
+            if let Err(err) = Regex::new(&row.regex) {
+                errors.push(format!("{}: invalid regex {:?}: {}", location, row.regex, err));
+            }
+        }
+
+        let iso2 = row.iso_codes.as_ref().and_then(|codes| codes.first());
+        if let (true, Some(ranges)) = (row.is_general(), iso2.and_then(|iso2| allocations.get(iso2))) {
+            row.icao24bit_from = format!("{:06X}", ranges[0].0);
+            row.icao24bit_to = format!("{:06X}", ranges[ranges.len() - 1].1);
+            row.icao24bit_prefixes = ranges.iter().flat_map(|&(start, end)| hex_prefixes(start, end)).collect();
+        }
+
+        rows.push(row);
+    }
+    rows
+}
+
+/// Print the differences between two versions of a table; returns whether
+/// there are any.
+fn summarize(table: &str, current: &[Row], generated: &[Row]) -> bool {
+    let current_by_key: HashMap<_, _> = current.iter().map(|row| (row.key(), row)).collect();
+    let generated_by_key: HashMap<_, _> = generated.iter().map(|row| (row.key(), row)).collect();
+
+    let mut lines = Vec::new();
+    for row in generated {
+        match current_by_key.get(&row.key()) {
+            None => lines.push(format!("  + {} ({})", row.name, row.description)),
+            Some(previous) => {
+                let changed = row.changed_fields(previous);
+                if !changed.is_empty() {
+                    lines.push(format!("  ~ {} ({}): {}", row.name, row.description, changed.join(", ")));
+                }
+            }
+        }
+    }
+    for row in current.iter().filter(|row| !generated_by_key.contains_key(&row.key())) {
+        lines.push(format!("  - {} ({})", row.name, row.description));
+    }
+
+    let order_changed = lines.is_empty() && current != generated;
+    if lines.is_empty() && !order_changed {
+        println!("{}: up to date", table);
+        return false;
+    }
+    let count = |marker: &str| lines.iter().filter(|line| line.starts_with(marker)).count();
+    println!(
+        "{}: {} added, {} removed, {} changed{}",
+        table,
+        count("  +"),
+        count("  -"),
+        count("  ~"),
+        if order_changed { ", rows reordered" } else { "" }
+    );
+    for line in &lines {
+        println!("{}", line);
+    }
+    true
+}
+
+fn write_table(path: &Path, header: &str, rows: &[Row]) -> Result<(), String> {
+    let mut csv = format!("{}\n", header);
+    for row in rows {
+        csv.push_str(&row.to_csv());
+        csv.push('\n');
+    }
+    fs::write(path, csv).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn run(data: &Path, check: bool, write: bool) -> Result<bool, String> {
+    let allocations = read_allocations(&data.join(ALLOCATIONS_CSV))?;
+    let mut errors = Vec::new();
+    let mut outdated = false;
+
+    let tables = [
+        (COUNTRIES_CSV, COUNTRIES_REGEX_CSV, COUNTRIES_HEADER, true),
+        (ORGANIZATIONS_CSV, ORGANIZATIONS_REGEX_CSV, ORGANIZATIONS_HEADER, false),
+    ];
+    let mut generated_tables = Vec::new();
+    for (source, target, header, countries) in tables {
+        let source = data.join(source);
+        let target = data.join(target);
+        let current = read_regex_table(&target, countries)?;
+        let generated = generate(&source, read_itu_table(&source, countries)?, &current, &allocations, &mut errors);
+        outdated |= summarize(&target.display().to_string(), &current, &generated);
+        generated_tables.push((target, header, generated));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    if write {
+        for (target, header, rows) in &generated_tables {
+            write_table(target, header, rows)?;
+        }
+        println!("Wrote {} tables", generated_tables.len());
+    }
+    Ok(!(check && outdated))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut data = PathBuf::from("data");
+    let mut check = false;
+    let mut write = false;
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--data" => data = PathBuf::from(rest.next().unwrap_or_else(|| usage(&args[0]))),
+            "--check" => check = true,
+            "--write" => write = true,
+            _ => usage(&args[0]),
+        }
+    }
+    if check && write {
+        usage(&args[0]);
+    }
+
+    match run(&data, check, write) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    }
+}
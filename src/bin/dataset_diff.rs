@@ -0,0 +1,48 @@
+//! Compare two versions of the ITU and ICAO data, e.g. before upgrading
+//! flydent in an attribution pipeline.
+//!
+//! With one directory, the data built into this binary is compared against it.
+//! Like `diff`, the exit status is 0 without differences, 1 with differences
+//! and 2 on errors.
+
+use flydent::dataset::Dataset;
+use std::env;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} [<OLD_DIR>] <NEW_DIR>", program);
+    eprintln!();
+    eprintln!("Lists callsign prefixes and ICAO address blocks added (+), removed (-) or");
+    eprintln!("reassigned (~) between two data directories laid out like flydent's data/.");
+    eprintln!("Without <OLD_DIR>, the built-in data is the old version.");
+    process::exit(2);
+}
+
+fn load(dir: &str) -> Dataset {
+    Dataset::from_dir(dir).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(2);
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let (old, new) = match args.len() {
+        2 if !args[1].starts_with('-') => (Dataset::builtin(), load(&args[1])),
+        3 => (load(&args[1]), load(&args[2])),
+        _ => usage(&args[0]),
+    };
+
+    let diff = old.diff(&new);
+    if diff.is_empty() {
+        println!("No differences");
+        return;
+    }
+    print!("{}", diff);
+    println!(
+        "{} prefix changes, {} address block changes",
+        diff.prefixes.len(),
+        diff.ranges.len()
+    );
+    process::exit(1);
+}
@@ -0,0 +1,116 @@
+//! Export hex <-> registration mapping tables as CSV or JSON, for loading into
+//! databases or search indexes.
+//!
+//! Without `--country` or `--range`, every supported country is exported.
+
+use flydent::registration::{registrations_in, supported_countries};
+use std::env;
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [--json] [--country <ISO2>]... [--range <START>-<END>]...",
+        program
+    );
+    eprintln!();
+    eprintln!("Writes hex,registration,iso2 rows for every address that encodes a registration.");
+    eprintln!("    --json             Write a JSON array instead of CSV");
+    eprintln!("    --country <ISO2>   Export a supported country, e.g. DE");
+    eprintln!("    --range <S>-<E>    Export a hex address range, e.g. 3C0000-3FFFFF");
+    process::exit(1);
+}
+
+fn parse_range(arg: &str) -> Option<RangeInclusive<u32>> {
+    let (start, end) = arg.split_once('-')?;
+    let start = u32::from_str_radix(start, 16).ok()?;
+    let end = u32::from_str_radix(end, 16).ok()?;
+    (start <= end && end <= 0xFFFFFF).then_some(start..=end)
+}
+
+fn main() {
+    if let Err(err) = run() {
+        // A closed pipe (e.g. `| head`) is not an error worth reporting
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let countries = supported_countries();
+
+    let mut json = false;
+    let mut ranges = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--country" => {
+                let iso2 = rest.next().unwrap_or_else(|| usage(&args[0])).to_ascii_uppercase();
+                match countries.iter().find(|country| country.iso2 == iso2) {
+                    Some(country) => ranges.extend(country.ranges.iter().cloned()),
+                    None => {
+                        eprintln!("Error: no registration converter for {}", iso2);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--range" => {
+                let range = rest.next().unwrap_or_else(|| usage(&args[0]));
+                match parse_range(range) {
+                    Some(range) => ranges.push(range),
+                    None => {
+                        eprintln!("Error: invalid range {}", range);
+                        process::exit(1);
+                    }
+                }
+            }
+            _ => usage(&args[0]),
+        }
+    }
+    if ranges.is_empty() {
+        ranges = countries.iter().flat_map(|country| country.ranges.iter().cloned()).collect();
+    }
+    ranges.sort_by_key(|range| *range.start());
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if json {
+        write!(out, "[")?;
+    } else {
+        writeln!(out, "hex,registration,iso2")?;
+    }
+
+    let mut first = true;
+    let mut last_written = None;
+    for range in ranges {
+        for (icao, conversion) in registrations_in(range) {
+            // Overlapping ranges from the arguments must not repeat rows
+            if last_written.is_some_and(|last| icao <= last) {
+                continue;
+            }
+            last_written = Some(icao);
+
+            if json {
+                let row = serde_json::json!({
+                    "hex": format!("{:06X}", icao),
+                    "registration": conversion.value,
+                    "iso2": conversion.iso2,
+                });
+                write!(out, "{}\n{}", if first { "" } else { "," }, row)?;
+            } else {
+                writeln!(out, "{:06X},{},{}", icao, conversion.value, conversion.iso2)?;
+            }
+            first = false;
+        }
+    }
+
+    if json {
+        writeln!(out, "\n]")?;
+    }
+    out.flush()
+}
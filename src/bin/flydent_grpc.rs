@@ -0,0 +1,36 @@
+//! Serve the flydent gRPC service (`flydent::grpc`, `proto/flydent.proto`).
+
+use flydent::grpc;
+use std::env;
+use std::net::SocketAddr;
+use std::process;
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:50051";
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} [--listen <ADDR>]", program);
+    eprintln!();
+    eprintln!("Serves the flydent.v1.Flydent gRPC service (Identify, Convert, BatchClassify).");
+    eprintln!("    --listen <ADDR>   Address to listen on (default: {})", DEFAULT_LISTEN);
+    process::exit(2);
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    let listen = match args.len() {
+        1 => DEFAULT_LISTEN,
+        3 if args[1] == "--listen" => &args[2],
+        _ => usage(&args[0]),
+    };
+    let addr: SocketAddr = listen.parse().unwrap_or_else(|_| {
+        eprintln!("Error: invalid listen address {:?}", listen);
+        process::exit(2);
+    });
+
+    eprintln!("Listening on {}", addr);
+    if let Err(err) = grpc::serve(addr).await {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,63 @@
+//! Enrich a Parquet file with flydent lookups, e.g. for a one-off pass over a
+//! data lake table of flights.
+//!
+//! Reads every row group of the input, appends the columns described by
+//! `flydent::arrow::enrich` and writes the result to a new file.
+
+use flydent::arrow::{enrich, enriched_schema, IdentifierKind};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::process;
+use std::sync::Arc;
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} (--hex | --registration) <COLUMN> <INPUT> <OUTPUT>", program);
+    eprintln!();
+    eprintln!("Copies the Parquet file INPUT to OUTPUT with columns appended for COLUMN:");
+    eprintln!("    --hex <COLUMN>            ICAO addresses (UInt32 or hex strings):");
+    eprintln!("                              adds iso2, country and registration");
+    eprintln!("    --registration <COLUMN>   Registrations: adds iso2, country and hex");
+    process::exit(2);
+}
+
+fn run(column: &str, kind: IdentifierKind, input: &str, output: &str) -> Result<usize, Box<dyn Error>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(input)?)?;
+    let schema = Arc::new(enriched_schema(builder.schema(), column, kind)?);
+    let reader = builder.build()?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(File::create(output)?, schema, Some(properties))?;
+
+    let mut rows = 0;
+    for batch in reader {
+        let batch = batch?;
+        rows += batch.num_rows();
+        writer.write(&enrich(&batch, column, kind)?)?;
+    }
+    writer.close()?;
+    Ok(rows)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 5 {
+        usage(&args[0]);
+    }
+    let kind = match args[1].as_str() {
+        "--hex" => IdentifierKind::Address,
+        "--registration" => IdentifierKind::Registration,
+        _ => usage(&args[0]),
+    };
+
+    match run(&args[2], kind, &args[3], &args[4]) {
+        Ok(rows) => eprintln!("Wrote {} rows to {}", rows, args[4]),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    }
+}
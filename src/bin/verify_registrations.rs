@@ -0,0 +1,84 @@
+//! Exhaustively round-trip every address of every supported registration
+//! scheme through the converters and report mismatches.
+//!
+//! For each address that decodes to a registration, checks that:
+//! - the registration converts back to the same address
+//! - both directions report the same country
+//! - the country matches the ICAO allocation table
+//!
+//! Exits with status 1 if any check fails.
+
+use flydent::icao::Icao24;
+use flydent::registration::{
+    icao_to_registration_detailed, registration_to_icao_detailed, supported_countries,
+};
+use std::process;
+
+/// Stop printing individual mismatches for a country after this many.
+const MAX_REPORTED: usize = 20;
+
+/// Check one address. Returns whether it encodes a registration, or a description
+/// of what went wrong.
+fn verify(address: Icao24, iso2: &str) -> Result<bool, String> {
+    let forward = match icao_to_registration_detailed(address.to_bytes()) {
+        Ok(conversion) => conversion,
+        Err(_) => return Ok(false),
+    };
+    if forward.iso2 != iso2 {
+        return Err(format!("decoded as {} ({}), expected {}", forward.value, forward.iso2, iso2));
+    }
+    if address.country() != Some(iso2) {
+        return Err(format!("{} is allocated to {:?}", forward.value, address.country()));
+    }
+
+    let back = registration_to_icao_detailed(&forward.value)
+        .map_err(|err| format!("{} does not convert back: {}", forward.value, err))?;
+    let back_address = Icao24::from_bytes(back.value);
+    if back_address != address || back.iso2 != iso2 {
+        return Err(format!(
+            "{} converts back to {} ({})",
+            forward.value, back_address, back.iso2
+        ));
+    }
+    Ok(true)
+}
+
+fn main() {
+    let mut failed = false;
+
+    for country in supported_countries() {
+        if !(country.to_icao && country.to_registration) {
+            continue;
+        }
+
+        let mut checked = 0u32;
+        let mut converted = 0u32;
+        let mut mismatches = 0usize;
+        for range in &country.ranges {
+            for address in range.clone() {
+                checked += 1;
+                let address = Icao24::new(address).expect("scheme ranges are 24-bit");
+                match verify(address, country.iso2) {
+                    Ok(true) => converted += 1,
+                    Ok(false) => {}
+                    Err(message) => {
+                        mismatches += 1;
+                        if mismatches <= MAX_REPORTED {
+                            eprintln!("{} {}: {}", country.iso2, address, message);
+                        }
+                    }
+                }
+            }
+        }
+
+        println!(
+            "{}: {} addresses, {} registrations, {} mismatches",
+            country.iso2, checked, converted, mismatches
+        );
+        failed |= mismatches > 0;
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
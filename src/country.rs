@@ -0,0 +1,116 @@
+//! Country Metadata
+//!
+//! Flag emoji and English names for the ISO 3166-1 alpha-2 codes returned
+//! throughout flydent, so a UI can render "🇳🇱 Netherlands" without a second
+//! crate. Requires the `country-meta` feature. The name table is generated by
+//! `build.rs` from `data/countries.csv`.
+
+use alloc::string::String;
+use core::fmt;
+
+/// English names of a country or territory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Country {
+    /// ISO 3166-1 alpha-2 code, e.g. `"NL"`
+    pub iso2: &'static str,
+    /// Short name in common use, e.g. `"Netherlands"`
+    pub name: &'static str,
+    /// Official name, e.g. `"Kingdom of the Netherlands"`
+    pub official_name: &'static str,
+}
+
+impl Country {
+    /// Flag emoji, built from the regional indicator symbols of the ISO2 code.
+    pub fn flag(&self) -> String {
+        self.iso2
+            .chars()
+            .map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)).unwrap())
+            .collect()
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.flag(), self.name)
+    }
+}
+
+// Countries generated by `build.rs` from `data/countries.csv`, sorted by ISO2 code.
+include!(concat!(env!("OUT_DIR"), "/countries.rs"));
+
+/// Look up a country by ISO2 code, ignoring case.
+///
+/// # Examples
+/// ```
+/// use flydent::country::lookup;
+///
+/// let netherlands = lookup("nl").unwrap();
+/// assert_eq!(netherlands.name, "Netherlands");
+/// assert_eq!(netherlands.official_name, "Kingdom of the Netherlands");
+/// assert_eq!(netherlands.to_string(), "🇳🇱 Netherlands");
+/// ```
+pub fn lookup(iso2: &str) -> Option<&'static Country> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    COUNTRIES
+        .binary_search_by(|country| country.iso2.cmp(iso2.as_str()))
+        .ok()
+        .map(|index| &COUNTRIES[index])
+}
+
+/// Flag emoji for an ISO2 code, e.g. `"🇺🇸"` for `"US"`.
+pub fn flag(iso2: &str) -> Option<String> {
+    lookup(iso2).map(Country::flag)
+}
+
+/// English short name for an ISO2 code, e.g. `"United States"` for `"US"`.
+pub fn name(iso2: &str) -> Option<&'static str> {
+    lookup(iso2).map(|country| country.name)
+}
+
+/// English official name for an ISO2 code, e.g. `"United States of America"`
+/// for `"US"`.
+pub fn official_name(iso2: &str) -> Option<&'static str> {
+    lookup(iso2).map(|country| country.official_name)
+}
+
+/// All countries, sorted by ISO2 code.
+pub fn countries() -> &'static [Country] {
+    COUNTRIES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_flags() {
+        assert_eq!(flag("US").as_deref(), Some("🇺🇸"));
+        assert_eq!(flag("gb").as_deref(), Some("🇬🇧"));
+        assert_eq!(name("US"), Some("United States"));
+        assert_eq!(official_name("US"), Some("United States of America"));
+        assert_eq!(name("KR"), Some("South Korea"));
+        assert_eq!(name("XK"), Some("Kosovo"));
+        assert_eq!(lookup("ZZ"), None);
+        assert_eq!(flag(""), None);
+    }
+
+    #[test]
+    fn table_is_sorted() {
+        assert!(COUNTRIES.windows(2).all(|pair| pair[0].iso2 < pair[1].iso2));
+    }
+
+    #[test]
+    fn covers_returned_codes() {
+        // Every ISO2 code flydent can return has a name
+        let codes = crate::DATA.iter().filter_map(|data| match &data.entity_result {
+            crate::EntityResult::Country { iso2, .. } => Some(iso2.as_str()),
+            crate::EntityResult::Organization { .. } => None,
+        });
+        let codes = codes
+            .chain((200..800).filter_map(crate::mmsi::lookup_mid).map(|mid| mid.iso2))
+            .chain(crate::airlines::airlines().iter().map(|airline| airline.iso2));
+        for iso2 in codes {
+            assert!(lookup(iso2).is_some(), "{}", iso2);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Dataset Versions
+//!
+//! Compares two versions of the ITU and ICAO data flydent attributes with, e.g.
+//! the tables built into this release and a data directory from another one,
+//! and reports which callsign prefixes and ICAO address blocks were added,
+//! removed or reassigned.
+
+use crate::{icao, parse_csv_line, parse_python_list, EntityResult, DATA};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const COUNTRIES_CSV: &str = "processed_itu_countries_regex.csv";
+const ORGANIZATIONS_CSV: &str = "processed_itu_organizations_regex.csv";
+const ALLOCATIONS_CSV: &str = "icao-allocations.csv";
+
+/// Error returned when a data directory cannot be read.
+#[derive(Debug)]
+pub enum DatasetError {
+    Io { path: PathBuf, source: io::Error },
+    /// A row without the expected columns
+    Malformed { path: PathBuf, line: usize },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            DatasetError::Malformed { path, line } => {
+                write!(f, "{}:{}: malformed row", path.display(), line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatasetError::Io { source, .. } => Some(source),
+            DatasetError::Malformed { .. } => None,
+        }
+    }
+}
+
+/// The attribution-relevant content of one dataset version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug)))]
+pub struct Dataset {
+    /// Callsign prefix -> holders of the rows listing it (ISO2 codes, or names
+    /// of organizations)
+    pub prefixes: BTreeMap<String, BTreeSet<String>>,
+    /// ICAO address block `(start, end)` -> holder (ISO2 code, or the
+    /// special-use kind such as `Temporary`)
+    pub ranges: BTreeMap<(u32, u32), String>,
+}
+
+impl Dataset {
+    /// The dataset built into this version of flydent.
+    pub fn builtin() -> Self {
+        let mut dataset = Dataset::default();
+        for data in DATA.iter() {
+            let holder = match &data.entity_result {
+                EntityResult::Country { iso2, .. } => iso2,
+                EntityResult::Organization { name, .. } => name,
+            };
+            dataset.add_prefixes(data.callsigns.iter().cloned(), holder);
+        }
+        for block in icao::allocations() {
+            let holder = match (block.iso2, block.special_use) {
+                (Some(iso2), _) => iso2.to_string(),
+                (None, Some(special_use)) => format!("{:?}", special_use),
+                (None, None) => block.name.to_string(),
+            };
+            dataset.ranges.insert((block.start, block.end), holder);
+        }
+        dataset
+    }
+
+    /// Read a dataset from a data directory laid out like flydent's `data/`:
+    /// `processed_itu_countries_regex.csv`,
+    /// `processed_itu_organizations_regex.csv` and `icao-allocations.csv`.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, DatasetError> {
+        let dir = dir.as_ref();
+        let read = |name: &str| {
+            let path = dir.join(name);
+            match fs::read_to_string(&path) {
+                Ok(csv) => Ok((path, csv)),
+                Err(source) => Err(DatasetError::Io { path, source }),
+            }
+        };
+        let mut dataset = Dataset::default();
+
+        // Countries: nation, description, priority, iso codes, callsign, ...
+        let (path, csv) = read(COUNTRIES_CSV)?;
+        for (line, fields) in data_rows(&csv) {
+            let iso2 = fields
+                .get(3)
+                .and_then(|codes| parse_python_list(codes).into_iter().next());
+            match (iso2, fields.get(4)) {
+                (Some(iso2), Some(callsigns)) => dataset.add_prefixes(parse_python_list(callsigns), &iso2),
+                _ => return Err(DatasetError::Malformed { path, line }),
+            }
+        }
+
+        // Organizations: name, description, priority, callsign, ...
+        let (path, csv) = read(ORGANIZATIONS_CSV)?;
+        for (line, fields) in data_rows(&csv) {
+            match (fields.first(), fields.get(3)) {
+                (Some(name), Some(callsigns)) => dataset.add_prefixes(parse_python_list(callsigns), name),
+                _ => return Err(DatasetError::Malformed { path, line }),
+            }
+        }
+
+        // Allocations: binary prefix, iso2, special_use, name, note
+        let (path, csv) = read(ALLOCATIONS_CSV)?;
+        for (line, fields) in data_rows(&csv) {
+            let block = match fields.as_slice() {
+                [prefix, iso2, special_use, name, ..] => binary_prefix_range(prefix).map(|range| {
+                    let holder = [iso2, special_use, name].into_iter().find(|field| !field.is_empty());
+                    (range, holder.cloned().unwrap_or_default())
+                }),
+                _ => None,
+            };
+            match block {
+                Some((range, holder)) => {
+                    dataset.ranges.insert(range, holder);
+                }
+                None => return Err(DatasetError::Malformed { path, line }),
+            }
+        }
+
+        Ok(dataset)
+    }
+
+    fn add_prefixes(&mut self, prefixes: impl IntoIterator<Item = String>, holder: &str) {
+        for prefix in prefixes {
+            self.prefixes.entry(prefix).or_default().insert(holder.to_string());
+        }
+    }
+
+    /// Changes from this dataset to `newer`.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::dataset::Dataset;
+    ///
+    /// let builtin = Dataset::builtin();
+    /// let mut newer = builtin.clone();
+    /// newer.prefixes.remove("PH");
+    /// let diff = builtin.diff(&newer);
+    /// assert_eq!(diff.prefixes.len(), 1);
+    /// assert!(diff.ranges.is_empty());
+    /// assert_eq!(diff.to_string(), "- prefix PH: NL\n");
+    /// ```
+    pub fn diff(&self, newer: &Dataset) -> DatasetDiff {
+        DatasetDiff {
+            prefixes: diff_maps(&self.prefixes, &newer.prefixes),
+            ranges: diff_maps(&self.ranges, &newer.ranges),
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl Dataset {
+    /// Serialize the dataset with rkyv, for a file that `Dataset::access` can
+    /// later read in place, e.g. memory-mapped.
+    pub fn to_bytes(&self) -> Result<rkyv::util::AlignedVec, rkyv::rancor::Error> {
+        rkyv::to_bytes::<rkyv::rancor::Error>(self)
+    }
+
+    /// Validate bytes written by `Dataset::to_bytes` and view them as an
+    /// archived dataset, without deserializing. `bytes` must be 16-byte aligned,
+    /// as memory maps and `AlignedVec` are.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::dataset::Dataset;
+    ///
+    /// let bytes = Dataset::builtin().to_bytes().unwrap();
+    /// let archived = Dataset::access(&bytes).unwrap();
+    /// let holders = archived.prefixes.get("PH").unwrap();
+    /// assert!(holders.iter().any(|holder| holder == "NL"));
+    /// ```
+    pub fn access(bytes: &[u8]) -> Result<&ArchivedDataset, rkyv::rancor::Error> {
+        rkyv::access::<ArchivedDataset, rkyv::rancor::Error>(bytes)
+    }
+}
+
+/// Non-empty lines after the header, with 1-based line numbers.
+fn data_rows(csv: &str) -> impl Iterator<Item = (usize, Vec<String>)> + '_ {
+    csv.lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, parse_csv_line(line)))
+}
+
+fn binary_prefix_range(prefix: &str) -> Option<(u32, u32)> {
+    if prefix.is_empty() || prefix.len() > 24 {
+        return None;
+    }
+    let shift = 24 - prefix.len() as u32;
+    let start = u32::from_str_radix(prefix, 2).ok()? << shift;
+    Some((start, start + ((1 << shift) - 1)))
+}
+
+/// A difference between two versions of a keyed table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<K, V> {
+    Added { key: K, value: V },
+    Removed { key: K, value: V },
+    /// The key is present in both versions with different values
+    Changed { key: K, old: V, new: V },
+}
+
+fn diff_maps<K: Ord + Clone, V: PartialEq + Clone>(old: &BTreeMap<K, V>, new: &BTreeMap<K, V>) -> Vec<Change<K, V>> {
+    let mut changes = Vec::new();
+    for (key, value) in old {
+        match new.get(key) {
+            None => changes.push(Change::Removed { key: key.clone(), value: value.clone() }),
+            Some(newer) if newer != value => changes.push(Change::Changed {
+                key: key.clone(),
+                old: value.clone(),
+                new: newer.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, value) in new {
+        if !old.contains_key(key) {
+            changes.push(Change::Added { key: key.clone(), value: value.clone() });
+        }
+    }
+    changes
+}
+
+/// Differences between two dataset versions, as returned by `Dataset::diff`.
+///
+/// A resized address block shows up as the old block removed and the new one
+/// added. `Display` renders one line per change, e.g. `~ prefix M: GB, IM -> GB`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetDiff {
+    pub prefixes: Vec<Change<String, BTreeSet<String>>>,
+    pub ranges: Vec<Change<(u32, u32), String>>,
+}
+
+impl DatasetDiff {
+    /// Whether the two versions attribute everything identically.
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty() && self.ranges.is_empty()
+    }
+}
+
+fn write_change<K, V>(
+    f: &mut fmt::Formatter<'_>,
+    kind: &str,
+    change: &Change<K, V>,
+    key: impl Fn(&K) -> String,
+    value: impl Fn(&V) -> String,
+) -> fmt::Result {
+    match change {
+        Change::Added { key: k, value: v } => writeln!(f, "+ {} {}: {}", kind, key(k), value(v)),
+        Change::Removed { key: k, value: v } => writeln!(f, "- {} {}: {}", kind, key(k), value(v)),
+        Change::Changed { key: k, old, new } => {
+            writeln!(f, "~ {} {}: {} -> {}", kind, key(k), value(old), value(new))
+        }
+    }
+}
+
+impl fmt::Display for DatasetDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let holders = |holders: &BTreeSet<String>| holders.iter().cloned().collect::<Vec<_>>().join(", ");
+        for change in &self.prefixes {
+            write_change(f, "prefix", change, String::clone, holders)?;
+        }
+        for change in &self.ranges {
+            let range = |&(start, end): &(u32, u32)| format!("{:06X}-{:06X}", start, end);
+            write_change(f, "range", change, range, String::clone)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_matches_data_dir() {
+        let data = Dataset::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/data")).unwrap();
+        let builtin = Dataset::builtin();
+        assert!(builtin.diff(&data).is_empty(), "{}", builtin.diff(&data));
+        assert_eq!(builtin.prefixes["PH"], BTreeSet::from(["NL".to_string()]));
+        assert_eq!(builtin.ranges[&(0xA00000, 0xAFFFFF)], "US");
+    }
+
+    #[test]
+    fn reports_each_kind_of_change() {
+        let old = Dataset::builtin();
+        let mut new = old.clone();
+        new.prefixes.insert("Q9".to_string(), BTreeSet::from(["XX".to_string()]));
+        new.prefixes.get_mut("M").unwrap().remove("IM");
+        new.ranges.remove(&(0x4C0000, 0x4C7FFF));
+        new.ranges.insert((0x4C0000, 0x4C3FFF), "RS".to_string());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.prefixes.len(), 2);
+        assert_eq!(diff.ranges.len(), 2);
+        let report = diff.to_string();
+        assert!(report.contains("+ prefix Q9: XX\n"));
+        assert!(report.contains("~ prefix M: GB, IM -> GB\n"));
+        assert!(report.contains("- range 4C0000-4C7FFF: RS\n"));
+        assert!(report.contains("+ range 4C0000-4C3FFF: RS\n"));
+    }
+
+    #[test]
+    fn missing_dir_is_an_error() {
+        let err = Dataset::from_dir("/nonexistent/flydent-data").unwrap_err();
+        assert!(matches!(err, DatasetError::Io { .. }));
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn archive_round_trip() {
+        let builtin = Dataset::builtin();
+        let bytes = builtin.to_bytes().unwrap();
+        let archived = Dataset::access(&bytes).unwrap();
+        assert_eq!(archived.ranges.len(), builtin.ranges.len());
+        let deserialized = rkyv::deserialize::<Dataset, rkyv::rancor::Error>(archived).unwrap();
+        assert_eq!(deserialized, builtin);
+        let other = rkyv::to_bytes::<rkyv::rancor::Error>(&42u32).unwrap();
+        assert!(Dataset::access(&other).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! C Interface
+//!
+//! `extern "C"` functions with a stable ABI for C and C++ callers such as SDR
+//! and receiver firmware. Enabled with the `ffi` feature. They are declared in
+//! the checked-in header `include/flydent.h`, which `build.rs` regenerates from
+//! this file with cbindgen when `FLYDENT_REGENERATE_HEADER` is set. Link against a shared or static library built with
+//! `cargo rustc --lib --features ffi --crate-type cdylib` (or `staticlib`).
+//!
+//! Strings are NUL-terminated UTF-8. Functions returning `int32_t` answer
+//! `FLYDENT_OK` or one of the negative `FLYDENT_*` error codes. Entities
+//! returned by `flydent_parse_registration` are owned by the caller and must be
+//! released with `flydent_entity_free`.
+
+use crate::icao::{self, Icao24};
+use crate::registration::{self, ConversionError};
+use crate::{EntityResult, Parser};
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// Success.
+pub const FLYDENT_OK: i32 = 0;
+/// The input is well-formed but cannot be identified or converted.
+pub const FLYDENT_NOT_FOUND: i32 = -1;
+/// A pointer is NULL, a string is not UTF-8, or the input is malformed.
+pub const FLYDENT_INVALID_ARGUMENT: i32 = -2;
+/// The output buffer cannot hold the result and its NUL terminator.
+pub const FLYDENT_BUFFER_TOO_SMALL: i32 = -3;
+
+/// Country or organization of a registration.
+#[repr(C)]
+pub struct FlydentEntity {
+    /// Nation, or organization name
+    pub name: *mut c_char,
+    pub description: *mut c_char,
+    /// ISO 3166-1 alpha-2 code, empty for organizations
+    pub iso2: [c_char; 3],
+    /// ISO 3166-1 alpha-3 code, empty for organizations
+    pub iso3: [c_char; 4],
+    pub canonical_callsign: *mut c_char,
+    pub organization: bool,
+}
+
+/// Copy `value` into the NUL-terminated C array `out`, truncating if needed.
+fn copy_code<const N: usize>(value: &str, out: &mut [c_char; N]) {
+    *out = [0; N];
+    for (slot, byte) in out.iter_mut().zip(value.bytes().take(N - 1)) {
+        *slot = byte as c_char;
+    }
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    // Dataset strings contain no NUL bytes
+    CString::new(value).map(CString::into_raw).unwrap_or(ptr::null_mut())
+}
+
+/// Borrow a C string as `&str`, or `None` if it is NULL or not UTF-8.
+///
+/// # Safety
+///
+/// `value` must be NULL or point to a NUL-terminated string that outlives the
+/// returned reference.
+unsafe fn borrow_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        return None;
+    }
+    CStr::from_ptr(value).to_str().ok()
+}
+
+/// Country or organization of a registration, or NULL if it cannot be
+/// identified. `strict` requires the dash between prefix and suffix.
+///
+/// # Safety
+///
+/// `registration` must be NULL or a NUL-terminated string. The result must be
+/// released with `flydent_entity_free`.
+#[no_mangle]
+pub unsafe extern "C" fn flydent_parse_registration(registration: *const c_char, strict: bool) -> *mut FlydentEntity {
+    let Some(registration) = borrow_str(registration) else {
+        return ptr::null_mut();
+    };
+    let Some(entity) = Parser::new().parse(registration, strict, false) else {
+        return ptr::null_mut();
+    };
+
+    let mut result = FlydentEntity {
+        name: ptr::null_mut(),
+        description: ptr::null_mut(),
+        iso2: [0; 3],
+        iso3: [0; 4],
+        canonical_callsign: ptr::null_mut(),
+        organization: false,
+    };
+    match entity {
+        EntityResult::Country { nation, description, iso2, iso3, canonical_callsign } => {
+            result.name = into_c_string(nation);
+            result.description = into_c_string(description);
+            copy_code(&iso2, &mut result.iso2);
+            copy_code(&iso3, &mut result.iso3);
+            result.canonical_callsign = into_c_string(canonical_callsign);
+        }
+        EntityResult::Organization { name, description, canonical_callsign } => {
+            result.name = into_c_string(name);
+            result.description = into_c_string(description);
+            result.canonical_callsign = into_c_string(canonical_callsign);
+            result.organization = true;
+        }
+    }
+    Box::into_raw(Box::new(result))
+}
+
+/// Release an entity returned by `flydent_parse_registration`. NULL is
+/// ignored.
+///
+/// # Safety
+///
+/// `entity` must be NULL or a pointer returned by `flydent_parse_registration`
+/// that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn flydent_entity_free(entity: *mut FlydentEntity) {
+    if entity.is_null() {
+        return;
+    }
+    let entity = Box::from_raw(entity);
+    for string in [entity.name, entity.description, entity.canonical_callsign] {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    }
+}
+
+/// Write the ISO2 code of the state an ICAO address is allocated to into
+/// `iso2`, a buffer of at least 3 bytes.
+///
+/// Answers `FLYDENT_NOT_FOUND` for special-use blocks and unallocated
+/// addresses, and `FLYDENT_INVALID_ARGUMENT` for addresses above `0xFFFFFF`.
+///
+/// # Safety
+///
+/// `iso2` must be NULL or point to at least 3 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn flydent_hex_to_country(address: u32, iso2: *mut c_char) -> i32 {
+    if iso2.is_null() || Icao24::new(address).is_none() {
+        return FLYDENT_INVALID_ARGUMENT;
+    }
+    match icao::lookup(address).and_then(|allocation| allocation.iso2) {
+        Some(code) => {
+            copy_code(code, &mut *(iso2 as *mut [c_char; 3]));
+            FLYDENT_OK
+        }
+        None => FLYDENT_NOT_FOUND,
+    }
+}
+
+/// Convert a registration to its ICAO address, for countries with an
+/// algorithmic mapping, e.g. `"N8437D"` to `0xAB8E4F`.
+///
+/// # Safety
+///
+/// `registration` must be NULL or a NUL-terminated string, and `address` NULL
+/// or writable.
+#[no_mangle]
+pub unsafe extern "C" fn flydent_registration_to_icao(registration: *const c_char, address: *mut u32) -> i32 {
+    let Some(registration) = borrow_str(registration) else {
+        return FLYDENT_INVALID_ARGUMENT;
+    };
+    if address.is_null() {
+        return FLYDENT_INVALID_ARGUMENT;
+    }
+    match registration::registration_to_icao(registration) {
+        Ok(bytes) => {
+            *address = Icao24::from_bytes(bytes).value();
+            FLYDENT_OK
+        }
+        Err(err) => conversion_error(&err),
+    }
+}
+
+/// Write the registration of an ICAO address into `buffer` of `len` bytes,
+/// NUL-terminated, for countries with an algorithmic mapping.
+///
+/// Answers the registration's length on success, or a negative error code.
+/// 16 bytes hold any registration.
+///
+/// # Safety
+///
+/// `buffer` must be NULL or point to at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn flydent_icao_to_registration(address: u32, buffer: *mut c_char, len: usize) -> i32 {
+    let Some(icao) = Icao24::new(address) else {
+        return FLYDENT_INVALID_ARGUMENT;
+    };
+    if buffer.is_null() {
+        return FLYDENT_INVALID_ARGUMENT;
+    }
+    let registration = match registration::icao_to_registration(icao.to_bytes()) {
+        Ok(registration) => registration,
+        Err(err) => return conversion_error(&err),
+    };
+    if registration.len() >= len {
+        return FLYDENT_BUFFER_TOO_SMALL;
+    }
+    ptr::copy_nonoverlapping(registration.as_ptr() as *const c_char, buffer, registration.len());
+    *buffer.add(registration.len()) = 0;
+    registration.len() as i32
+}
+
+fn conversion_error(err: &ConversionError) -> i32 {
+    match err {
+        ConversionError::InvalidFormat { .. } | ConversionError::InvalidNNumber(_) => FLYDENT_INVALID_ARGUMENT,
+        _ => FLYDENT_NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c_str(value: &str) -> CString {
+        CString::new(value).unwrap()
+    }
+
+    unsafe fn read(value: *const c_char) -> String {
+        CStr::from_ptr(value).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_registration() {
+        unsafe {
+            let entity = flydent_parse_registration(c_str("PH-BHA").as_ptr(), false);
+            assert!(!entity.is_null());
+            assert_eq!(read((*entity).name), "Netherlands");
+            assert_eq!(read((*entity).iso2.as_ptr()), "NL");
+            assert_eq!(read((*entity).iso3.as_ptr()), "NLD");
+            assert!(!(*entity).organization);
+            flydent_entity_free(entity);
+
+            let entity = flydent_parse_registration(c_str("4Y-ABC").as_ptr(), false);
+            assert!((*entity).organization);
+            assert_eq!(read((*entity).iso2.as_ptr()), "");
+            flydent_entity_free(entity);
+
+            assert!(flydent_parse_registration(c_str("ZZZZZZZ").as_ptr(), false).is_null());
+            assert!(flydent_parse_registration(ptr::null(), false).is_null());
+            flydent_entity_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn hex_to_country() {
+        let mut iso2 = [0 as c_char; 3];
+        unsafe {
+            assert_eq!(flydent_hex_to_country(0x3C65A1, iso2.as_mut_ptr()), FLYDENT_OK);
+            assert_eq!(read(iso2.as_ptr()), "DE");
+            assert_eq!(flydent_hex_to_country(0xF00000, iso2.as_mut_ptr()), FLYDENT_NOT_FOUND);
+            assert_eq!(flydent_hex_to_country(0x1000000, iso2.as_mut_ptr()), FLYDENT_INVALID_ARGUMENT);
+            assert_eq!(flydent_hex_to_country(0x3C65A1, ptr::null_mut()), FLYDENT_INVALID_ARGUMENT);
+        }
+    }
+
+    #[test]
+    fn converters() {
+        let mut address = 0;
+        let mut buffer = [0 as c_char; 16];
+        unsafe {
+            assert_eq!(flydent_registration_to_icao(c_str("N8437D").as_ptr(), &mut address), FLYDENT_OK);
+            assert_eq!(address, 0xAB8E4F);
+            assert_eq!(flydent_registration_to_icao(c_str("G-EUPT").as_ptr(), &mut address), FLYDENT_NOT_FOUND);
+            assert_eq!(flydent_registration_to_icao(c_str("N0").as_ptr(), &mut address), FLYDENT_INVALID_ARGUMENT);
+
+            assert_eq!(flydent_icao_to_registration(0x3C65A1, buffer.as_mut_ptr(), buffer.len()), 6);
+            assert_eq!(read(buffer.as_ptr()), "D-AIMA");
+            assert_eq!(flydent_icao_to_registration(0x3C65A1, buffer.as_mut_ptr(), 6), FLYDENT_BUFFER_TOO_SMALL);
+            assert_eq!(flydent_icao_to_registration(0xF00000, buffer.as_mut_ptr(), buffer.len()), FLYDENT_NOT_FOUND);
+        }
+    }
+
+    #[test]
+    fn header_is_current() {
+        // build.rs generates the header of this file into OUT_DIR; rebuild with
+        // FLYDENT_REGENERATE_HEADER=1 to update the checked-in copy
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/flydent.h"));
+        assert!(
+            generated == include_str!("../include/flydent.h"),
+            "include/flydent.h is stale; rebuild with FLYDENT_REGENERATE_HEADER=1"
+        );
+    }
+}
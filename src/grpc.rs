@@ -0,0 +1,284 @@
+//! gRPC Service
+//!
+//! A tonic implementation of the `flydent.v1.Flydent` service defined in
+//! `proto/flydent.proto`, wrapping the parser and converters. Enabled with the
+//! `grpc` feature; the `flydent-grpc` binary serves `service()`. The message
+//! types below mirror the proto file, and `flydent_client` is available for
+//! Rust callers.
+
+use crate::icao::{self, Icao24};
+use crate::registration::{self, ConversionError};
+use crate::{EntityResult, Parser};
+use std::net::SocketAddr;
+use tonic::{Request, Response, Status};
+
+// Service traits and clients generated by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/flydent.v1.Flydent.rs"));
+
+pub use flydent_client::FlydentClient;
+pub use flydent_server::{Flydent, FlydentServer};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IdentifyRequest {
+    #[prost(string, tag = "1")]
+    pub value: String,
+    /// Treat `value` as a hex ICAO address instead of a registration
+    #[prost(bool, tag = "2")]
+    pub icao24bit: bool,
+    /// Require the dash between prefix and suffix, and six hex digits
+    #[prost(bool, tag = "3")]
+    pub strict: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Entity {
+    /// Set for countries, empty for international organizations
+    #[prost(string, tag = "1")]
+    pub iso2: String,
+    #[prost(string, tag = "2")]
+    pub iso3: String,
+    /// Nation or organization name
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "4")]
+    pub description: String,
+    #[prost(string, tag = "5")]
+    pub canonical_callsign: String,
+    #[prost(bool, tag = "6")]
+    pub organization: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IdentifyResponse {
+    #[prost(message, optional, tag = "1")]
+    pub entity: Option<Entity>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConvertRequest {
+    #[prost(string, tag = "1")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConvertResponse {
+    /// Six uppercase hex digits
+    #[prost(string, tag = "1")]
+    pub hex: String,
+    #[prost(string, tag = "2")]
+    pub registration: String,
+    #[prost(string, tag = "3")]
+    pub iso2: String,
+    /// Conversion scheme, e.g. `"US N-number"`
+    #[prost(string, tag = "4")]
+    pub scheme: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BatchClassifyRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub values: Vec<String>,
+}
+
+/// What a `Classification` input turned out to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Kind {
+    Unknown = 0,
+    Hex = 1,
+    Registration = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Classification {
+    #[prost(string, tag = "1")]
+    pub input: String,
+    #[prost(enumeration = "Kind", tag = "2")]
+    pub kind: i32,
+    /// Empty when unknown, e.g. for organizations and ICAO special-use blocks
+    #[prost(string, tag = "3")]
+    pub iso2: String,
+    #[prost(string, tag = "4")]
+    pub name: String,
+    #[prost(string, tag = "5")]
+    pub hex: String,
+    #[prost(string, tag = "6")]
+    pub registration: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BatchClassifyResponse {
+    /// One per request value, in order
+    #[prost(message, repeated, tag = "1")]
+    pub results: Vec<Classification>,
+}
+
+impl From<EntityResult> for Entity {
+    fn from(entity: EntityResult) -> Self {
+        match entity {
+            EntityResult::Country { nation, description, iso2, iso3, canonical_callsign } => Entity {
+                iso2,
+                iso3,
+                name: nation,
+                description,
+                canonical_callsign,
+                organization: false,
+            },
+            EntityResult::Organization { name, description, canonical_callsign } => Entity {
+                name,
+                description,
+                canonical_callsign,
+                organization: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+fn classify(parser: &Parser, input: &str) -> Classification {
+    let input = input.trim();
+    let mut classification = Classification { input: input.to_string(), ..Default::default() };
+
+    if let Ok(icao) = Icao24::parse_hex(input) {
+        classification.kind = Kind::Hex as i32;
+        classification.hex = icao.to_string();
+        if let Some(allocation) = icao::lookup(icao.value()) {
+            classification.iso2 = allocation.iso2.unwrap_or_default().to_string();
+            classification.name = allocation.name.to_string();
+        }
+        classification.registration = registration::icao_to_registration(icao.to_bytes()).unwrap_or_default();
+    } else if let Some(entity) = parser.parse_simple(input) {
+        let entity = Entity::from(entity);
+        classification.kind = Kind::Registration as i32;
+        classification.hex = registration::registration_to_icao(input)
+            .map(|bytes| Icao24::from_bytes(bytes).to_string())
+            .unwrap_or_default();
+        classification.iso2 = entity.iso2;
+        classification.name = entity.name;
+        classification.registration = entity.canonical_callsign;
+    }
+    classification
+}
+
+/// The `Flydent` service implementation.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FlydentService;
+
+#[tonic::async_trait]
+impl Flydent for FlydentService {
+    async fn identify(&self, request: Request<IdentifyRequest>) -> Result<Response<IdentifyResponse>, Status> {
+        let request = request.into_inner();
+        match Parser::new().parse(&request.value, request.strict, request.icao24bit) {
+            Some(entity) => Ok(Response::new(IdentifyResponse { entity: Some(entity.into()) })),
+            None => Err(Status::not_found(format!("cannot identify {:?}", request.value))),
+        }
+    }
+
+    async fn convert(&self, request: Request<ConvertRequest>) -> Result<Response<ConvertResponse>, Status> {
+        match registration::convert(&request.get_ref().value) {
+            Ok(converted) => Ok(Response::new(ConvertResponse {
+                hex: converted.icao.to_string(),
+                registration: converted.registration,
+                iso2: converted.iso2.to_string(),
+                scheme: converted.scheme.to_string(),
+            })),
+            Err(err @ (ConversionError::InvalidFormat { .. } | ConversionError::InvalidNNumber(_))) => {
+                Err(Status::invalid_argument(err.to_string()))
+            }
+            Err(err) => Err(Status::not_found(err.to_string())),
+        }
+    }
+
+    async fn batch_classify(
+        &self,
+        request: Request<BatchClassifyRequest>,
+    ) -> Result<Response<BatchClassifyResponse>, Status> {
+        let parser = Parser::new();
+        let results = request.get_ref().values.iter().map(|value| classify(&parser, value)).collect();
+        Ok(Response::new(BatchClassifyResponse { results }))
+    }
+}
+
+/// The service, ready to add to a `tonic::transport::Server`.
+pub fn service() -> FlydentServer<FlydentService> {
+    FlydentServer::new(FlydentService)
+}
+
+/// Serve `service()` on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder().add_service(service()).serve(addr).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn identify() {
+        let request = IdentifyRequest { value: "PH-BHA".to_string(), ..Default::default() };
+        let entity = FlydentService.identify(Request::new(request)).await.unwrap().into_inner().entity.unwrap();
+        assert_eq!(entity.iso2, "NL");
+        assert!(!entity.organization);
+
+        let request = IdentifyRequest { value: "AB8E4F".to_string(), icao24bit: true, strict: true };
+        let entity = FlydentService.identify(Request::new(request)).await.unwrap().into_inner().entity.unwrap();
+        assert_eq!(entity.iso2, "US");
+
+        let request = IdentifyRequest { value: "ZZZZZZZ".to_string(), ..Default::default() };
+        let status = FlydentService.identify(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn convert() {
+        let request = ConvertRequest { value: "N8437D".to_string() };
+        let converted = FlydentService.convert(Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(converted.hex, "AB8E4F");
+        assert_eq!(converted.scheme, "US N-number");
+
+        let request = ConvertRequest { value: "N0".to_string() };
+        let status = FlydentService.convert(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn batch_classify() {
+        let values = ["3C65A1", "4Y-ABC", "nonsense"].map(String::from).to_vec();
+        let request = BatchClassifyRequest { values };
+        let results = FlydentService.batch_classify(Request::new(request)).await.unwrap().into_inner().results;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].kind(), Kind::Hex);
+        assert_eq!(results[0].registration, "D-AIMA");
+        assert_eq!(results[1].kind(), Kind::Registration);
+        assert_eq!(results[1].iso2, "");
+        assert_eq!(results[2].kind(), Kind::Unknown);
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        use prost::Message;
+
+        let response = ConvertResponse {
+            hex: "3C65A1".to_string(),
+            registration: "D-AIMA".to_string(),
+            iso2: "DE".to_string(),
+            scheme: "Germany D-A".to_string(),
+        };
+        let decoded = ConvertResponse::decode(response.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, response);
+    }
+
+    #[test]
+    fn proto_declares_the_service() {
+        let proto = include_str!("../proto/flydent.proto");
+        assert!(proto.contains("package flydent.v1;"));
+        for rpc in [
+            "rpc Identify(IdentifyRequest) returns (IdentifyResponse);",
+            "rpc Convert(ConvertRequest) returns (ConvertResponse);",
+            "rpc BatchClassify(BatchClassifyRequest) returns (BatchClassifyResponse);",
+        ] {
+            assert!(proto.contains(rpc), "{}", rpc);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Historical Attribution
+//!
+//! Resolves ICAO addresses and registration marks as of a date, so records
+//! from before a state dissolved are attributed to the state of the time:
+//! `0x4C0000` is Yugoslavia before 2003 and Serbia today, `CCCP-85001` is a
+//! Soviet registration. Former states are identified by their ISO 3166-3 code
+//! (e.g. `SUHH` for the Soviet Union). Dates outside every recorded period fall
+//! back to the current tables. The periods are generated by `build.rs` from
+//! `data/historical-allocations.csv` and `data/historical-marks.csv`.
+
+use core::fmt;
+use core::str::FromStr;
+use crate::{icao, normalize_dashes, EntityResult, Parser, COUNTRY_ROWS, DATA};
+
+/// A calendar date, ordered chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Build a date, or `None` if the month or day does not exist.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days).contains(&day).then_some(Date { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Error returned when a date is not a valid `YYYY-MM-DD` date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub struct ParseDateError;
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid date, expected YYYY-MM-DD")
+    }
+}
+
+impl core::error::Error for ParseDateError {}
+
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('-');
+        let mut next = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len && part.chars().all(|c| c.is_ascii_digit()))
+                .ok_or(ParseDateError)
+        };
+        let year = next(4)?.parse().map_err(|_| ParseDateError)?;
+        let month = next(2)?.parse().map_err(|_| ParseDateError)?;
+        let day = next(2)?.parse().map_err(|_| ParseDateError)?;
+        if parts.next().is_some() {
+            return Err(ParseDateError);
+        }
+        Date::new(year, month, day).ok_or(ParseDateError)
+    }
+}
+
+/// The state holding an address block or registration mark at some date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Holder {
+    /// ISO 3166-1 alpha-2 code of a current state, or ISO 3166-3 code of a
+    /// former one, e.g. `"YUCS"` for Yugoslavia
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+/// A former holder of `key`, from `from` (or the beginning) until the day
+/// before `until`.
+struct Period<K: 'static> {
+    key: K,
+    holder: Holder,
+    from: Option<Date>,
+    until: Option<Date>,
+}
+
+impl<K> Period<K> {
+    fn contains(&self, date: Date) -> bool {
+        self.from.is_none_or(|from| from <= date) && self.until.is_none_or(|until| date < until)
+    }
+}
+
+// Periods generated by `build.rs` from `data/historical-allocations.csv` and
+// `data/historical-marks.csv`.
+include!(concat!(env!("OUT_DIR"), "/history.rs"));
+
+/// State an ICAO address was allocated to on `date`.
+///
+/// # Examples
+/// ```
+/// use flydent::history::{icao_as_of, Date};
+///
+/// let date = |s: &str| s.parse::<Date>().unwrap();
+/// assert_eq!(icao_as_of(0x4C0000, date("1995-06-01")).unwrap().code, "YUCS");
+/// assert_eq!(icao_as_of(0x4C0000, date("2004-06-01")).unwrap().code, "CSXX");
+/// assert_eq!(icao_as_of(0x4C0000, date("2010-06-01")).unwrap().code, "RS");
+/// assert_eq!(icao_as_of(0x155000, date("1989-01-01")).unwrap().name, "Soviet Union");
+/// ```
+pub fn icao_as_of(address: u32, date: Date) -> Option<Holder> {
+    let historical = HISTORICAL_ALLOCATIONS
+        .iter()
+        .filter(|period| (period.key.0..=period.key.1).contains(&address) && period.contains(date))
+        .min_by_key(|period| period.key.1 - period.key.0);
+    if let Some(period) = historical {
+        return Some(period.holder);
+    }
+
+    let allocation = icao::lookup(address)?;
+    Some(Holder {
+        code: allocation.iso2?,
+        name: allocation.name,
+    })
+}
+
+/// State a hex address prefix was allocated to on `date`, if all of its
+/// addresses had the same holder, e.g. `"4C"` or `"4C0"`.
+///
+/// # Examples
+/// ```
+/// use flydent::history::{hex_prefix_as_of, Date};
+///
+/// let date = |s: &str| s.parse::<Date>().unwrap();
+/// assert_eq!(hex_prefix_as_of("4C0", date("1990-01-01")).unwrap().code, "YUCS");
+/// assert_eq!(hex_prefix_as_of("4C0", date("2020-01-01")).unwrap().code, "RS");
+/// ```
+pub fn hex_prefix_as_of(hex: &str, date: Date) -> Option<Holder> {
+    let hex = hex.trim();
+    if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let shift = 4 * (6 - hex.len() as u32);
+    let start = u32::from_str_radix(hex, 16).ok()? << shift;
+    let end = start + ((1 << shift) - 1);
+
+    let historical = HISTORICAL_ALLOCATIONS
+        .iter()
+        .filter(|period| period.key.0 <= start && end <= period.key.1 && period.contains(date))
+        .min_by_key(|period| period.key.1 - period.key.0);
+    if let Some(period) = historical {
+        return Some(period.holder);
+    }
+
+    let iso2 = icao::prefix_country(hex)?;
+    icao_as_of(start, date).filter(|holder| holder.code == iso2)
+}
+
+/// State a registration mark belonged to on `date`.
+///
+/// Marks of international organizations (e.g. the UN's `4U`) have no state and
+/// yield `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::history::{registration_as_of, Date};
+///
+/// let date = |s: &str| s.parse::<Date>().unwrap();
+/// assert_eq!(registration_as_of("CCCP-85001", date("1985-01-01")).unwrap().code, "SUHH");
+/// assert_eq!(registration_as_of("DDR-SEA", date("1989-01-01")).unwrap().name, "East Germany");
+/// assert_eq!(registration_as_of("OK-ABC", date("1990-01-01")).unwrap().code, "CSHH");
+/// assert_eq!(registration_as_of("OK-ABC", date("2000-01-01")).unwrap().code, "CZ");
+/// ```
+pub fn registration_as_of(registration: &str, date: Date) -> Option<Holder> {
+    let registration = normalize_dashes(registration.trim()).to_ascii_uppercase();
+
+    let historical = HISTORICAL_MARKS
+        .iter()
+        .filter(|period| registration.starts_with(period.key) && period.contains(date))
+        .max_by_key(|period| period.key.len());
+    if let Some(period) = historical {
+        return Some(period.holder);
+    }
+
+    match Parser::new().parse(&registration, false, false)? {
+        EntityResult::Country { iso2, .. } => {
+            let data = &DATA[*COUNTRY_ROWS.get(iso2.as_str())?];
+            match &data.entity_result {
+                EntityResult::Country { iso2, nation, .. } => Some(Holder { code: iso2, name: nation }),
+                EntityResult::Organization { .. } => None,
+            }
+        }
+        EntityResult::Organization { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(date("2003-02-04"), Date::new(2003, 2, 4).unwrap());
+        assert_eq!(date("2000-02-29").to_string(), "2000-02-29");
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!("2003-2-4".parse::<Date>().is_err());
+        assert!("2003-02-04-01".parse::<Date>().is_err());
+        assert!(date("1991-12-25") < date("1991-12-26"));
+    }
+
+    #[test]
+    fn periods_end_on_until() {
+        // The Soviet Union dissolved on 1991-12-26
+        assert_eq!(icao_as_of(0x100000, date("1991-12-25")).unwrap().code, "SUHH");
+        assert_eq!(icao_as_of(0x100000, date("1991-12-26")).unwrap().code, "RU");
+        assert_eq!(icao_as_of(0x498000, date("1992-12-31")).unwrap().code, "CSHH");
+        assert_eq!(icao_as_of(0x498000, date("1993-01-01")).unwrap().code, "CZ");
+    }
+
+    #[test]
+    fn unrelated_addresses_use_current_table() {
+        assert_eq!(icao_as_of(0xA00001, date("1970-01-01")).unwrap().code, "US");
+        assert_eq!(icao_as_of(0xF00000, date("1970-01-01")), None);
+        assert_eq!(hex_prefix_as_of("A", date("1970-01-01")).unwrap().code, "US");
+        // 4C spans Serbia and other blocks, so it has no single holder today
+        assert_eq!(hex_prefix_as_of("4C", date("2020-01-01")), None);
+    }
+
+    #[test]
+    fn registrations() {
+        assert_eq!(registration_as_of("YU-AND", date("1990-01-01")).unwrap().code, "YUCS");
+        assert_eq!(registration_as_of("YU-AND", date("2005-01-01")).unwrap().code, "CSXX");
+        assert_eq!(registration_as_of("YU-AND", date("2010-01-01")).unwrap().code, "RS");
+        assert_eq!(registration_as_of("dm-sea", date("1970-01-01")).unwrap().code, "DDDE");
+        assert_eq!(registration_as_of("D-MABC", date("1970-01-01")).unwrap().code, "DE");
+        assert_eq!(registration_as_of("4U-ABC", date("2000-01-01")), None);
+    }
+
+    #[test]
+    fn history_tables() {
+        for period in HISTORICAL_ALLOCATIONS {
+            assert!(period.key.0 <= period.key.1);
+            assert!(period.from < period.until);
+        }
+        assert!(HISTORICAL_MARKS.iter().all(|period| period.until.is_some()));
+    }
+}
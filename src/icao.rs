@@ -1,285 +1,1309 @@
-/// ICAO 24-bit Address Allocation
-///
-/// Maps ICAO 24-bit address prefixes to ISO 3166-1 alpha-2 country codes.
-/// Based on ICAO Doc 8643 aircraft type designators and address allocations.
-
-/// ICAO address allocations as (binary_prefix, iso2_country_code) tuples.
-/// Sorted by prefix length (longest first) to ensure correct prefix matching.
-const ICAO_ALLOCATIONS: &[(&str, &str)] = &[
-    // 14-bit prefixes
-    ("00001100101000", "AG"),  // Antigua and Barbuda
-    ("01010000000100", "AL"),  // Albania
-    ("00001010101000", "BB"),  // Barbados
-    ("00001010101100", "BZ"),  // Belize
-    ("00001001010000", "BJ"),  // Benin
-    ("01101000000000", "BT"),  // Bhutan
-    ("111010010100", "BO"),  // Bolivia
-    ("01010001001100", "BA"),  // Bosnia and Herzegovina
-    ("00000011000000", "BW"),  // Botswana
-    ("10001001010100", "BN"),  // Brunei Darussalam
-    ("000010011100", "BF"),  // Burkina Faso
-    ("000000110010", "BI"),  // Burundi
-    ("011100001110", "KH"),  // Cambodia
-    ("000000110100", "CM"),  // Cameroon
-    ("00001001011000", "CV"),  // Cape Verde
-    ("000001101100", "CF"),  // Central African Republic
-    ("000010000100", "TD"),  // Chad
-    ("111010000000", "CL"),  // Chile
-    ("000010101100", "CO"),  // Colombia
-    ("00000011010100", "KM"),  // Comoros
-    ("000000110110", "CG"),  // Congo
-    ("10010000000100", "CK"),  // Cook Islands
-    ("000010101110", "CR"),  // Costa Rica
-    ("000000111000", "CI"),  // Côte d'Ivoire
-    ("01010000000111", "HR"),  // Croatia
-    ("000010110000", "CU"),  // Cuba
-    ("01001100100000", "CY"),  // Cyprus
-    ("011100100", "KP"),  // Democratic People's Republic of Korea (North Korea)
-    ("000010001100", "CD"),  // Democratic Republic of the Congo
-    ("00001001100000", "DJ"),  // Djibouti
-    ("000011000100", "DO"),  // Dominican Republic
-    ("111010000100", "EC"),  // Ecuador
-    ("000010110010", "SV"),  // El Salvador
-    ("000001000010", "GQ"),  // Equatorial Guinea
-    ("00100000001000", "ER"),  // Eritrea
-    ("01010001000100", "EE"),  // Estonia
-    ("000001000000", "ET"),  // Ethiopia
-    ("110010001000", "FJ"),  // Fiji
-    ("000000111110", "GA"),  // Gabon
-    ("000010011010", "GM"),  // Gambia
-    ("01010001010000", "GE"),  // Georgia
-    ("000001000100", "GH"),  // Ghana
-    ("00001100110000", "GD"),  // Grenada
-    ("000010110100", "GT"),  // Guatemala
-    ("000001000110", "GN"),  // Guinea
-    ("00000100100000", "GW"),  // Guinea-Bissau
-    ("000010110110", "GY"),  // Guyana
-    ("000010111000", "HT"),  // Haiti
-    ("000010111010", "HN"),  // Honduras
-    ("010011001100", "IS"),  // Iceland
-    ("011100110", "IR"),  // Iran, Islamic Republic of
-    ("011100101", "IQ"),  // Iraq
-    ("010011001010", "IE"),  // Ireland
-    ("011100111", "IL"),  // Israel
-    ("000010111110", "JM"),  // Jamaica
-    ("011101000", "JO"),  // Jordan
-    ("01101000001100", "KZ"),  // Kazakhstan
-    ("000001001100", "KE"),  // Kenya
-    ("11001000111000", "KI"),  // Kiribati
-    ("011100000110", "KW"),  // Kuwait
-    ("01100000000100", "KG"),  // Kyrgyzstan
-    ("011100001000", "LA"),  // Lao People's Democratic Republic
-    ("01010000001011", "LV"),  // Latvia
-    ("011101001", "LB"),  // Lebanon
-    ("00000100101000", "LS"),  // Lesotho
-    ("000001010000", "LR"),  // Liberia
-    ("01010000001111", "LT"),  // Lithuania
-    ("01001101000000", "LU"),  // Luxembourg
-    ("000001010100", "MG"),  // Madagascar
-    ("000001011000", "MW"),  // Malawi
-    ("011101010", "MY"),  // Malaysia
-    ("00000101101000", "MV"),  // Maldives
-    ("000001011100", "ML"),  // Mali
-    ("01001101001000", "MT"),  // Malta
-    ("10010000000000", "MH"),  // Marshall Islands
-    ("00000101111000", "MR"),  // Mauritania
-    ("00000110000000", "MU"),  // Mauritius
-    ("01101000000100", "FM"),  // Micronesia, Federated States of
-    ("01001101010000", "MC"),  // Monaco
-    ("01101000001000", "MN"),  // Mongolia
-    ("000000000110", "MZ"),  // Mozambique
-    ("011100000100", "MM"),  // Myanmar
-    ("00100000000100", "NA"),  // Namibia
-    ("11001000101000", "NR"),  // Nauru
-    ("011100001010", "NP"),  // Nepal
-    ("000011000000", "NI"),  // Nicaragua
-    ("000001100010", "NE"),  // Niger
-    ("000001100100", "NG"),  // Nigeria
-    ("01110000110000", "OM"),  // Oman
-    ("011101100", "PK"),  // Pakistan
-    ("01101000010000", "PW"),  // Palau
-    ("000011000010", "PA"),  // Panama
-    ("100010011000", "PG"),  // Papua New Guinea
-    ("111010001000", "PY"),  // Paraguay
-    ("111010001100", "PE"),  // Peru
-    ("011101011", "PH"),  // Philippines
-    ("00000110101000", "QA"),  // Qatar
-    ("011100011", "KR"),  // Republic of Korea (South Korea)
-    ("01010000010011", "MD"),  // Republic of Moldova
-    ("000001101110", "RW"),  // Rwanda
-    ("11001000110000", "LC"),  // Saint Lucia
-    ("00001011110000", "VC"),  // Saint Vincent and the Grenadines
-    ("10010000001000", "WS"),  // Samoa
-    ("01010000000000", "SM"),  // San Marino
-    ("00001001111000", "ST"),  // Sao Tome and Principe
-    ("011100010", "SA"),  // Saudi Arabia
-    ("000001110000", "SN"),  // Senegal
-    ("00000111010000", "SC"),  // Seychelles
-    ("00000111011000", "SL"),  // Sierra Leone
-    ("011101101", "SG"),  // Singapore
-    ("01010000010111", "SK"),  // Slovakia
-    ("01010000011011", "SI"),  // Slovenia
-    ("10001001011100", "SB"),  // Solomon Islands
-    ("000001111000", "SO"),  // Somalia
-    ("011101110", "LK"),  // Sri Lanka
-    ("000001111100", "SD"),  // Sudan
-    ("000011001000", "SR"),  // Suriname
-    ("00000111101000", "SZ"),  // Swaziland
-    ("01010001010100", "TJ"),  // Tajikistan
-    ("01010001001000", "MK"),  // The former Yugoslav Republic of Macedonia
-    ("000010001000", "TG"),  // Togo
-    ("11001000110100", "TO"),  // Tonga
-    ("000011000110", "TT"),  // Trinidad and Tobago
-    ("01100000000110", "TM"),  // Turkmenistan
-    ("000001101000", "UG"),  // Uganda
-    ("100010010110", "AE"),  // United Arab Emirates
-    ("000010000000", "TZ"),  // United Republic of Tanzania
-    ("111010010000", "UY"),  // Uruguay
-    ("01010000011111", "UZ"),  // Uzbekistan
-    ("11001001000000", "VU"),  // Vanuatu
-    ("100010010000", "YE"),  // Yemen
-    ("000010001010", "ZM"),  // Zambia
-    ("00000000010000", "ZW"),  // Zimbabwe
-    ("10001001100100", "ZZ"),  // ICAO (2)
-    ("11110000100100", "ZZ"),  // ICAO (2)
-
-    // 12-bit prefixes
-    ("011100000000", "AF"),  // Afghanistan
-    ("01100000000000", "AM"),  // Armenia
-    ("01100000000010", "AZ"),  // Azerbaijan
-    ("000010101000", "BS"),  // Bahamas
-    ("100010010100", "BH"),  // Bahrain
-    ("011100000010", "BD"),  // Bangladesh
-    ("01010001000000", "BY"),  // Belarus
-
-    // 9-bit prefixes
-    ("000010100", "DZ"),  // Algeria
-    ("010001000", "AT"),  // Austria
-    ("010001001", "BE"),  // Belgium
-    ("010001010", "BG"),  // Bulgaria
-    ("010001011", "DK"),  // Denmark
-    ("010001100", "FI"),  // Finland
-    ("010001101", "GR"),  // Greece
-    ("010001110", "HU"),  // Hungary
-    ("010001111", "NO"),  // Norway
-    ("100010100", "ID"),  // Indonesia
-    ("010010000", "NL"),  // Netherlands, Kingdom of the
-    ("010010001", "PL"),  // Poland
-    ("010010010", "PT"),  // Portugal
-    ("010010011", "CZ"),  // Czech Republic
-    ("010010100", "RO"),  // Romania
-    ("010010101", "SE"),  // Sweden
-    ("010010110", "CH"),  // Switzerland
-    ("010010111", "TR"),  // Turkey
-    ("110010000", "NZ"),  // New Zealand
-    ("010100001", "UA"),  // Ukraine
-    ("000011010", "MX"),  // Mexico
-    ("000011011", "VE"),  // Venezuela
-    ("100010000", "TH"),  // Thailand
-    ("100010001", "VN"),  // Viet Nam
-    ("010011000", "RS"),  // Yugoslavia
-    ("111100000", "ZZ"),  // ICAO (1)
-
-    // 6-bit prefixes
-    ("111000", "AR"),  // Argentina
-    ("011111", "AU"),  // Australia
-    ("110000", "CA"),  // Canada
-    ("111001", "BR"),  // Brazil
-    ("001110", "FR"),  // France
-    ("001111", "DE"),  // Germany
-    ("100000", "IN"),  // India
-    ("001100", "IT"),  // Italy
-    ("100001", "JP"),  // Japan
-    ("001101", "ES"),  // Spain
-    ("010000", "GB"),  // United Kingdom
-
-    // 4-bit prefixes
-    ("1010", "US"),  // United States
-    ("0001", "RU"),  // Russian Federation
-
-    // 9-bit prefixes (continued, ordered by value)
-    ("000000001", "ZA"),  // South Africa
-    ("000000010", "EG"),  // Egypt
-    ("000000011", "LY"),  // Libyan Arab Jamahiriya
-    ("000000100", "MA"),  // Morocco
-    ("000000101", "TN"),  // Tunisia
-    ("000010010000", "AO"),  // Angola
+//! ICAO 24-bit Address Allocation
+//!
+//! Maps ICAO 24-bit address prefixes to ISO 3166-1 alpha-2 country codes.
+//! Based on ICAO Doc 8643 aircraft type designators and address allocations.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::format;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+use core::str::FromStr;
+#[cfg(feature = "alloc")]
+use once_cell::sync::Lazy;
+
+/// One entry of the allocation table: a binary address prefix and its owner.
+#[derive(Debug, Clone, Copy)]
+struct Allocation {
+    /// First address of the block (the prefix followed by zero bits)
+    start: u32,
+    /// Number of significant prefix bits
+    bits: u32,
+    /// Owning state, or `None` for an ICAO special-use block
+    iso2: Option<&'static str>,
+    special_use: Option<SpecialUse>,
+    name: &'static str,
+}
+
+impl Allocation {
+    /// Mask selecting the prefix bits of a 24-bit address.
+    const fn mask(&self) -> u32 {
+        (0xFFFFFF << (24 - self.bits)) & 0xFFFFFF
+    }
+
+    /// Number of addresses in the block.
+    const fn size(&self) -> u32 {
+        1 << (24 - self.bits)
+    }
+
+    const fn contains(&self, icao_u32: u32) -> bool {
+        icao_u32 & self.mask() == self.start
+    }
+
+    fn country_allocation(&self) -> CountryAllocation {
+        CountryAllocation {
+            iso2: self.iso2,
+            special_use: self.special_use,
+            name: self.name,
+            start: self.start,
+            end: self.start + self.size() - 1,
+            size: self.size(),
+        }
+    }
+}
+
+/// Build a state `Allocation` from a binary prefix string at compile time.
+const fn alloc(binary_prefix: &str, iso2: &'static str, name: &'static str) -> Allocation {
+    prefix_allocation(binary_prefix, Some(iso2), None, name)
+}
+
+/// Build an ICAO special-use `Allocation` from a binary prefix string at compile time.
+const fn special(binary_prefix: &str, special_use: SpecialUse, name: &'static str) -> Allocation {
+    prefix_allocation(binary_prefix, None, Some(special_use), name)
+}
+
+const fn prefix_allocation(
+    binary_prefix: &str,
+    iso2: Option<&'static str>,
+    special_use: Option<SpecialUse>,
+    name: &'static str,
+) -> Allocation {
+    let digits = binary_prefix.as_bytes();
+    assert!(!digits.is_empty() && digits.len() <= 24, "prefix must have 1 to 24 bits");
+
+    let mut value = 0u32;
+    let mut i = 0;
+    while i < digits.len() {
+        assert!(digits[i] == b'0' || digits[i] == b'1', "prefix must be binary");
+        value = (value << 1) | (digits[i] - b'0') as u32;
+        i += 1;
+    }
+
+    let bits = digits.len() as u32;
+    Allocation {
+        start: value << (24 - bits),
+        bits,
+        iso2,
+        special_use,
+        name,
+    }
+}
+
+// ICAO address allocations, generated by `build.rs` from `data/icao-allocations.csv`
+// as `alloc(binary_prefix, iso2_country_code, country_name)` and
+// `special(binary_prefix, SpecialUse::Kind, block_name)` entries. Sorted by
+// prefix length (longest first) to ensure correct prefix matching.
+//
+// Kosovo, South Sudan and Timor-Leste have no block of their own here yet;
+// addresses operated by those states resolve to `None`.
+include!(concat!(env!("OUT_DIR"), "/icao_allocations.rs"));
+
+/// Convert a 24-bit ICAO address (as u32) to its allocated country's ISO2 code.
+///
+/// This is a convenience function that validates the input is a valid 24-bit value
+/// (i.e., the 8 most significant bits are zero) before delegating to `icao_to_country`.
+///
+/// Like `icao_to_country`, this is a `const fn` and can be evaluated at compile time.
+///
+/// # Arguments
+/// * `icao_u32` - 24-bit ICAO address as a u32 (must be <= 0xFFFFFF)
+///
+/// # Returns
+/// * `Some(&str)` - ISO 3166-1 alpha-2 country code if allocation found
+/// * `None` - If the value is invalid (> 24 bits), no allocation matches, or the
+///   address is in an ICAO special-use block (see `special_use`)
+///
+/// # Examples
+/// ```
+/// use flydent::icao::icao_u32_to_country;
+///
+/// // United States allocation (0xAB8E4F = N8437D)
+/// assert_eq!(icao_u32_to_country(0xAB8E4F), Some("US"));
+///
+/// // Serbia (block originally allocated to Yugoslavia)
+/// assert_eq!(icao_u32_to_country(0x4C0000), Some("RS"));
+///
+/// // Invalid: more than 24 bits
+/// assert_eq!(icao_u32_to_country(0x01000000), None);
+/// ```
+pub const fn icao_u32_to_country(icao_u32: u32) -> Option<&'static str> {
+    // Check if the 8 MSB are zero (valid 24-bit value)
+    if icao_u32 > 0xFFFFFF {
+        return None;
+    }
+
+    match find_allocation(icao_u32) {
+        Some(allocation) => allocation.iso2,
+        None => None,
+    }
+}
+
+/// Convert a 24-bit ICAO address to its allocated country's ISO2 code.
+///
+/// # Arguments
+/// * `icao` - 24-bit ICAO address as a 3-byte array (big-endian)
+///
+/// # Returns
+/// * `Some(&str)` - ISO 3166-1 alpha-2 country code if allocation found
+/// * `None` - If no allocation matches the address
+///
+/// # Examples
+/// ```
+/// use flydent::icao::icao_to_country;
+///
+/// // United States allocation (starts with 1010)
+/// let us_icao = [0xAB, 0x8E, 0x4F];  // Binary: 1010 1011 1000 1110 0100 1111
+/// assert_eq!(icao_to_country(us_icao), Some("US"));
+///
+/// // Serbia, former Yugoslavia block (starts with 010011000)
+/// let yu_icao = [0x4C, 0x00, 0x00];  // Binary: 0100 1100 0000 0000 0000 0000
+/// assert_eq!(icao_to_country(yu_icao), Some("RS"));
+/// ```
+pub const fn icao_to_country(icao: [u8; 3]) -> Option<&'static str> {
+    let icao_u32 = ((icao[0] as u32) << 16) | ((icao[1] as u32) << 8) | (icao[2] as u32);
+    icao_u32_to_country(icao_u32)
+}
+
+/// Find the first allocation table entry whose prefix matches the address.
+const fn find_allocation(icao_u32: u32) -> Option<&'static Allocation> {
+    // Check each allocation prefix (already sorted longest-first)
+    let mut i = 0;
+    while i < ICAO_ALLOCATIONS.len() {
+        if ICAO_ALLOCATIONS[i].contains(icao_u32) {
+            return Some(&ICAO_ALLOCATIONS[i]);
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Purpose of an address block administered by ICAO itself rather than a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum SpecialUse {
+    /// ICAO (1): addresses issued temporarily, e.g. to aircraft awaiting a
+    /// permanent allocation from their state of registry
+    Temporary,
+    /// ICAO (2): addresses reserved for special use in the interests of flight safety
+    FlightSafety,
+}
+
+impl SpecialUse {
+    /// Human-readable description of the block's purpose.
+    pub fn description(&self) -> &'static str {
+        match self {
+            SpecialUse::Temporary => "ICAO temporary address allocation",
+            SpecialUse::FlightSafety => "ICAO reserved for flight safety",
+        }
+    }
+}
+
+impl fmt::Display for SpecialUse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Find the ICAO special-use block containing a 24-bit address, if any.
+///
+/// These blocks belong to no state, so `icao_u32_to_country` returns `None` for them.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::{special_use, SpecialUse};
+///
+/// assert_eq!(special_use(0xF00000), Some(SpecialUse::Temporary));
+/// assert_eq!(special_use(0xF09000), Some(SpecialUse::FlightSafety));
+/// assert_eq!(special_use(0xAB8E4F), None);
+/// ```
+pub const fn special_use(icao_u32: u32) -> Option<SpecialUse> {
+    if icao_u32 > 0xFFFFFF {
+        return None;
+    }
+
+    match find_allocation(icao_u32) {
+        Some(allocation) => allocation.special_use,
+        None => None,
+    }
+}
+
+/// Check whether a 24-bit address falls inside a state's allocation.
+///
+/// Equivalent to `icao_u32_to_country(icao_u32).is_some()`. Values wider than
+/// 24 bits are never allocated.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::is_allocated;
+///
+/// assert!(is_allocated(0xAB8E4F));
+/// assert!(!is_allocated(0xF00000));  // ICAO special-use block
+/// assert!(!is_allocated(0xFFFFFF));  // unassigned
+/// ```
+pub const fn is_allocated(icao_u32: u32) -> bool {
+    icao_u32_to_country(icao_u32).is_some()
+}
+
+/// Check whether a 24-bit address falls inside a block reserved by ICAO itself.
+///
+/// Equivalent to `special_use(icao_u32).is_some()`. Note that this is narrower
+/// than `AddressClass::Reserved`, which covers unassigned space (see `is_unassigned`).
+///
+/// # Examples
+/// ```
+/// use flydent::icao::is_reserved;
+///
+/// assert!(is_reserved(0xF00000));
+/// assert!(is_reserved(0xF09000));
+/// assert!(!is_reserved(0xAB8E4F));
+/// assert!(!is_reserved(0xFFFFFF));
+/// ```
+pub const fn is_reserved(icao_u32: u32) -> bool {
+    special_use(icao_u32).is_some()
+}
+
+/// Check whether a 24-bit address is covered by no block at all.
+///
+/// Such addresses belong neither to a state nor to an ICAO special-use block and
+/// usually indicate a corrupted or made-up address. Values wider than 24 bits
+/// are not addresses and return `false`.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::is_unassigned;
+///
+/// assert!(is_unassigned(0xFFFFFF));
+/// assert!(!is_unassigned(0xAB8E4F));
+/// assert!(!is_unassigned(0xF00000));
+/// assert!(!is_unassigned(0x01000000));
+/// ```
+pub const fn is_unassigned(icao_u32: u32) -> bool {
+    icao_u32 <= 0xFFFFFF && find_allocation(icao_u32).is_none()
+}
+
+/// A single ICAO address block together with the state it is allocated to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountryAllocation {
+    /// ISO 3166-1 alpha-2 country code, or `None` for ICAO special-use blocks
+    pub iso2: Option<&'static str>,
+    /// Purpose of the block when it is administered by ICAO rather than a state
+    pub special_use: Option<SpecialUse>,
+    /// Country or block name as listed in the allocation table
+    pub name: &'static str,
+    /// First address of the block
+    pub start: u32,
+    /// Last address of the block (inclusive)
+    pub end: u32,
+    /// Number of addresses in the block
+    pub size: u32,
+}
+
+impl CountryAllocation {
+    /// Whether this is an ICAO-administered special block rather than a state allocation.
+    pub fn is_special(&self) -> bool {
+        self.special_use.is_some()
+    }
+
+    /// First address of the block as a 6-digit uppercase hex string.
+    #[cfg(feature = "alloc")]
+    pub fn start_hex(&self) -> String {
+        format!("{:06X}", self.start)
+    }
+
+    /// Last address of the block as a 6-digit uppercase hex string.
+    #[cfg(feature = "alloc")]
+    pub fn end_hex(&self) -> String {
+        format!("{:06X}", self.end)
+    }
+}
+
+/// Look up the allocation block containing a 24-bit ICAO address.
+///
+/// Unlike `icao_u32_to_country`, this returns the full allocation: country name,
+/// block boundaries and size. The boundaries are those of the matching table entry,
+/// so an address in a sub-block (such as Hong Kong inside China) reports the
+/// sub-block.
+///
+/// # Arguments
+/// * `icao_u32` - 24-bit ICAO address as a u32 (must be <= 0xFFFFFF)
+///
+/// # Returns
+/// * `Some(CountryAllocation)` - The matching allocation block
+/// * `None` - If the value is invalid (> 24 bits) or no allocation matches
+///
+/// # Examples
+/// ```
+/// use flydent::icao::lookup;
+///
+/// let allocation = lookup(0xAB8E4F).unwrap();
+/// assert_eq!(allocation.iso2, Some("US"));
+/// assert_eq!(allocation.name, "United States");
+/// assert_eq!(allocation.start_hex(), "A00000");
+/// assert_eq!(allocation.end_hex(), "AFFFFF");
+/// assert_eq!(allocation.size, 0x100000);
+/// assert!(!allocation.is_special());
+/// ```
+pub fn lookup(icao_u32: u32) -> Option<CountryAllocation> {
+    if icao_u32 > 0xFFFFFF {
+        return None;
+    }
+
+    find_allocation(icao_u32).map(Allocation::country_allocation)
+}
+
+/// Every block of the allocation table, state and special-use, longest prefix first.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::allocations;
+///
+/// let blocks = allocations();
+/// assert!(blocks.iter().any(|block| block.iso2 == Some("US") && block.start_hex() == "A00000"));
+/// assert!(blocks.iter().any(|block| block.is_special()));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn allocations() -> Vec<CountryAllocation> {
+    ICAO_ALLOCATIONS.iter().map(Allocation::country_allocation).collect()
+}
+
+/// Every block of the allocation table, like `allocations`, but borrowed from
+/// the static table without allocating. Requires the `core-only` feature.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::allocation_table;
+///
+/// let us = allocation_table().find(|block| block.iso2 == Some("US")).unwrap();
+/// assert_eq!((us.start, us.end), (0xA00000, 0xAFFFFF));
+/// ```
+#[cfg(feature = "core-only")]
+pub fn allocation_table() -> impl ExactSizeIterator<Item = CountryAllocation> + Clone {
+    ICAO_ALLOCATIONS.iter().map(Allocation::country_allocation)
+}
+
+/// Flattened, non-overlapping `(start, end, iso2)` ranges covering every allocated
+/// address, ordered by `start`.
+///
+/// Built by splitting the 24-bit space at every prefix boundary and resolving each
+/// piece with `icao_u32_to_country`, so it always agrees with the prefix table.
+#[cfg(feature = "alloc")]
+static ALLOCATION_RANGES: Lazy<Vec<(u32, u32, &'static str)>> = Lazy::new(|| {
+    let mut boundaries = vec![0u32, 0x1000000];
+    for allocation in ICAO_ALLOCATIONS {
+        boundaries.push(allocation.start);
+        boundaries.push(allocation.start + allocation.size());
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut ranges: Vec<(u32, u32, &'static str)> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1] - 1);
+        if let Some(country_code) = icao_u32_to_country(start) {
+            match ranges.last_mut() {
+                Some(last) if last.2 == country_code && last.1 + 1 == start => last.1 = end,
+                _ => ranges.push((start, end, country_code)),
+            }
+        }
+    }
+    ranges
+});
+
+/// Country that every address of a hex prefix is allocated to.
+///
+/// `hex` holds one to six hex digits and stands for all addresses that begin with
+/// them, e.g. `"7C"` covers `0x7C0000..=0x7CFFFF`. The result is `None` when the
+/// prefix is not valid hex or when its addresses are split between several
+/// countries or unallocated space, so a prefix that ends mid-nibble inside an
+/// allocation (Austria's 9-bit `010001000`, say) still resolves correctly.
+#[cfg(feature = "alloc")]
+pub(crate) fn prefix_country(hex: &str) -> Option<&'static str> {
+    if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let shift = 4 * (6 - hex.len() as u32);
+    let start = u32::from_str_radix(hex, 16).ok()? << shift;
+    let end = start + ((1 << shift) - 1);
+
+    let idx = ALLOCATION_RANGES.partition_point(|&(_, range_end, _)| range_end < start);
+    match ALLOCATION_RANGES.get(idx) {
+        Some(&(range_start, range_end, country_code)) if range_start <= start && end <= range_end => {
+            Some(country_code)
+        }
+        _ => None,
+    }
+}
+
+/// Classify many 24-bit ICAO addresses at once.
+///
+/// Equivalent to calling `icao_u32_to_country` on every element, but resolves each
+/// address with a binary search over a pre-merged range table instead of matching
+/// prefixes one by one, and reuses the previous answer for runs of addresses that
+/// fall in the same allocation. Intended for bulk work such as historical ADS-B dumps
+/// with tens of millions of rows.
+///
+/// # Arguments
+/// * `addresses` - 24-bit ICAO addresses as u32 values
+///
+/// # Returns
+/// A vector with one entry per input address, in input order. Values with more than
+/// 24 bits or without a matching allocation yield `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::classify_many;
+///
+/// let countries = classify_many(&[0xAB8E4F, 0x4C0000, 0xFFFFFF, 0x01000000]);
+/// assert_eq!(countries, vec![Some("US"), Some("RS"), None, None]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn classify_many(addresses: &[u32]) -> Vec<Option<&'static str>> {
+    let mut results = Vec::with_capacity(addresses.len());
+    // Range of the most recent hit; consecutive addresses often share an allocation
+    let mut cached: Option<(u32, u32, &'static str)> = None;
+
+    for &icao_u32 in addresses {
+        if icao_u32 > 0xFFFFFF {
+            results.push(None);
+            continue;
+        }
+        if let Some((start, end, country_code)) = cached {
+            if (start..=end).contains(&icao_u32) {
+                results.push(Some(country_code));
+                continue;
+            }
+        }
+
+        let idx = ALLOCATION_RANGES.partition_point(|&(_, end, _)| end < icao_u32);
+        match ALLOCATION_RANGES.get(idx) {
+            Some(&range) if range.0 <= icao_u32 => {
+                cached = Some(range);
+                results.push(Some(range.2));
+            }
+            _ => results.push(None),
+        }
+    }
+
+    results
+}
+
+/// List the address ranges allocated to a country.
+///
+/// Ranges are taken from the flattened allocation table, so sub-blocks that belong
+/// to another entry (such as Hong Kong inside China) are excluded and adjacent blocks
+/// of the same country are merged. The comparison on `iso2` is case-insensitive.
+///
+/// # Arguments
+/// * `iso2` - ISO 3166-1 alpha-2 country code
+///
+/// # Returns
+/// The inclusive ranges in ascending order; empty if the country has no allocation.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::allocation_range;
+///
+/// assert_eq!(allocation_range("US"), vec![0xA00000..=0xAFFFFF]);
+/// assert_eq!(allocation_range("CN").len(), 2); // Hong Kong and Macao are carved out
+/// assert!(allocation_range("XX").is_empty());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn allocation_range(iso2: &str) -> Vec<RangeInclusive<u32>> {
+    ranges_for(iso2).to_vec()
+}
+
+/// Effective address ranges per ISO2 code, derived from `ALLOCATION_RANGES`.
+#[cfg(feature = "alloc")]
+static RANGES_BY_COUNTRY: Lazy<BTreeMap<&'static str, Vec<RangeInclusive<u32>>>> =
+    Lazy::new(|| {
+        let mut map: BTreeMap<&'static str, Vec<RangeInclusive<u32>>> = BTreeMap::new();
+        for &(start, end, country_code) in ALLOCATION_RANGES.iter() {
+            map.entry(country_code).or_default().push(start..=end);
+        }
+        map
+    });
+
+/// Reverse lookup: the address ranges allocated to a country.
+///
+/// Same ranges as `allocation_range`, but borrowed from a table built once, which
+/// makes it cheap enough to call per message when filtering raw Mode S traffic to a
+/// country of interest. The comparison on `iso2` is case-insensitive.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::ranges_for;
+///
+/// let swiss = ranges_for("CH");
+/// assert_eq!(swiss, &[0x4B0000..=0x4B7FFF]);
+/// assert!(swiss.iter().any(|range| range.contains(&0x4B1234)));
+/// assert!(ranges_for("XX").is_empty());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn ranges_for(iso2: &str) -> &'static [RangeInclusive<u32>] {
+    RANGES_BY_COUNTRY
+        .get(iso2.to_ascii_uppercase().as_str())
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// Lazily iterate over every address allocated to a country, in ascending order.
+///
+/// Covers exactly the ranges returned by `ranges_for`. Together with the
+/// `registration` converters this can produce complete hex-to-registration tables.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::{allocation_size, iter_addresses};
+///
+/// let mut addresses = iter_addresses("BE");
+/// assert_eq!(addresses.next(), Some(0x448000));
+/// assert_eq!(iter_addresses("BE").count() as u32, allocation_size("BE"));
+/// assert_eq!(iter_addresses("XX").next(), None);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn iter_addresses(iso2: &str) -> impl Iterator<Item = u32> {
+    ranges_for(iso2).iter().flat_map(|range| range.clone())
+}
+
+/// Pick a uniformly random address within a country's allocation.
+///
+/// Every address returned by `ranges_for(iso2)` is equally likely, even when the
+/// country has several blocks of different sizes. Requires the `rand` feature.
+///
+/// # Returns
+/// * `Some(u32)` - A random address allocated to the country
+/// * `None` - If the country has no allocation
+///
+/// # Examples
+/// ```
+/// use flydent::icao::{icao_u32_to_country, random_address};
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+/// let address = random_address("DE", &mut rng).unwrap();
+/// assert_eq!(icao_u32_to_country(address), Some("DE"));
+/// ```
+#[cfg(feature = "rand")]
+pub fn random_address<R: rand::Rng + ?Sized>(iso2: &str, rng: &mut R) -> Option<u32> {
+    let total = allocation_size(iso2);
+    if total == 0 {
+        return None;
+    }
+
+    nth_address(iso2, rng.random_range(0..total))
+}
+
+/// The `offset`-th address of a country's allocation, counting across all of its
+/// ranges in address order; equivalent to `iter_addresses(iso2).nth(offset)`.
+#[cfg(any(feature = "rand", feature = "proptest"))]
+pub(crate) fn nth_address(iso2: &str, mut offset: u32) -> Option<u32> {
+    for range in ranges_for(iso2) {
+        let size = range.end() - range.start() + 1;
+        if offset < size {
+            return Some(range.start() + offset);
+        }
+        offset -= size;
+    }
+
+    None
+}
+
+/// Reverse lookup: the binary prefixes allocated to a country in the allocation table.
+///
+/// Unlike `ranges_for`, these are the raw table entries, so a block with nested
+/// sub-allocations (such as China) is reported by its full prefix.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::prefixes_for;
+///
+/// assert_eq!(prefixes_for("US"), vec!["1010"]);
+/// assert_eq!(prefixes_for("ag"), vec!["00001100101000"]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn prefixes_for(iso2: &str) -> Vec<String> {
+    ICAO_ALLOCATIONS
+        .iter()
+        .filter(|allocation| allocation.iso2.is_some_and(|code| code.eq_ignore_ascii_case(iso2)))
+        .map(|allocation| {
+            format!(
+                "{:0width$b}",
+                allocation.start >> (24 - allocation.bits),
+                width = allocation.bits as usize
+            )
+        })
+        .collect()
+}
+
+/// Total number of addresses allocated to a country.
+///
+/// This is the sum of the sizes of the ranges returned by `allocation_range`.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::allocation_size;
+///
+/// assert_eq!(allocation_size("US"), 0x100000);
+/// assert_eq!(allocation_size("AG"), 1024);
+/// assert_eq!(allocation_size("XX"), 0);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn allocation_size(iso2: &str) -> u32 {
+    allocation_range(iso2)
+        .iter()
+        .map(|range| range.end() - range.start() + 1)
+        .sum()
+}
+
+/// The part of an address range attributed to one allocation block, as
+/// reported by `countries_in_range`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeShare {
+    pub allocation: CountryAllocation,
+    /// First address of the range in the block
+    pub start: u32,
+    /// Last address of the range in the block (inclusive)
+    pub end: u32,
+    /// Number of addresses of the range in the block. Smaller than
+    /// `end - start + 1` when a sub-block (such as Hong Kong inside China) is
+    /// carved out in between.
+    pub size: u32,
+}
+
+/// The allocation blocks, state and special-use, that intersect an address
+/// range, in order of their first address in it, with the size of each
+/// overlap; e.g. to partition the 24-bit space for sharded processing.
+///
+/// Every address is attributed to the block `lookup` returns for it.
+/// Unallocated addresses are not reported, and the range is clipped to 24 bits.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::countries_in_range;
+///
+/// let shares = countries_in_range(0xA00000..=0xAFFFFF);
+/// assert_eq!(shares.len(), 1);
+/// assert_eq!((shares[0].allocation.iso2, shares[0].size), (Some("US"), 0x100000));
+///
+/// // The upper half of the China block holds Hong Kong and Macao
+/// let shares = countries_in_range(0x780000..=0x7BFFFF);
+/// let codes: Vec<_> = shares.iter().map(|share| share.allocation.iso2).collect();
+/// assert_eq!(codes, [Some("CN"), Some("HK"), Some("MO")]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn countries_in_range(range: RangeInclusive<u32>) -> Vec<RangeShare> {
+    let (first, last) = (*range.start(), (*range.end()).min(0xFFFFFF));
+    if first > last {
+        return Vec::new();
+    }
+
+    // The owning block can only change where a block starts or ends
+    let mut boundaries = vec![first, last + 1];
+    for allocation in ICAO_ALLOCATIONS {
+        for boundary in [allocation.start, allocation.start + allocation.size()] {
+            if first < boundary && boundary <= last {
+                boundaries.push(boundary);
+            }
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut shares: Vec<(&'static Allocation, RangeShare)> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1] - 1);
+        let Some(allocation) = find_allocation(start) else {
+            continue;
+        };
+        match shares.iter_mut().find(|(block, _)| core::ptr::eq(*block, allocation)) {
+            Some((_, share)) => {
+                share.end = end;
+                share.size += end - start + 1;
+            }
+            None => shares.push((
+                allocation,
+                RangeShare { allocation: allocation.country_allocation(), start, end, size: end - start + 1 },
+            )),
+        }
+    }
+    shares.into_iter().map(|(_, share)| share).collect()
+}
+
+/// Sub-blocks that states commonly reserve for military aircraft, as
+/// (first_address, last_address, iso2_country_code) tuples.
+///
+/// These are not published by ICAO. The table is a subset of the ranges that
+/// ADS-B trackers such as readsb and tar1090 use to flag military traffic, so a
+/// match means "likely military" and no match does not rule it out. Left out
+/// are the smaller blocks those lists give for further states, among them
+/// Denmark, Greece, Norway, Portugal, Hungary, the Czech Republic, Egypt, Saudi
+/// Arabia, Oman and Chile, as well as additional blocks for states listed here
+/// (e.g. the United Kingdom and Australia).
+const MILITARY_BLOCKS: &[(u32, u32, &str)] = &[
+    (0xADF7C8, 0xAFFFFF, "US"),  // Above the N-number range
+    (0x43C000, 0x43CFFF, "GB"),
+    (0x3AA000, 0x3AFFFF, "FR"),
+    (0x3B7000, 0x3BFFFF, "FR"),
+    (0x3EA000, 0x3EBFFF, "DE"),
+    (0x3F4000, 0x3FBFFF, "DE"),
+    (0x33FF00, 0x33FFFF, "IT"),
+    (0x350000, 0x37FFFF, "ES"),
+    (0x444000, 0x446FFF, "AT"),
+    (0x44F000, 0x44FFFF, "BE"),
+    (0x480000, 0x480FFF, "NL"),
+    (0x48D800, 0x48D87F, "PL"),
+    (0x4B7000, 0x4B7FFF, "CH"),
+    (0x4B8200, 0x4B82FF, "TR"),
+    (0x738A00, 0x738AFF, "IL"),
+    (0x7CF800, 0x7CFAFF, "AU"),
+    (0x800200, 0x8002FF, "IN"),
+    (0xC20000, 0xC3FFFF, "CA"),
+    (0xE40000, 0xE41FFF, "BR"),
 ];
 
-/// Convert a 24-bit ICAO address (as u32) to its allocated country's ISO2 code.
-///
-/// This is a convenience function that validates the input is a valid 24-bit value
-/// (i.e., the 8 most significant bits are zero) before delegating to `icao_to_country`.
-///
-/// # Arguments
-/// * `icao_u32` - 24-bit ICAO address as a u32 (must be <= 0xFFFFFF)
-///
-/// # Returns
-/// * `Some(&str)` - ISO 3166-1 alpha-2 country code if allocation found
-/// * `None` - If the value is invalid (> 24 bits) or no allocation matches
+/// Broad usage class of a 24-bit ICAO address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressClass {
+    /// Inside a state allocation and not in a known military sub-block
+    Civil,
+    /// Inside a sub-block a state commonly uses for military aircraft
+    Military,
+    /// Not covered by any allocation (unassigned or reserved space)
+    Reserved,
+    /// Inside an ICAO-administered special block
+    SpecialUse,
+    /// Marked by the decoder as not an ICAO address (TIS-B track file, UAT
+    /// self-assigned address or other non-ICAO source)
+    NonIcao,
+}
+
+/// Flag that readsb/dump1090 set on bit 24 of an address that is not an ICAO
+/// 24-bit address (TIS-B track files, UAT self-assigned addresses, ...).
+pub const NON_ICAO_FLAG: u32 = 1 << 24;
+
+/// Check whether a numeric address carries the readsb/dump1090 non-ICAO flag.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::{is_non_icao, NON_ICAO_FLAG};
+///
+/// assert!(is_non_icao(NON_ICAO_FLAG | 0xAB8E4F));
+/// assert!(!is_non_icao(0xAB8E4F));
+/// ```
+pub fn is_non_icao(address: u32) -> bool {
+    address & !0xFFFFFF == NON_ICAO_FLAG
+}
+
+/// Check whether a hex address string is marked as non-ICAO.
+///
+/// readsb, dump1090 and tar1090 print non-ICAO addresses with a leading `~`
+/// (e.g. `"~2a3b4c"`). Only addresses marked this way, or with
+/// `NON_ICAO_FLAG`, are detected: FAA anonymous and UAT self-assigned addresses
+/// have no range of their own, so unmarked ones look like ordinary addresses
+/// and are attributed to the allocation they fall in.
+///
+/// # Examples
+/// ```
+/// use flydent::icao::is_non_icao_hex;
+///
+/// assert!(is_non_icao_hex("~2a3b4c"));
+/// assert!(!is_non_icao_hex("AB8E4F"));
+/// ```
+pub fn is_non_icao_hex(address: &str) -> bool {
+    address.trim_start().starts_with('~')
+}
+
+/// Classify how a 24-bit ICAO address is used.
+///
+/// The classification is layered over the country lookup: ICAO special blocks are
+/// `SpecialUse`, unallocated space is `Reserved`, and addresses inside a state's
+/// allocation are `Military` when they fall in one of the well-known military
+/// sub-blocks and `Civil` otherwise. Only a subset of the military blocks that
+/// ADS-B trackers flag is known, so `Civil` does not rule out military use.
+/// Addresses carrying `NON_ICAO_FLAG` are `NonIcao` and never attributed to a
+/// country.
+///
+/// # Arguments
+/// * `icao_u32` - 24-bit ICAO address as a u32, optionally with `NON_ICAO_FLAG` set
+///
+/// # Returns
+/// * `Some(AddressClass)` - The usage class of the address
+/// * `None` - If the value is invalid (> 24 bits and not a flagged non-ICAO address)
+///
+/// # Examples
+/// ```
+/// use flydent::icao::{address_class, AddressClass};
+///
+/// assert_eq!(address_class(0xAB8E4F), Some(AddressClass::Civil));
+/// assert_eq!(address_class(0xAE1234), Some(AddressClass::Military));
+/// assert_eq!(address_class(0xF00000), Some(AddressClass::SpecialUse));
+/// assert_eq!(address_class(0xFFFFFF), Some(AddressClass::Reserved));
+/// assert_eq!(address_class(0x01AB8E4F), Some(AddressClass::NonIcao));
+/// assert_eq!(address_class(0x02000000), None);
+/// ```
+pub fn address_class(icao_u32: u32) -> Option<AddressClass> {
+    if is_non_icao(icao_u32) {
+        return Some(AddressClass::NonIcao);
+    }
+    if icao_u32 > 0xFFFFFF {
+        return None;
+    }
+
+    let class = match find_allocation(icao_u32) {
+        None => AddressClass::Reserved,
+        Some(Allocation { special_use: Some(_), .. }) => AddressClass::SpecialUse,
+        Some(Allocation { iso2: None, .. }) => AddressClass::Reserved,
+        Some(Allocation { iso2: Some(country_code), .. }) => {
+            let military = MILITARY_BLOCKS.iter().any(|&(start, end, iso2)| {
+                iso2 == *country_code && (start..=end).contains(&icao_u32)
+            });
+            if military {
+                AddressClass::Military
+            } else {
+                AddressClass::Civil
+            }
+        }
+    };
+
+    Some(class)
+}
+
+/// A validated 24-bit ICAO aircraft address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug)))]
+pub struct Icao24(u32);
+
+/// Error returned when a string cannot be parsed as an ICAO 24-bit address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum ParseHexError {
+    /// The input was empty (after trimming whitespace and any `0x` prefix)
+    Empty,
+    /// The input contained a character that is neither a hex digit nor a separator
+    InvalidCharacter(char),
+    /// The input did not contain exactly six hex digits
+    InvalidLength(usize),
+    /// Separated input was not three groups of two hex digits split by the
+    /// same separator
+    InvalidGrouping,
+}
+
+impl fmt::Display for ParseHexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHexError::Empty => write!(f, "empty ICAO address"),
+            ParseHexError::InvalidCharacter(c) => {
+                write!(f, "invalid character {:?} in ICAO address", c)
+            }
+            ParseHexError::InvalidLength(len) => {
+                write!(f, "ICAO address must have 6 hex digits, found {}", len)
+            }
+            ParseHexError::InvalidGrouping => {
+                write!(f, "separated ICAO address must be three groups of two hex digits with one kind of separator")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseHexError {}
+
+/// Characters accepted between the bytes of a hex address ("AB 8E 4F",
+/// "ab:8e:4f", "AB-8E-4F").
+const HEX_SEPARATORS: &[char] = &[' ', ':', '-'];
+
+impl Icao24 {
+    /// Create an address from a u32, returning `None` if it has more than 24 bits.
+    pub const fn new(icao_u32: u32) -> Option<Self> {
+        if icao_u32 > 0xFFFFFF {
+            None
+        } else {
+            Some(Icao24(icao_u32))
+        }
+    }
+
+    /// Create an address from a big-endian 3-byte array.
+    pub const fn from_bytes(icao: [u8; 3]) -> Self {
+        Icao24(((icao[0] as u32) << 16) | ((icao[1] as u32) << 8) | (icao[2] as u32))
+    }
+
+    /// The address as a u32.
+    pub const fn value(self) -> u32 {
+        self.0
+    }
+
+    /// The address as a big-endian 3-byte array.
+    pub const fn to_bytes(self) -> [u8; 3] {
+        [(self.0 >> 16) as u8, (self.0 >> 8) as u8, self.0 as u8]
+    }
+
+    /// ISO2 code of the country the address is allocated to.
+    pub const fn country(self) -> Option<&'static str> {
+        icao_u32_to_country(self.0)
+    }
+
+    /// Parse a hex address in any of the common notations.
+    ///
+    /// Accepts upper- or lowercase digits, an optional `0x` prefix, and bytes
+    /// separated by spaces, colons or dashes (one kind throughout), as SDR logs and Wireshark exports
+    /// write them. Surrounding whitespace is ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::icao::Icao24;
+    ///
+    /// for input in ["AB8E4F", "ab8e4f", "0xAB8E4F", "AB 8E 4F", "ab:8e:4f", "AB-8E-4F"] {
+    ///     assert_eq!(Icao24::parse_hex(input).unwrap().value(), 0xAB8E4F);
+    /// }
+    /// assert!(Icao24::parse_hex("AB8E4").is_err());
+    /// assert!(Icao24::parse_hex("XYZ123").is_err());
+    /// ```
+    pub fn parse_hex(input: &str) -> Result<Self, ParseHexError> {
+        let trimmed = input.trim();
+        let digits = trimmed
+            .strip_prefix("0x")
+            .or_else(|| trimmed.strip_prefix("0X"))
+            .unwrap_or(trimmed);
+
+        if digits.is_empty() {
+            return Err(ParseHexError::Empty);
+        }
+
+        if let Some(c) = digits
+            .chars()
+            .find(|c| !c.is_ascii_hexdigit() && !HEX_SEPARATORS.contains(c))
+        {
+            return Err(ParseHexError::InvalidCharacter(c));
+        }
+
+        // All bytes are separated by the same character
+        if let Some(separator) = digits.chars().find(|c| HEX_SEPARATORS.contains(c)) {
+            let mut groups = digits.split(separator);
+            if groups.clone().count() != 3 || groups.any(|group| group.len() != 2) {
+                return Err(ParseHexError::InvalidGrouping);
+            }
+        }
+
+        let hex = digits.chars().filter_map(|c| c.to_digit(16));
+        let len = hex.clone().count();
+        if len != 6 {
+            return Err(ParseHexError::InvalidLength(len));
+        }
+
+        // Six validated hex digits always fit in 24 bits
+        Ok(Icao24(hex.fold(0, |value, digit| (value << 4) | digit)))
+    }
+}
+
+impl fmt::Display for Icao24 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:06X}", self.0)
+    }
+}
+
+impl FromStr for Icao24 {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Icao24::parse_hex(s)
+    }
+}
+
+impl From<[u8; 3]> for Icao24 {
+    fn from(icao: [u8; 3]) -> Self {
+        Icao24::from_bytes(icao)
+    }
+}
+
+impl From<Icao24> for u32 {
+    fn from(icao: Icao24) -> Self {
+        icao.0
+    }
+}
+
+/// Logged as six uppercase hex digits, like `Display`. Like the derived
+/// `defmt::Format` impls elsewhere, only compiled for bare-metal targets: defmt
+/// has no logger on hosted ones, and its interned symbols cannot be exported
+/// from a `cdylib` build of the crate.
+#[cfg(all(feature = "defmt", target_os = "none"))]
+impl defmt::Format for Icao24 {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u32:06X}", self.0)
+    }
+}
+
+/// Error returned when a value has more than 24 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub struct AddressRangeError(pub u32);
+
+impl fmt::Display for AddressRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#X} is not a 24-bit ICAO address", self.0)
+    }
+}
+
+impl core::error::Error for AddressRangeError {}
+
+impl TryFrom<u32> for Icao24 {
+    type Error = AddressRangeError;
+
+    fn try_from(icao_u32: u32) -> Result<Self, Self::Error> {
+        Icao24::new(icao_u32).ok_or(AddressRangeError(icao_u32))
+    }
+}
+
+/// Country lookup for the ICAO address types of ADS-B decoders, so decoded
+/// messages can be attributed without converting by hand.
 ///
 /// # Examples
 /// ```
-/// use flydent::icao::icao_u32_to_country;
-///
-/// // United States allocation (0xAB8E4F = N8437D)
-/// assert_eq!(icao_u32_to_country(0xAB8E4F), Some("US"));
-///
-/// // Yugoslavia allocation
-/// assert_eq!(icao_u32_to_country(0x4C0000), Some("RS"));
+/// use flydent::icao::{AddressCountry, Icao24};
 ///
-/// // Invalid: more than 24 bits
-/// assert_eq!(icao_u32_to_country(0x01000000), None);
+/// assert_eq!(Icao24::from_bytes([0xAB, 0x8E, 0x4F]).country(), Some("US"));
+/// assert_eq!(AddressCountry::country(&0x3C65A1u32), Some("DE"));
 /// ```
-pub fn icao_u32_to_country(icao_u32: u32) -> Option<&'static str> {
-    // Check if the 8 MSB are zero (valid 24-bit value)
-    if icao_u32 > 0xFFFFFF {
-        return None;
+pub trait AddressCountry {
+    /// ISO2 code of the country the address is allocated to.
+    fn country(&self) -> Option<&'static str>;
+}
+
+impl AddressCountry for Icao24 {
+    fn country(&self) -> Option<&'static str> {
+        icao_u32_to_country(self.0)
     }
+}
 
-    // Convert u32 to [u8; 3] big-endian
-    let icao = [
-        ((icao_u32 >> 16) & 0xFF) as u8,
-        ((icao_u32 >> 8) & 0xFF) as u8,
-        (icao_u32 & 0xFF) as u8,
-    ];
+impl AddressCountry for u32 {
+    fn country(&self) -> Option<&'static str> {
+        icao_u32_to_country(*self)
+    }
+}
 
-    icao_to_country(icao)
+#[cfg(feature = "adsb_deku")]
+impl From<adsb_deku::ICAO> for Icao24 {
+    fn from(icao: adsb_deku::ICAO) -> Self {
+        Icao24::from_bytes(icao.0)
+    }
 }
 
-/// Convert a 24-bit ICAO address to its allocated country's ISO2 code.
-///
-/// # Arguments
-/// * `icao` - 24-bit ICAO address as a 3-byte array (big-endian)
-///
-/// # Returns
-/// * `Some(&str)` - ISO 3166-1 alpha-2 country code if allocation found
-/// * `None` - If no allocation matches the address
+#[cfg(feature = "adsb_deku")]
+impl From<Icao24> for adsb_deku::ICAO {
+    fn from(icao: Icao24) -> Self {
+        adsb_deku::ICAO(icao.to_bytes())
+    }
+}
+
+#[cfg(feature = "adsb_deku")]
+impl AddressCountry for adsb_deku::ICAO {
+    fn country(&self) -> Option<&'static str> {
+        icao_to_country(self.0)
+    }
+}
+
+/// Fails only for hand-built values wider than 24 bits; decoded addresses
+/// always fit.
+#[cfg(feature = "rs1090")]
+impl TryFrom<rs1090::decode::ICAO> for Icao24 {
+    type Error = AddressRangeError;
+
+    fn try_from(icao: rs1090::decode::ICAO) -> Result<Self, Self::Error> {
+        Icao24::try_from(icao.0)
+    }
+}
+
+#[cfg(feature = "rs1090")]
+impl From<Icao24> for rs1090::decode::ICAO {
+    fn from(icao: Icao24) -> Self {
+        rs1090::decode::ICAO(icao.0)
+    }
+}
+
+#[cfg(feature = "rs1090")]
+impl AddressCountry for rs1090::decode::ICAO {
+    fn country(&self) -> Option<&'static str> {
+        icao_u32_to_country(self.0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Icao24 {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Icao24(u.int_in_range(0..=0xFFFFFF)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <u32 as arbitrary::Arbitrary>::size_hint(depth)
+    }
+}
+
+/// Stored as an `INTEGER` column. Decodes from 32- or 64-bit integer columns,
+/// or from `TEXT` columns holding the address in any `parse_hex` notation.
+#[cfg(feature = "sqlx")]
+impl<DB: sqlx::Database> sqlx::Type<DB> for Icao24
+where
+    i32: sqlx::Type<DB>,
+    i64: sqlx::Type<DB>,
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i32 as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i32 as sqlx::Type<DB>>::compatible(ty)
+            || <i64 as sqlx::Type<DB>>::compatible(ty)
+            || <String as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for Icao24
+where
+    i32: sqlx::Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        // 24 bits always fit
+        <i32 as sqlx::Encode<'q, DB>>::encode_by_ref(&(self.0 as i32), buf)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for Icao24
+where
+    i32: sqlx::Type<DB> + sqlx::Decode<'r, DB>,
+    i64: sqlx::Type<DB> + sqlx::Decode<'r, DB>,
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        use sqlx::ValueRef;
+
+        let ty = value.type_info().into_owned();
+        if <i32 as sqlx::Type<DB>>::compatible(&ty) {
+            Icao24::from_column_integer(<i32 as sqlx::Decode<DB>>::decode(value)?.into())
+        } else if <i64 as sqlx::Type<DB>>::compatible(&ty) {
+            Icao24::from_column_integer(<i64 as sqlx::Decode<DB>>::decode(value)?)
+        } else {
+            Ok(Icao24::parse_hex(&<String as sqlx::Decode<DB>>::decode(value)?)?)
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl Icao24 {
+    fn from_column_integer(value: i64) -> Result<Self, sqlx::error::BoxDynError> {
+        u32::try_from(value)
+            .ok()
+            .and_then(Icao24::new)
+            .ok_or_else(|| format!("{} is not a 24-bit ICAO address", value).into())
+    }
+}
+
+/// Unassigned stretches at least this many addresses long are reported as gaps
+/// by `validate_allocations`.
+pub const GAP_REPORT_THRESHOLD: u32 = 0x10000;
+
+/// Two allocation blocks where one lies inside the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationOverlap {
+    /// The enclosing (shorter-prefix) block
+    pub outer: CountryAllocation,
+    /// The nested (longer-prefix) block
+    pub inner: CountryAllocation,
+}
+
+/// Result of auditing the allocation table with `validate_allocations`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg(feature = "alloc")]
+pub struct AllocationReport {
+    /// Nested blocks. These are expected for sub-allocations (e.g. Hong Kong
+    /// inside China) but worth reviewing when the countries differ unexpectedly.
+    pub overlaps: Vec<AllocationOverlap>,
+    /// Entries that can never match because an earlier, shorter prefix in the
+    /// table already covers them.
+    pub shadowed: Vec<CountryAllocation>,
+    /// Unassigned ranges of at least `GAP_REPORT_THRESHOLD` addresses.
+    pub gaps: Vec<RangeInclusive<u32>>,
+}
+
+#[cfg(feature = "alloc")]
+impl AllocationReport {
+    /// Whether the table is free of errors. Overlaps and gaps are informational.
+    pub fn is_valid(&self) -> bool {
+        self.shadowed.is_empty()
+    }
+}
+
+/// Audit the allocation table for overlapping, shadowed and missing ranges.
 ///
 /// # Examples
 /// ```
-/// use flydent::icao::icao_to_country;
-///
-/// // United States allocation (starts with 1010)
-/// let us_icao = [0xAB, 0x8E, 0x4F];  // Binary: 1010 1011 1000 1110 0100 1111
-/// assert_eq!(icao_to_country(us_icao), Some("US"));
+/// use flydent::icao::validate_allocations;
 ///
-/// // Yugoslavia allocation (starts with 010011000)
-/// let yu_icao = [0x4C, 0x00, 0x00];  // Binary: 0100 1100 0000 0000 0000 0000
-/// assert_eq!(icao_to_country(yu_icao), Some("RS"));
+/// let report = validate_allocations();
+/// assert!(report.is_valid());
+/// // Hong Kong and Macao are nested inside the China block
+/// assert!(report.overlaps.iter().any(|o| o.outer.iso2 == Some("CN") && o.inner.iso2 == Some("HK")));
 /// ```
-pub fn icao_to_country(icao: [u8; 3]) -> Option<&'static str> {
-    // Convert bytes to 24-bit binary string
-    let binary = format!("{:08b}{:08b}{:08b}", icao[0], icao[1], icao[2]);
+#[cfg(feature = "alloc")]
+pub fn validate_allocations() -> AllocationReport {
+    audit_allocations(ICAO_ALLOCATIONS, GAP_REPORT_THRESHOLD)
+}
 
-    // Check each allocation prefix (already sorted longest-first)
-    for (prefix, country_code) in ICAO_ALLOCATIONS {
-        if binary.starts_with(prefix) {
-            return Some(country_code);
+#[cfg(feature = "alloc")]
+fn audit_allocations(table: &[Allocation], min_gap: u32) -> AllocationReport {
+    let mut report = AllocationReport::default();
+
+    for (i, earlier) in table.iter().enumerate() {
+        for later in &table[i + 1..] {
+            let (outer, inner) = if earlier.bits <= later.bits {
+                (earlier, later)
+            } else {
+                (later, earlier)
+            };
+            if !outer.contains(inner.start) {
+                continue;
+            }
+
+            report.overlaps.push(AllocationOverlap {
+                outer: outer.country_allocation(),
+                inner: inner.country_allocation(),
+            });
+            // First match wins, so a later, more specific entry is unreachable
+            if core::ptr::eq(inner, later) {
+                report.shadowed.push(later.country_allocation());
+            }
         }
     }
 
-    None
+    let mut covered: Vec<(u32, u32)> = table
+        .iter()
+        .map(|allocation| (allocation.start, allocation.start + allocation.size()))
+        .collect();
+    covered.sort_unstable();
+
+    let mut next_free = 0u32;
+    for (start, end) in covered.into_iter().chain(core::iter::once((0x1000000, 0x1000000))) {
+        if start > next_free && start - next_free >= min_gap {
+            report.gaps.push(next_free..=start - 1);
+        }
+        next_free = next_free.max(end);
+    }
+
+    report
 }
 
 #[cfg(test)]
@@ -298,7 +1322,7 @@ mod tests {
 
     #[test]
     fn test_yugoslavia_allocation() {
-        // Yugoslavia starts with 010011000 (0x4C)
+        // Former Yugoslavia block, now Serbia: 010011000 (0x4C)
         let yu_icao = [0x4C, 0x00, 0x00];  // Binary: 0100 1100 0000 0000 0000 0000
         assert_eq!(icao_to_country(yu_icao), Some("RS"));
     }
@@ -324,11 +1348,79 @@ mod tests {
         assert_eq!(icao_to_country(ru_icao), Some("RU"));
     }
 
+    #[test]
+    fn test_china_allocation() {
+        // China: 011110 (0x780000-0x7BFFFF)
+        assert_eq!(icao_u32_to_country(0x780000), Some("CN"));
+        assert_eq!(icao_u32_to_country(0x7BFFFF), Some("CN"));
+        assert_eq!(icao_u32_to_country(0x7C0000), Some("AU"));
+    }
+
+    #[test]
+    fn test_hong_kong_and_macao_allocations() {
+        // Sub-blocks carved out of the China allocation take precedence over it
+        assert_eq!(icao_u32_to_country(0x789000), Some("HK"));
+        assert_eq!(icao_u32_to_country(0x789FFF), Some("HK"));
+        assert_eq!(icao_u32_to_country(0x78A000), Some("MO"));
+        assert_eq!(icao_u32_to_country(0x78AFFF), Some("MO"));
+        assert_eq!(icao_u32_to_country(0x788FFF), Some("CN"));
+        assert_eq!(icao_u32_to_country(0x78B000), Some("CN"));
+    }
+
+    #[test]
+    fn test_taiwan_allocation() {
+        // Taiwan: 10001001100100 (0x899000-0x8993FF)
+        assert_eq!(icao_u32_to_country(0x899000), Some("TW"));
+        assert_eq!(icao_u32_to_country(0x8993FF), Some("TW"));
+        assert_eq!(icao_u32_to_country(0x899400), None);
+    }
+
+    #[test]
+    fn test_post_2000_states() {
+        // Montenegro received its own block after leaving Serbia and Montenegro
+        assert_eq!(icao_u32_to_country(0x516000), Some("ME"));
+        assert_eq!(icao_u32_to_country(0x5163FF), Some("ME"));
+        // The legacy Yugoslavia block continues to resolve to Serbia
+        assert_eq!(icao_u32_to_country(0x4C7FFF), Some("RS"));
+    }
+
+    #[test]
+    fn test_syria_allocation() {
+        // Syrian Arab Republic: 011101111 (0x778000)
+        assert_eq!(icao_u32_to_country(0x778000), Some("SY"));
+        assert_eq!(icao_u32_to_country(0x77FFFF), Some("SY"));
+    }
+
     #[test]
     fn test_icao_special_allocation() {
-        // ICAO special allocations return "ZZ"
+        // ICAO special blocks belong to no country
         let icao_special = [0xF0, 0x00, 0x00];  // Starts with 111100000
-        assert_eq!(icao_to_country(icao_special), Some("ZZ"));
+        assert_eq!(icao_to_country(icao_special), None);
+        assert_eq!(special_use(0xF00000), Some(SpecialUse::Temporary));
+        assert_eq!(special_use(0xF07FFF), Some(SpecialUse::Temporary));
+        assert_eq!(special_use(0xF09000), Some(SpecialUse::FlightSafety));
+        assert_eq!(special_use(0xF093FF), Some(SpecialUse::FlightSafety));
+        assert_eq!(special_use(0xF08000), None);
+        assert_eq!(special_use(0x01F00000), None);
+    }
+
+    #[test]
+    fn test_allocation_predicates() {
+        // Exactly one predicate holds for every valid address
+        let addresses = [
+            0x000000, 0x0CA3FF, 0x0CA400, 0x4C0000, 0xAB8E4F, 0xF00000, 0xF08000, 0xF093FF, 0xFFFFFF,
+        ];
+        for address in addresses {
+            let held = [is_allocated(address), is_reserved(address), is_unassigned(address)];
+            assert_eq!(held.iter().filter(|&&h| h).count(), 1, "{:06X}", address);
+            assert_eq!(is_allocated(address), icao_u32_to_country(address).is_some());
+            assert_eq!(is_reserved(address), special_use(address).is_some());
+        }
+
+        assert!(is_unassigned(0xF08000));
+        for invalid in [0x01000000, NON_ICAO_FLAG | 0xAB8E4F] {
+            assert!(!is_allocated(invalid) && !is_reserved(invalid) && !is_unassigned(invalid));
+        }
     }
 
     #[test]
@@ -354,7 +1446,27 @@ mod tests {
         assert_ne!(icao_to_country(ag_icao), Some("RU"));
     }
 
-    // Tests for u32 convenience function
+    #[test]
+    fn test_const_evaluation() {
+        // Lookups can be evaluated at compile time
+        const US: Option<&str> = icao_u32_to_country(0xAB8E4F);
+        const CA: Option<&str> = icao_to_country([0xC0, 0x00, 0x01]);
+        const NONE: Option<&str> = icao_u32_to_country(0xFFFFFF);
+        assert_eq!(US, Some("US"));
+        assert_eq!(CA, Some("CA"));
+        assert_eq!(NONE, None);
+    }
+
+    #[test]
+    fn test_alloc_prefix_parsing() {
+        let allocation = alloc("00001100101000", "AG", "Antigua and Barbuda");
+        assert_eq!(allocation.start, 0x0CA000);
+        assert_eq!(allocation.bits, 14);
+        assert_eq!(allocation.size(), 1024);
+        assert_eq!(allocation.mask(), 0xFFFC00);
+        assert!(allocation.contains(0x0CA3FF));
+        assert!(!allocation.contains(0x0CA400));
+    }
 
     #[test]
     fn test_u32_usa_allocation() {
@@ -365,7 +1477,7 @@ mod tests {
 
     #[test]
     fn test_u32_yugoslavia_allocation() {
-        // Yugoslavia: 0x4C0000
+        // Serbia (former Yugoslavia block): 0x4C0000
         assert_eq!(icao_u32_to_country(0x4C0000), Some("RS"));
     }
 
@@ -404,7 +1516,7 @@ mod tests {
         // Verify u32 version gives same results as array version
         let test_cases = vec![
             0xAB8E4F,  // US
-            0x4C0000,  // Yugoslavia
+            0x4C0000,  // Serbia (former Yugoslavia block)
             0xC00001,  // Canada
             0x400000,  // UK
             0x100000,  // Russia
@@ -419,4 +1531,375 @@ mod tests {
             assert_eq!(icao_u32_to_country(icao_u32), icao_to_country(icao_array));
         }
     }
+
+    // Tests for rich allocation lookup
+
+    #[test]
+    fn test_lookup_us_block() {
+        let allocation = lookup(0xAB8E4F).unwrap();
+        assert_eq!(allocation.iso2, Some("US"));
+        assert_eq!(allocation.name, "United States");
+        assert_eq!(allocation.start, 0xA00000);
+        assert_eq!(allocation.end, 0xAFFFFF);
+        assert_eq!(allocation.size, 0x100000);
+        assert_eq!(allocation.special_use, None);
+        assert!(!allocation.is_special());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_lookup_sub_block() {
+        // Hong Kong reports its own sub-block, not the surrounding China block
+        let allocation = lookup(0x789123).unwrap();
+        assert_eq!(allocation.iso2, Some("HK"));
+        assert_eq!(allocation.start_hex(), "789000");
+        assert_eq!(allocation.end_hex(), "789FFF");
+        assert_eq!(allocation.size, 0x1000);
+
+        let allocation = lookup(0x0CA123).unwrap();
+        assert_eq!(allocation.iso2, Some("AG"));
+        assert_eq!(allocation.size, 1024);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_lookup_special_and_invalid() {
+        let allocation = lookup(0xF00000).unwrap();
+        assert_eq!(allocation.iso2, None);
+        assert_eq!(allocation.special_use, Some(SpecialUse::Temporary));
+        assert!(allocation.is_special());
+        assert_eq!(allocation.end_hex(), "F07FFF");
+
+        assert_eq!(lookup(0xFFFFFF), None);
+        assert_eq!(lookup(0x01000000), None);
+    }
+
+    // Tests for per-country range helpers
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_allocation_range_single_block() {
+        assert_eq!(allocation_range("US"), vec![0xA00000..=0xAFFFFF]);
+        assert_eq!(allocation_range("us"), vec![0xA00000..=0xAFFFFF]);
+        assert_eq!(allocation_range("AG"), vec![0x0CA000..=0x0CA3FF]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_allocation_range_excludes_sub_blocks() {
+        assert_eq!(
+            allocation_range("CN"),
+            vec![0x780000..=0x788FFF, 0x78B000..=0x7BFFFF]
+        );
+        assert_eq!(allocation_size("CN"), 0x40000 - 0x2000);
+        assert_eq!(allocation_size("HK"), 0x1000);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_allocation_size_unknown_country() {
+        assert!(allocation_range("XX").is_empty());
+        assert_eq!(allocation_size("XX"), 0);
+    }
+
+    // Tests for address usage classification
+
+    #[test]
+    fn test_military_blocks_lie_within_their_country() {
+        for &(start, end, iso2) in MILITARY_BLOCKS {
+            assert_eq!(icao_u32_to_country(start), Some(iso2), "start of {:06X}", start);
+            assert_eq!(icao_u32_to_country(end), Some(iso2), "end of {:06X}", end);
+        }
+    }
+
+    #[test]
+    fn test_address_class() {
+        assert_eq!(address_class(0xAB8E4F), Some(AddressClass::Civil));
+        assert_eq!(address_class(0xADF7C7), Some(AddressClass::Civil));
+        assert_eq!(address_class(0xADF7C8), Some(AddressClass::Military));
+        assert_eq!(address_class(0x43C123), Some(AddressClass::Military));
+        assert_eq!(address_class(0x400000), Some(AddressClass::Civil));
+        assert_eq!(address_class(0xF00000), Some(AddressClass::SpecialUse));
+        assert_eq!(address_class(0x000000), Some(AddressClass::Reserved));
+        assert_eq!(address_class(0x2000000), None);
+    }
+
+    #[test]
+    fn test_non_icao_addresses() {
+        assert!(is_non_icao(NON_ICAO_FLAG));
+        assert!(is_non_icao(NON_ICAO_FLAG | 0xFFFFFF));
+        assert!(!is_non_icao(0xFFFFFF));
+        assert!(!is_non_icao(0x3000000));
+        assert_eq!(address_class(NON_ICAO_FLAG | 0xAB8E4F), Some(AddressClass::NonIcao));
+        // The flagged value is never attributed to a country
+        assert_eq!(icao_u32_to_country(NON_ICAO_FLAG | 0xAB8E4F), None);
+
+        assert!(is_non_icao_hex("~ab8e4f"));
+        assert!(is_non_icao_hex(" ~AB8E4F"));
+        assert!(!is_non_icao_hex("ab8e4f"));
+        // Unmarked, a self-assigned address is indistinguishable from an allocated one
+        assert_eq!(address_class(0xAB8E4F), Some(AddressClass::Civil));
+    }
+
+    // Tests for the Icao24 type
+
+    #[test]
+    fn test_icao24_parse_hex_notations() {
+        for input in ["AB8E4F", "ab8e4f", "0xAB8E4F", "0Xab8e4f", "AB 8E 4F", "ab:8e:4f", "AB-8E-4F", " AB8E4F\n"] {
+            assert_eq!(Icao24::parse_hex(input), Ok(Icao24(0xAB8E4F)), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_icao24_parse_hex_errors() {
+        assert_eq!(Icao24::parse_hex(""), Err(ParseHexError::Empty));
+        assert_eq!(Icao24::parse_hex("0x"), Err(ParseHexError::Empty));
+        assert_eq!(Icao24::parse_hex("AB8E4G"), Err(ParseHexError::InvalidCharacter('G')));
+        assert_eq!(Icao24::parse_hex("AB8E4"), Err(ParseHexError::InvalidLength(5)));
+        assert_eq!(Icao24::parse_hex("AB8E4F0"), Err(ParseHexError::InvalidLength(7)));
+        assert_eq!(Icao24::parse_hex("AB 8E4F"), Err(ParseHexError::InvalidGrouping));
+        assert_eq!(Icao24::parse_hex("A:B8:E4F"), Err(ParseHexError::InvalidGrouping));
+        assert_eq!(Icao24::parse_hex("AB--8E4F"), Err(ParseHexError::InvalidGrouping));
+        assert_eq!(Icao24::parse_hex("-AB-8E-4F"), Err(ParseHexError::InvalidGrouping));
+        // Separators must not be mixed
+        assert_eq!(Icao24::parse_hex("ab:8e-4f"), Err(ParseHexError::InvalidGrouping));
+        assert_eq!(Icao24::parse_hex("AB 8E:4F"), Err(ParseHexError::InvalidGrouping));
+        assert_eq!(Icao24::parse_hex("AB_8E_4F"), Err(ParseHexError::InvalidCharacter('_')));
+    }
+
+    #[test]
+    fn test_icao24_conversions() {
+        let icao: Icao24 = "AB8E4F".parse().unwrap();
+        assert_eq!(icao.to_string(), "AB8E4F");
+        assert_eq!(icao.to_bytes(), [0xAB, 0x8E, 0x4F]);
+        assert_eq!(Icao24::from([0xAB, 0x8E, 0x4F]), icao);
+        assert_eq!(u32::from(icao), 0xAB8E4F);
+        assert_eq!(icao.country(), Some("US"));
+        assert_eq!(Icao24::new(0x1000000), None);
+        assert_eq!(Icao24::new(0x00000F).unwrap().to_string(), "00000F");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_ranges_for() {
+        assert_eq!(ranges_for("CH"), &[0x4B0000..=0x4B7FFF]);
+        assert_eq!(ranges_for("ch"), ranges_for("CH"));
+        assert_eq!(ranges_for("CN").len(), 2);
+        assert!(ranges_for("XX").is_empty());
+        // Every allocated address of a country falls in one of its ranges
+        for icao_u32 in [0x780000, 0x7ABCDE, 0x7BFFFF] {
+            assert!(ranges_for("CN").iter().any(|range| range.contains(&icao_u32)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_prefix_country() {
+        assert_eq!(prefix_country("700123"), Some("AF"));
+        assert_eq!(prefix_country("700"), Some("AF"));
+        assert_eq!(prefix_country("a"), Some("US"));
+        // Austria's 9-bit prefix spans 440..=447
+        assert_eq!(prefix_country("440"), Some("AT"));
+        assert_eq!(prefix_country("447"), Some("AT"));
+        assert_eq!(prefix_country("44"), None);
+        // China without its Hong Kong and Macao sub-blocks
+        assert_eq!(prefix_country("788"), Some("CN"));
+        assert_eq!(prefix_country("78"), None);
+        assert_eq!(prefix_country("789"), Some("HK"));
+        // Special-use and unallocated space
+        assert_eq!(prefix_country("F00"), None);
+        assert_eq!(prefix_country("FFF"), None);
+        assert_eq!(prefix_country(""), None);
+        assert_eq!(prefix_country("7001234"), None);
+        assert_eq!(prefix_country("70G"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_prefixes_for() {
+        assert_eq!(prefixes_for("US"), vec!["1010"]);
+        assert_eq!(prefixes_for("CN"), vec!["011110"]);
+        assert!(prefixes_for("ZZ").is_empty());
+        assert!(prefixes_for("XX").is_empty());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_address_within_allocation() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        for iso2 in ["DE", "CN", "AG", "US"] {
+            for _ in 0..1000 {
+                let address = random_address(iso2, &mut rng).unwrap();
+                assert_eq!(icao_u32_to_country(address), Some(iso2), "{:06X}", address);
+            }
+        }
+        assert_eq!(random_address("XX", &mut rng), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_countries_in_range() {
+        // A range inside one block
+        let shares = countries_in_range(0x3C0000..=0x3C00FF);
+        assert_eq!(shares.len(), 1);
+        assert_eq!((shares[0].start, shares[0].end, shares[0].size), (0x3C0000, 0x3C00FF, 0x100));
+        assert_eq!(shares[0].allocation.iso2, Some("DE"));
+
+        // China around its carve-outs: one share, counted without them
+        let shares = countries_in_range(0x780000..=0x7BFFFF);
+        let china = shares.iter().find(|share| share.allocation.iso2 == Some("CN")).unwrap();
+        let carved: u32 = shares.iter().filter(|share| share.allocation.iso2 != Some("CN")).map(|share| share.size).sum();
+        assert_eq!(china.size + carved, 0x40000);
+        assert_eq!(china.end, 0x7BFFFF);
+        assert!(china.end - china.start + 1 > china.size);
+
+        // The whole space: every allocated address exactly once
+        let shares = countries_in_range(0..=u32::MAX);
+        let allocated = (0..=0xFFFFFFu32).step_by(0x100).filter(|&address| lookup(address).is_some()).count() as u32;
+        let total: u32 = shares.iter().map(|share| share.size).sum();
+        assert_eq!(total / 0x100, allocated);
+        for share in &shares {
+            assert_eq!(lookup(share.start).unwrap(), share.allocation);
+            assert_eq!(lookup(share.end).unwrap(), share.allocation);
+        }
+
+        assert!(countries_in_range(0x1000000..=0x2000000).is_empty());
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = countries_in_range(0xA00001..=0xA00000);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_iter_addresses() {
+        let addresses: Vec<u32> = iter_addresses("AG").collect();
+        assert_eq!(addresses.len(), 1024);
+        assert_eq!(addresses.first(), Some(&0x0CA000));
+        assert_eq!(addresses.last(), Some(&0x0CA3FF));
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // China skips the Hong Kong and Macao sub-blocks
+        let mut china = iter_addresses("CN").skip_while(|&a| a < 0x788FFF);
+        assert_eq!(china.next(), Some(0x788FFF));
+        assert_eq!(china.next(), Some(0x78B000));
+    }
+
+    // Tests for the allocation audit
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_validate_builtin_allocations() {
+        let report = validate_allocations();
+        assert!(report.is_valid(), "shadowed entries: {:?}", report.shadowed);
+        assert!(report
+            .overlaps
+            .iter()
+            .any(|o| o.outer.iso2 == Some("CN") && o.inner.iso2 == Some("MO")));
+        for gap in &report.gaps {
+            assert!(gap.end() - gap.start() + 1 >= GAP_REPORT_THRESHOLD);
+            assert_eq!(icao_u32_to_country(*gap.start()), None);
+            assert_eq!(icao_u32_to_country(*gap.end()), None);
+        }
+        // 0xB00000-0xBFFFFF is not allocated to anyone
+        assert!(report.gaps.iter().any(|gap| gap.contains(&0xB00000)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_audit_detects_shadowed_entry() {
+        let table = [
+            alloc("0001", "RU", "Russian Federation"),
+            alloc("00010000", "XX", "Shadowed"),
+        ];
+        let report = audit_allocations(&table, 0x100000);
+        assert!(!report.is_valid());
+        assert_eq!(report.shadowed.len(), 1);
+        assert_eq!(report.shadowed[0].iso2, Some("XX"));
+        assert_eq!(report.overlaps.len(), 1);
+        assert_eq!(report.gaps, vec![0x000000..=0x0FFFFF, 0x200000..=0xFFFFFF]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_audit_accepts_longest_first_order() {
+        let table = [
+            alloc("00010000", "XX", "Nested"),
+            alloc("0001", "RU", "Russian Federation"),
+        ];
+        let report = audit_allocations(&table, 0x100000);
+        assert!(report.is_valid());
+        assert_eq!(report.overlaps.len(), 1);
+        assert_eq!(report.overlaps[0].outer.iso2, Some("RU"));
+    }
+
+    // Tests for bulk classification
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_classify_many_matches_single_lookup() {
+        let addresses: Vec<u32> = (0..=0xFFFFFFu32).step_by(0x0FFF).collect();
+        let bulk = classify_many(&addresses);
+        for (icao_u32, country) in addresses.iter().zip(bulk) {
+            assert_eq!(country, icao_u32_to_country(*icao_u32), "mismatch at {:06X}", icao_u32);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_classify_many_preserves_order_and_invalid() {
+        let addresses = [0xAB8E4F, 0xFFFFFF, 0x01000000, 0xAB8E50, 0x0CA000];
+        assert_eq!(
+            classify_many(&addresses),
+            vec![Some("US"), None, None, Some("US"), Some("AG")]
+        );
+        assert!(classify_many(&[]).is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "core-only", feature = "alloc"))]
+    fn test_allocation_table_matches_allocations() {
+        assert_eq!(allocation_table().len(), allocations().len());
+        assert!(allocation_table().eq(allocations()));
+    }
+
+    // Tests for conversions from and to other crates' types
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_sqlx_column_integers() {
+        assert_eq!(Icao24::from_column_integer(0xAB8E4F).unwrap().value(), 0xAB8E4F);
+        assert_eq!(Icao24::from_column_integer(0).unwrap().value(), 0);
+        assert!(Icao24::from_column_integer(0x1000000).is_err());
+        assert!(Icao24::from_column_integer(-1).is_err());
+    }
+
+    #[test]
+    fn test_address_range() {
+        assert_eq!(Icao24::try_from(0xAB8E4F).unwrap().value(), 0xAB8E4F);
+        assert_eq!(Icao24::try_from(0x1000000), Err(AddressRangeError(0x1000000)));
+        assert_eq!(AddressRangeError(0x1000000).to_string(), "0x1000000 is not a 24-bit ICAO address");
+        assert_eq!(AddressCountry::country(&0xF00000u32), None);
+    }
+
+    #[cfg(feature = "adsb_deku")]
+    #[test]
+    fn test_adsb_deku_interop() {
+        let decoded = adsb_deku::ICAO([0xAB, 0x8E, 0x4F]);
+        assert_eq!(decoded.country(), Some("US"));
+        let icao = Icao24::from(decoded);
+        assert_eq!(icao.value(), 0xAB8E4F);
+        assert_eq!(adsb_deku::ICAO::from(icao), decoded);
+    }
+
+    #[cfg(feature = "rs1090")]
+    #[test]
+    fn test_rs1090_interop() {
+        let decoded = rs1090::decode::ICAO(0x3C65A1);
+        assert_eq!(decoded.country(), Some("DE"));
+        let icao = Icao24::try_from(decoded).unwrap();
+        assert_eq!(rs1090::decode::ICAO::from(icao), decoded);
+        assert!(Icao24::try_from(rs1090::decode::ICAO(0x1000000)).is_err());
+    }
 }
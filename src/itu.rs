@@ -0,0 +1,297 @@
+//! ITU Call Sign Series
+//!
+//! Attributes radio callsigns of any service (amateur, maritime, broadcast,
+//! aeronautical) to the country or organization holding their ITU call sign
+//! series, e.g. "PA3XYZ" to the Netherlands. The series come from the same ITU
+//! data as the registration `Parser`, which refines them with the
+//! aviation-specific registration formats.
+//!
+//! `check_format` applies the rules every callsign and registration follows,
+//! whatever its country, as a fast pre-filter before either lookup.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+use crate::{EntityResult, DATA};
+use once_cell::sync::Lazy;
+
+/// Holder of an ITU call sign series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesHolder {
+    Country {
+        /// ISO 3166-1 alpha-2 country code
+        iso2: &'static str,
+        /// Country name as listed in the ITU data
+        nation: &'static str,
+    },
+    /// An international organization, e.g. the United Nations (`4U`)
+    Organization { name: &'static str },
+}
+
+/// A call sign series prefix and its holder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallsignSeries {
+    /// The series prefix, e.g. `"PA"` or `"3DN"`
+    pub prefix: &'static str,
+    pub holder: SeriesHolder,
+}
+
+/// Series prefix -> holder. Rows describing the general registry of a country
+/// take precedence over special-purpose rows listing the same prefix, and
+/// countries holding an ICAO 24-bit allocation over territories sharing their
+/// series (the Isle of Man's "M", Liechtenstein's "HB").
+static SERIES: Lazy<BTreeMap<&'static str, SeriesHolder>> = Lazy::new(|| {
+    let mut rows: Vec<_> = DATA.iter().collect();
+    rows.sort_by_key(|data| (!is_general(&data.entity_result), data.icao24bit_prefixes.is_empty()));
+    let mut series = BTreeMap::new();
+    for data in rows {
+        let holder = match &data.entity_result {
+            EntityResult::Country { iso2, nation, .. } => SeriesHolder::Country { iso2, nation },
+            EntityResult::Organization { name, .. } => SeriesHolder::Organization { name },
+        };
+        // Aviation-only marks such as Bermuda's "VP-B" are not ITU series
+        for prefix in data.callsigns.iter().filter(|prefix| !prefix.contains('-')) {
+            series.entry(prefix.as_str()).or_insert(holder);
+        }
+    }
+    series
+});
+
+static MAX_PREFIX_LEN: Lazy<usize> = Lazy::new(|| SERIES.keys().map(|prefix| prefix.len()).max().unwrap_or(0));
+
+fn is_general(entity: &EntityResult) -> bool {
+    match entity {
+        EntityResult::Country { description, .. } | EntityResult::Organization { description, .. } => {
+            description == "general"
+        }
+    }
+}
+
+/// All call sign series, sorted by prefix.
+pub fn series() -> Vec<CallsignSeries> {
+    let mut series: Vec<CallsignSeries> = SERIES
+        .iter()
+        .map(|(&prefix, &holder)| CallsignSeries { prefix, holder })
+        .collect();
+    series.sort_by_key(|entry| entry.prefix);
+    series
+}
+
+/// Find the series a callsign belongs to, by the longest matching prefix.
+///
+/// # Examples
+/// ```
+/// use flydent::itu::{lookup_callsign, SeriesHolder};
+///
+/// let series = lookup_callsign("3DN2AB").unwrap();
+/// assert_eq!(series.prefix, "3DN");
+/// assert!(matches!(series.holder, SeriesHolder::Country { iso2: "FJ", .. }));
+///
+/// let un = lookup_callsign("4U1UN").unwrap();
+/// assert!(matches!(un.holder, SeriesHolder::Organization { .. }));
+/// ```
+pub fn lookup_callsign(callsign: &str) -> Option<CallsignSeries> {
+    let callsign = callsign.trim().to_ascii_uppercase();
+    (1..=callsign.len().min(*MAX_PREFIX_LEN)).rev().find_map(|len| {
+        let prefix = callsign.get(..len)?;
+        SERIES
+            .get_key_value(prefix)
+            .map(|(&prefix, &holder)| CallsignSeries { prefix, holder })
+    })
+}
+
+/// ISO2 code of the country holding a callsign's series. Callsigns of
+/// international organizations yield `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::itu::country_of_callsign;
+///
+/// assert_eq!(country_of_callsign("PA3XYZ"), Some("NL"));
+/// assert_eq!(country_of_callsign("W1AW"), Some("US"));
+/// assert_eq!(country_of_callsign("DL1ABC"), Some("DE"));
+/// ```
+pub fn country_of_callsign(callsign: &str) -> Option<&'static str> {
+    match lookup_callsign(callsign)?.holder {
+        SeriesHolder::Country { iso2, .. } => Some(iso2),
+        SeriesHolder::Organization { .. } => None,
+    }
+}
+
+/// Longest callsign or registration accepted by `check_format`, dashes
+/// included, e.g. `RDPL-12345`.
+pub const MAX_CALLSIGN_LEN: usize = 10;
+
+/// Suffixes that registrations must not use, as they read as distress and
+/// urgency signals.
+const RESERVED_SUFFIXES: &[&str] = &["SOS", "XXX", "PAN", "TTT"];
+
+/// Why a string cannot be a callsign or registration of any country.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum FormatError {
+    Empty,
+    /// Longer than `MAX_CALLSIGN_LEN`
+    TooLong,
+    /// Neither an ASCII letter, a digit nor a dash
+    InvalidCharacter(char),
+    /// A dash at either end, two in a row, or more than two
+    MisplacedDash,
+    /// No ITU series starts with `0` or `1`, or with two digits
+    InvalidSeries,
+    /// Only digits
+    NoLetter,
+    /// A suffix after the dash that is reserved (`SOS`, `PAN`, ...) or starts
+    /// with `Q`, which could be taken for a Q code
+    ReservedSuffix,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Empty => write!(f, "callsign is empty"),
+            FormatError::TooLong => write!(f, "callsign is longer than {} characters", MAX_CALLSIGN_LEN),
+            FormatError::InvalidCharacter(c) => write!(f, "invalid character {:?} in callsign", c),
+            FormatError::MisplacedDash => write!(f, "misplaced dash in callsign"),
+            FormatError::InvalidSeries => write!(f, "callsign does not start with an ITU series"),
+            FormatError::NoLetter => write!(f, "callsign has no letter"),
+            FormatError::ReservedSuffix => write!(f, "callsign suffix is reserved"),
+        }
+    }
+}
+
+impl core::error::Error for FormatError {}
+
+/// Check the rules every callsign and registration follows, independent of
+/// country: at most `MAX_CALLSIGN_LEN` letters, digits and dashes, with a
+/// letter, starting with a possible ITU series (a letter, or a digit from 2 to
+/// 9 followed by a letter or dash), and no reserved suffix after a dash.
+///
+/// Much cheaper than `lookup_callsign` or `Parser::parse`, so suited to
+/// discard garbage before them; passing says nothing about the country.
+///
+/// # Examples
+/// ```
+/// use flydent::itu::{check_format, FormatError};
+///
+/// assert_eq!(check_format("PH-BHA"), Ok(()));
+/// assert_eq!(check_format("4X-EKA"), Ok(()));
+/// assert_eq!(check_format("pa3xyz"), Ok(()));
+/// assert_eq!(check_format("12345"), Err(FormatError::InvalidSeries));
+/// assert_eq!(check_format("PH_BHA"), Err(FormatError::InvalidCharacter('_')));
+/// assert_eq!(check_format("N-SOS"), Err(FormatError::ReservedSuffix));
+/// ```
+pub fn check_format(callsign: &str) -> Result<(), FormatError> {
+    let callsign = callsign.trim();
+    let bytes = callsign.as_bytes();
+    if bytes.is_empty() {
+        return Err(FormatError::Empty);
+    }
+    if let Some(c) = callsign.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-') {
+        return Err(FormatError::InvalidCharacter(c));
+    }
+    if bytes.len() > MAX_CALLSIGN_LEN {
+        return Err(FormatError::TooLong);
+    }
+
+    let dashes = bytes.iter().filter(|&&byte| byte == b'-').count();
+    if bytes[0] == b'-' || bytes[bytes.len() - 1] == b'-' || callsign.contains("--") || dashes > 2 {
+        return Err(FormatError::MisplacedDash);
+    }
+    let series = match bytes {
+        [b'0' | b'1', ..] => false,
+        [first, second, ..] if first.is_ascii_digit() => second.is_ascii_alphabetic() || *second == b'-',
+        _ => true,
+    };
+    if !series {
+        return Err(FormatError::InvalidSeries);
+    }
+    if !bytes.iter().any(u8::is_ascii_alphabetic) {
+        return Err(FormatError::NoLetter);
+    }
+
+    if let Some((_, suffix)) = callsign.rsplit_once('-') {
+        let reserved = RESERVED_SUFFIXES.iter().any(|reserved| suffix.eq_ignore_ascii_case(reserved));
+        if reserved || suffix.starts_with(['Q', 'q']) {
+            return Err(FormatError::ReservedSuffix);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_wins() {
+        let cases = [
+            ("PA3XYZ", Some("NL")),
+            ("ph-abc", Some("NL")),
+            ("G4ABC", Some("GB")),
+            ("M0XYZ", Some("GB")),
+            ("K1ABC", Some("US")),
+            ("AA1A", Some("US")),
+            ("VE3XYZ", Some("CA")),
+            ("3DA0RU", Some("SZ")),
+            ("3DN1XX", Some("FJ")),
+            ("F5ABC", Some("FR")),
+            ("HB9ABC", Some("CH")),
+            ("HB0XYZ", Some("LI")),
+            ("4U1UN", None),
+        ];
+        for (callsign, iso2) in cases {
+            assert_eq!(country_of_callsign(callsign), iso2, "{}", callsign);
+        }
+        assert!(lookup_callsign("").is_none());
+    }
+
+    #[test]
+    fn formats() {
+        for callsign in ["D-AIMA", "N8437D", "2-ABCD", "VP-BAA", "VP-B-AA", "RDPL-12345", " G4ABC ", "4U1UN", "B"] {
+            assert_eq!(check_format(callsign), Ok(()), "{}", callsign);
+        }
+        let cases = [
+            ("", FormatError::Empty),
+            ("  ", FormatError::Empty),
+            ("RDPL-123456", FormatError::TooLong),
+            ("PH BHA", FormatError::InvalidCharacter(' ')),
+            ("PH–BHA", FormatError::InvalidCharacter('–')),
+            ("-PHBHA", FormatError::MisplacedDash),
+            ("PH-", FormatError::MisplacedDash),
+            ("PH--BHA", FormatError::MisplacedDash),
+            ("A-B-C-D", FormatError::MisplacedDash),
+            ("0ABC", FormatError::InvalidSeries),
+            ("1-ABC", FormatError::InvalidSeries),
+            ("44ABC", FormatError::InvalidSeries),
+            ("2-123", FormatError::NoLetter),
+            ("PH-XXX", FormatError::ReservedSuffix),
+            ("ZK-QAB", FormatError::ReservedSuffix),
+            ("ph-pan", FormatError::ReservedSuffix),
+        ];
+        for (callsign, error) in cases {
+            assert_eq!(check_format(callsign), Err(error), "{}", callsign);
+        }
+    }
+
+    #[test]
+    fn dataset_registrations_are_well_formed() {
+        for entry in series() {
+            if let SeriesHolder::Country { iso2, .. } = entry.holder {
+                if let Some(example) = crate::marks::example_registration(iso2) {
+                    assert_eq!(check_format(&example), Ok(()), "{}", example);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn series_table() {
+        let series = series();
+        assert!(series.windows(2).all(|pair| pair[0].prefix < pair[1].prefix));
+        assert!(series.iter().all(|entry| !entry.prefix.contains('-')));
+        for entry in &series {
+            assert_eq!(lookup_callsign(entry.prefix).map(|found| found.prefix), Some(entry.prefix));
+        }
+    }
+}
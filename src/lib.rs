@@ -36,19 +36,112 @@
 //!     println!("ICAO identifier parsed: {:?}", result);
 //! }
 //! ```
+//!
+//! ## `no_std`
+//!
+//! Without the default `std` feature the crate is `#![no_std]`. With the
+//! `alloc` feature it needs only `alloc`, so flight-computer and gateway
+//! firmware can parse callsigns. The parser, `icao` and the converters are
+//! available; `dataset` needs `std`. The lazily built tables use
+//! `critical-section`, so the firmware must provide a `critical_section`
+//! implementation.
+//!
+//! For receivers without an allocator, build with only the `core-only`
+//! feature. The crate then never touches the heap: `icao::icao_to_country`,
+//! `icao::allocation_table`, `registration::registration_to_icao` and
+//! `registration::icao_to_registration_buf`, which writes into a fixed-size
+//! buffer, remain.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-use once_cell::sync::Lazy;
+#[cfg(not(any(feature = "alloc", feature = "core-only")))]
+compile_error!("flydent needs the `alloc` feature, or `core-only` for targets without an allocator");
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// Links the std implementation that the lazily built tables need in no_std
+// builds
+#[cfg(test)]
+use critical_section as _;
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::format;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use once_cell::sync::{Lazy, OnceCell};
+#[cfg(feature = "alloc")]
 use regex::Regex;
-use std::collections::HashMap;
 
+#[cfg(feature = "alloc")]
+pub mod adsb;
+#[cfg(feature = "alloc")]
+pub mod airlines;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "country-meta")]
+pub mod country;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "alloc")]
+pub mod history;
 pub mod icao;
+#[cfg(feature = "alloc")]
+pub mod itu;
+#[cfg(feature = "alloc")]
+pub mod marks;
+#[cfg(feature = "alloc")]
+pub mod military;
+#[cfg(feature = "alloc")]
+pub mod mmsi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "readsb")]
+pub mod readsb;
+#[cfg(feature = "alloc")]
+pub mod region;
 pub mod registration;
-
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "alloc")]
+pub mod sbs;
+#[cfg(feature = "alloc")]
+pub mod scan;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "alloc")]
+pub mod squawk;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "alloc")]
+pub mod types;
+#[cfg(feature = "alloc")]
+pub mod uas;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use icao::Icao24;
+
+#[cfg(feature = "alloc")]
 fn normalize_dashes(input: &str) -> String {
     // Convert common non-ASCII dash characters to ASCII hyphen-minus
     input.replace(['–', '—', '−'], "-") // minus sign (U+2212)
 }
 
+#[cfg(feature = "alloc")]
 fn generate_canonical_form(input: &str, iso2: &str, callsign_prefixes: &[String]) -> String {
     // Countries where canonical form has NO dash between prefix and suffix
     let no_dash_countries = [
@@ -78,7 +171,10 @@ fn generate_canonical_form(input: &str, iso2: &str, callsign_prefixes: &[String]
     }
 }
 
+#[cfg(feature = "alloc")]
 #[derive(Debug, Clone)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug)))]
 pub enum EntityResult {
     Country {
         nation: String,
@@ -94,6 +190,7 @@ pub enum EntityResult {
     },
 }
 
+#[cfg(feature = "alloc")]
 impl EntityResult {
     pub fn canonical_callsign(&self) -> &String {
         match self {
@@ -105,18 +202,123 @@ impl EntityResult {
             } => canonical_callsign,
         }
     }
+
+    /// Category of aircraft implied by the registration block, for national
+    /// schemes that reserve marks by category (e.g. "D-1234" gliders, "D-MABC"
+    /// microlights, "OH-G123" autogyros).
+    ///
+    /// `None` for general registrations and blocks mixing several categories.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::{AircraftCategory, Parser};
+    ///
+    /// let parser = Parser::new();
+    /// let glider = parser.parse_simple("D-1234").unwrap();
+    /// assert_eq!(glider.aircraft_category(), Some(AircraftCategory::Glider));
+    /// let airliner = parser.parse_simple("D-AIMA").unwrap();
+    /// assert_eq!(airliner.aircraft_category(), None);
+    /// ```
+    pub fn aircraft_category(&self) -> Option<AircraftCategory> {
+        let description = match self {
+            EntityResult::Country { description, .. } => description,
+            EntityResult::Organization { description, .. } => description,
+        };
+        AircraftCategory::from_description(description)
+    }
 }
 
+/// Aircraft category encoded in a registration block.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug)))]
+pub enum AircraftCategory {
+    Glider,
+    PoweredGlider,
+    /// Ultralights and microlights, including trikes
+    Ultralight,
+    Balloon,
+    Airship,
+    /// Helicopters
+    Rotorcraft,
+    /// Autogyros
+    Gyroplane,
+    Unmanned,
+    /// Experimental and amateur-built aircraft
+    Experimental,
+}
+
+#[cfg(feature = "alloc")]
+impl AircraftCategory {
+    /// Map an ITU row description to a category. Descriptions naming several
+    /// categories ("gliders and balloons") map to `None`.
+    fn from_description(description: &str) -> Option<Self> {
+        use AircraftCategory::*;
+
+        Some(match description {
+            "gliders" | "single-seat gliders" | "double-seat gliders" | "oldtimer gliders" => Glider,
+            "powered gliders" | "motor-gliders" | "motorgliders" | "moter gliders" => PoweredGlider,
+            "ultralights" | "microlights" | "ultralight aircraft" | "ultralight trikes"
+            | "ultralight aeroplanes" | "ultralight 3-axis" | "powered ultralights"
+            | "unpowered ultralights" => Ultralight,
+            "balloons" => Balloon,
+            "airships" => Airship,
+            "helicopters" | "rotocraft" | "turboshaft helicopters" | "piston engine helicopters" => {
+                Rotorcraft
+            }
+            "autogyro" | "autogyros" | "gyrocopters" | "ultralight gyro" => Gyroplane,
+            "drones" | "unmanned" | "unmanned aircraft" => Unmanned,
+            "experimental" | "experimental non-LSA aircraft" | "homebuilt" | "home-built aircraft"
+            | "amateur-builts" | "amature builds" | "amuture builds" => Experimental,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
 #[derive(Debug, Clone)]
 struct EntityData {
     entity_result: EntityResult,
     priority: i32,
     callsigns: Vec<String>,
+    /// Suffix blocks as listed by the ITU, e.g. `AAA-ZZZ`
+    suffixes: Vec<String>,
     regex: String,
     strict_regex: String,
     icao24bit_prefixes: Vec<String>,
+    /// `regex` and `strict_regex`, compiled on first use
+    compiled_regex: OnceCell<Option<Regex>>,
+    compiled_strict_regex: OnceCell<Option<Regex>>,
 }
 
+#[cfg(feature = "alloc")]
+impl EntityData {
+    /// Whether `input` matches the row's loose or strict pattern. Rows whose
+    /// pattern does not compile match nothing.
+    fn is_match(&self, input: &str, strict: bool) -> bool {
+        let (compiled, pattern) = if strict {
+            (&self.compiled_strict_regex, &self.strict_regex)
+        } else {
+            (&self.compiled_regex, &self.regex)
+        };
+        #[cfg(feature = "tracing")]
+        tracing::trace!(pattern = pattern.as_str(), cache_hit = compiled.get().is_some(), "regex lookup");
+        compiled
+            .get_or_init(|| {
+                let regex = Regex::new(pattern);
+                #[cfg(feature = "tracing")]
+                if let Err(err) = &regex {
+                    tracing::warn!(pattern = pattern.as_str(), error = %err, "dataset pattern does not compile");
+                }
+                regex.ok()
+            })
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(input))
+    }
+}
+
+#[cfg(feature = "alloc")]
 fn parse_python_list(s: &str) -> Vec<String> {
     if s.starts_with('[') && s.ends_with(']') {
         let inner = &s[1..s.len() - 1];
@@ -142,6 +344,7 @@ fn parse_python_list(s: &str) -> Vec<String> {
     }
 }
 
+#[cfg(feature = "alloc")]
 fn parse_csv_line(line: &str) -> Vec<String> {
     let mut fields = Vec::new();
     let mut current_field = String::new();
@@ -170,6 +373,7 @@ fn parse_csv_line(line: &str) -> Vec<String> {
     fields
 }
 
+#[cfg(feature = "alloc")]
 macro_rules! build_data {
     () => {{
         let mut all_data = Vec::new();
@@ -185,12 +389,17 @@ macro_rules! build_data {
             }
 
             let fields = parse_csv_line(line);
+            #[cfg(feature = "tracing")]
+            if fields.len() < 10 {
+                tracing::warn!(line, fields = fields.len(), "skipping malformed country row");
+            }
             if fields.len() >= 10 {
                 let nation = fields[0].clone();
                 let description = fields[1].clone();
                 let priority: i32 = fields[2].parse().unwrap_or(0);
                 let iso_codes = parse_python_list(&fields[3]);
                 let callsigns = parse_python_list(&fields[4]);
+                let suffixes = parse_python_list(&fields[5]);
                 let regex_str = fields[6].clone();
                 let icao24bit_prefixes = parse_python_list(&fields[9]);
 
@@ -209,9 +418,12 @@ macro_rules! build_data {
                     },
                     priority,
                     callsigns,
+                    suffixes,
                     regex: regex_str,
                     strict_regex: strict_regex_str,
                     icao24bit_prefixes,
+                    compiled_regex: OnceCell::new(),
+                    compiled_strict_regex: OnceCell::new(),
                 });
             }
         }
@@ -227,11 +439,16 @@ macro_rules! build_data {
             }
 
             let fields = parse_csv_line(line);
+            #[cfg(feature = "tracing")]
+            if fields.len() < 9 {
+                tracing::warn!(line, fields = fields.len(), "skipping malformed organization row");
+            }
             if fields.len() >= 9 {
                 let name = fields[0].clone();
                 let description = fields[1].clone();
                 let priority: i32 = fields[2].parse().unwrap_or(0);
                 let callsigns = parse_python_list(&fields[3]);
+                let suffixes = parse_python_list(&fields[4]);
                 let regex_str = fields[5].clone();
                 let icao24bit_prefixes = parse_python_list(&fields[8]);
 
@@ -245,9 +462,12 @@ macro_rules! build_data {
                     },
                     priority,
                     callsigns,
+                    suffixes,
                     regex: regex_str,
                     strict_regex: strict_regex_str,
                     icao24bit_prefixes,
+                    compiled_regex: OnceCell::new(),
+                    compiled_strict_regex: OnceCell::new(),
                 });
             }
         }
@@ -256,10 +476,12 @@ macro_rules! build_data {
     }};
 }
 
+#[cfg(feature = "alloc")]
 static DATA: Lazy<Vec<EntityData>> = Lazy::new(|| build_data!());
 
-static CALLSIGNS_MAP: Lazy<HashMap<String, Vec<usize>>> = Lazy::new(|| {
-    let mut map = HashMap::new();
+#[cfg(feature = "alloc")]
+static CALLSIGNS_MAP: Lazy<BTreeMap<String, Vec<usize>>> = Lazy::new(|| {
+    let mut map = BTreeMap::new();
     for (i, data) in DATA.iter().enumerate() {
         for callsign in &data.callsigns {
             map.entry(callsign.clone()).or_insert_with(Vec::new).push(i);
@@ -268,172 +490,432 @@ static CALLSIGNS_MAP: Lazy<HashMap<String, Vec<usize>>> = Lazy::new(|| {
     map
 });
 
-static ICAO_MAP: Lazy<HashMap<String, usize>> = Lazy::new(|| {
-    let mut map = HashMap::new();
+/// ISO2 code -> index of the row that represents the country in ICAO 24-bit
+/// lookups. The address itself is resolved by the allocation table in `icao`, so
+/// both lookups share one source of allocation data.
+#[cfg(feature = "alloc")]
+static COUNTRY_ROWS: Lazy<BTreeMap<&'static str, usize>> = Lazy::new(|| {
+    // Pick one representative row per country: prefer the row the dataset marks
+    // with ICAO prefixes, then a "general" row, then the first row.
+    let rank = |data: &EntityData| match &data.entity_result {
+        _ if !data.icao24bit_prefixes.is_empty() => 0,
+        EntityResult::Country { description, .. } if description == "general" => 1,
+        _ => 2,
+    };
+
+    let mut country_rows: BTreeMap<&str, usize> = BTreeMap::new();
     for (i, data) in DATA.iter().enumerate() {
-        for prefix in &data.icao24bit_prefixes {
-            map.insert(prefix.clone(), i);
+        if let EntityResult::Country { iso2, .. } = &data.entity_result {
+            match country_rows.get(iso2.as_str()) {
+                Some(&current) if rank(&DATA[current]) <= rank(data) => {}
+                _ => {
+                    country_rows.insert(iso2, i);
+                }
+            }
         }
     }
-    map
+    country_rows
 });
 
+/// Strict registration patterns of every dataset row for a country.
+#[cfg(feature = "proptest")]
+pub(crate) fn registration_patterns(iso2: &str) -> Vec<&'static str> {
+    DATA.iter()
+        .filter(|data| {
+            matches!(&data.entity_result, EntityResult::Country { iso2: code, .. } if code.eq_ignore_ascii_case(iso2))
+        })
+        .map(|data| data.strict_regex.as_str())
+        .collect()
+}
+
+/// Callsign prefixes with a dash of their own, e.g. Bermuda's `VP-B` within the
+/// United Kingdom's `VP`, keyed by the prefix without dashes.
+#[cfg(feature = "alloc")]
+static TERRITORY_PREFIXES: Lazy<BTreeMap<String, &'static str>> = Lazy::new(|| {
+    CALLSIGNS_MAP
+        .keys()
+        .filter(|callsign| callsign.contains('-'))
+        .map(|callsign| (callsign.replace('-', ""), callsign.as_str()))
+        .collect()
+});
+
+#[cfg(feature = "alloc")]
 static MIN_CALLSIGN_LEN: Lazy<usize> =
     Lazy::new(|| CALLSIGNS_MAP.keys().map(|k| k.len()).min().unwrap_or(0));
 
+#[cfg(feature = "alloc")]
 static MAX_CALLSIGN_LEN: Lazy<usize> =
     Lazy::new(|| CALLSIGNS_MAP.keys().map(|k| k.len()).max().unwrap_or(0));
 
-pub struct Parser;
+/// How much an attribution can be trusted, from least to most.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum Confidence {
+    /// Reached by rewriting the input, e.g. reading `VPBAA` as `VP-BAA`
+    Heuristic,
+    /// Only a prefix is known: a registration whose suffix no row of its
+    /// prefix allows, or a partial hex address
+    Prefix,
+    /// The row's pattern matches, but the suffix is outside the ITU suffix
+    /// blocks of the row
+    Lax,
+    /// The suffix lies in the row's ITU suffix blocks, or the hex address is
+    /// complete
+    Strict,
+}
+
+/// A dataset row matching a registration, as listed by `Parser::candidates`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub entity: EntityResult,
+    /// Priority of the row in the dataset, or the parser's override for its
+    /// holder. The highest priority wins.
+    pub priority: i32,
+    /// The row's callsign prefix the registration starts with, e.g. `VP-B`
+    pub prefix: String,
+    pub confidence: Confidence,
+}
+
+/// Outcome of `Parser::disambiguate`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct Disambiguation {
+    /// The answer of `Parser::parse`
+    pub entity: EntityResult,
+    /// The callsign prefix that decided it
+    pub prefix: String,
+    /// Whether another candidate was just as good, by priority, tie-break and
+    /// prefix length, so that `entity` is only the first in dataset order
+    pub ambiguous: bool,
+    /// The other candidates, best first
+    pub alternatives: Vec<Candidate>,
+}
+
+/// Orders candidates of equal priority; `Ordering::Less` prefers the first.
+#[cfg(feature = "alloc")]
+type TieBreak = dyn Fn(&Candidate, &Candidate) -> Ordering + Send + Sync;
+
+/// Holder of a row: the ISO2 code of a country, or the name of an organization.
+#[cfg(feature = "alloc")]
+fn holder(entity: &EntityResult) -> &str {
+    match entity {
+        EntityResult::Country { iso2, .. } => iso2,
+        EntityResult::Organization { name, .. } => name,
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct Parser {
+    /// Priority overrides by holder
+    priorities: BTreeMap<String, i32>,
+    tie_break: Option<Arc<TieBreak>>,
+}
 
+#[cfg(feature = "alloc")]
 impl Parser {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
-    fn parse_registration(&self, input: &str, strict: bool) -> Option<Vec<&EntityData>> {
+    /// Use `priority` instead of the dataset's for every row of `holder`, an
+    /// ISO2 code or an organization name.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::{EntityResult, Parser};
+    ///
+    /// // Bermuda's "VP-B" series is also part of the United Kingdom's "VP"
+    /// let parser = Parser::new().with_priority("GB", 1);
+    /// match parser.parse_simple("VP-BAA") {
+    ///     Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "GB"),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn with_priority(mut self, holder: &str, priority: i32) -> Self {
+        self.priorities.insert(holder.to_string(), priority);
+        self
+    }
+
+    /// Decide between matches of equal priority with `tie_break` instead of
+    /// preferring the longest prefix, e.g. to always prefer organizations over
+    /// countries.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::{EntityResult, Parser};
+    ///
+    /// // Prefer organizations, then China over Hong Kong and Taiwan for "B-"
+    /// let rank = |entity: &EntityResult| match entity {
+    ///     EntityResult::Organization { .. } => 0,
+    ///     EntityResult::Country { iso2, .. } if iso2 == "CN" => 1,
+    ///     EntityResult::Country { .. } => 2,
+    /// };
+    /// let parser = Parser::new().with_tie_break(move |a, b| rank(&a.entity).cmp(&rank(&b.entity)));
+    /// match parser.parse_simple("B-HAA") {
+    ///     Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "CN"),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn with_tie_break(
+        mut self,
+        tie_break: impl Fn(&Candidate, &Candidate) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.tie_break = Some(Arc::new(tie_break));
+        self
+    }
+
+    /// `input` written with the dash of the longest territory prefix it starts
+    /// with, e.g. `VPBAA` as `VP-BAA`. `None` if it has a dash already.
+    fn territory_form(input: &str) -> Option<String> {
+        if input.contains('-') {
+            return None;
+        }
+        TERRITORY_PREFIXES
+            .iter()
+            .filter(|(undashed, _)| input.len() > undashed.len() && input.starts_with(undashed.as_str()))
+            .max_by_key(|(undashed, _)| undashed.len())
+            .map(|(undashed, prefix)| format!("{}{}", prefix, &input[undashed.len()..]))
+    }
+
+    fn parse_registration(&self, input: &str, strict: bool) -> Vec<(&'static str, &'static EntityData)> {
+        let mut datasets = self.prefix_rows(input);
+        datasets.retain(|(_, data)| data.is_match(input, strict));
+        datasets
+    }
+
+    /// Rows with a callsign prefix `input` starts with, whatever the suffix.
+    fn prefix_rows(&self, input: &str) -> Vec<(&'static str, &'static EntityData)> {
         let mut datasets = Vec::new();
 
-        for callsign_len in *MIN_CALLSIGN_LEN..=*MAX_CALLSIGN_LEN {
+        // Longest callsign prefix first, so that among equal priorities the most
+        // specific registry (e.g. Bermuda "VP-B" over United Kingdom "VP") wins
+        for callsign_len in (*MIN_CALLSIGN_LEN..=*MAX_CALLSIGN_LEN).rev() {
             if input.len() >= callsign_len {
                 let prefix = &input[0..callsign_len];
-                if let Some(indices) = CALLSIGNS_MAP.get(prefix) {
+                if let Some((callsign, indices)) = CALLSIGNS_MAP.get_key_value(prefix) {
                     for &idx in indices {
-                        datasets.push(&DATA[idx]);
+                        datasets.push((callsign.as_str(), &DATA[idx]));
                     }
                 }
             }
         }
+        datasets
+    }
 
-        if datasets.is_empty() {
+    fn parse_icao24bit(&self, input: &str, strict: bool) -> Option<(&EntityData, Confidence)> {
+        // Non-ICAO addresses (TIS-B, anonymized) must not be attributed to a country
+        if icao::is_non_icao_hex(input) {
             return None;
         }
 
-        let mut matches_by_priority: HashMap<i32, Vec<&EntityData>> = HashMap::new();
-
-        for data in datasets {
-            let regex_str = if strict {
-                &data.strict_regex
-            } else {
-                &data.regex
-            };
-
-            if let Ok(regex) = Regex::new(regex_str) {
-                if regex.is_match(input) {
-                    matches_by_priority
-                        .entry(data.priority)
-                        .or_default()
-                        .push(data);
-                }
+        // Strict mode takes six uppercase hex digits, optionally written as
+        // three bytes separated by the same character ("AB 8E 4F", "AB:8E:4F",
+        // "AB-8E-4F")
+        let uppercase_hex = |byte: u8| byte.is_ascii_digit() || (b'A'..=b'F').contains(&byte);
+        let strict_form = match input.as_bytes() {
+            bytes @ [_, _, _, _, _, _] => bytes.iter().all(|&byte| uppercase_hex(byte)),
+            [a, b, s1, c, d, s2, e, f] => {
+                [a, b, c, d, e, f].iter().all(|&&byte| uppercase_hex(byte)) && b" :-".contains(s1) && s1 == s2
             }
+            _ => false,
+        };
+        if strict && !strict_form {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(input, "ICAO 24bit address must be hexadecimal with length of 6 chars");
+            return None;
         }
 
-        if let Some(max_priority) = matches_by_priority.keys().max() {
-            matches_by_priority.get(max_priority).cloned()
-        } else {
-            None
-        }
+        // A full address in any notation resolves through the bit-level table;
+        // otherwise the input is a partial hex prefix
+        let (iso2, confidence) = match Icao24::parse_hex(input) {
+            Ok(address) => (address.country()?, Confidence::Strict),
+            Err(_) if !strict => (icao::prefix_country(input)?, Confidence::Prefix),
+            Err(_) => return None,
+        };
+
+        COUNTRY_ROWS.get(iso2).map(|&idx| (&DATA[idx], confidence))
     }
 
-    fn parse_icao24bit(&self, input: &str, strict: bool) -> Option<Vec<&EntityData>> {
-        if strict && !Regex::new(r"^[0-9A-F]{6}$").unwrap().is_match(input) {
-            eprintln!(
-                "Warning: ICAO 24bit '{}' must be hexadecimal with length of 6 chars",
-                input
-            );
-            return None;
+    /// The row's entity, with the canonical form of `input`.
+    fn entity(data: &EntityData, input: &str) -> EntityResult {
+        match &data.entity_result {
+            EntityResult::Country {
+                nation,
+                description,
+                iso2,
+                iso3,
+                ..
+            } => EntityResult::Country {
+                nation: nation.clone(),
+                description: description.clone(),
+                iso2: iso2.clone(),
+                iso3: iso3.clone(),
+                canonical_callsign: generate_canonical_form(input, iso2, &data.callsigns),
+            },
+            EntityResult::Organization { name, description, .. } => EntityResult::Organization {
+                name: name.clone(),
+                description: description.clone(),
+                // Organizations keep normalized format
+                canonical_callsign: input.to_string(),
+            },
         }
+    }
 
-        let mut matches = Vec::new();
+    /// Every dataset row matching a registration, best first: by priority, then
+    /// by the tie-break, then longest prefix first. `parse` answers the first.
+    ///
+    /// Unless `strict`, a registration without dashes is read with the dash of
+    /// a territory prefix it starts with, so `VPBAA` is Bermuda's `VP-BAA`
+    /// rather than one of the United Kingdom's `VP` marks.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::Parser;
+    ///
+    /// let candidates = Parser::new().candidates("PH-BHA", true);
+    /// assert_eq!(candidates[0].entity.canonical_callsign(), "PH-BHA");
+    /// assert_eq!(candidates[0].priority, 0);
+    /// ```
+    pub fn candidates(&self, input: &str, strict: bool) -> Vec<Candidate> {
+        self.candidates_where(input, strict, |_, _, _| true)
+    }
 
-        for i in 0..input.len() {
-            let prefix = &input[0..=i];
-            if let Some(&idx) = ICAO_MAP.get(prefix) {
-                matches.push(&DATA[idx]);
+    /// `candidates` out of the rows `keep` accepts, given the normalized input,
+    /// the matched prefix and the row.
+    fn candidates_where(&self, input: &str, strict: bool, keep: impl Fn(&str, &str, &EntityData) -> bool) -> Vec<Candidate> {
+        let mut input = normalize_dashes(input);
+        let mut matches = self.parse_registration(&input, strict);
+        matches.retain(|(prefix, data)| keep(&input, prefix, data));
+        let mut rewritten = false;
+        if let Some(territory) = Self::territory_form(&input).filter(|_| !strict) {
+            let mut territory_matches = self.parse_registration(&territory, strict);
+            territory_matches.retain(|(prefix, data)| keep(&territory, prefix, data));
+            if !territory_matches.is_empty() {
+                (input, matches, rewritten) = (territory, territory_matches, true);
             }
         }
 
-        if matches.is_empty() {
-            None
+        let mut candidates: Vec<Candidate> = matches
+            .into_iter()
+            .map(|(prefix, data)| {
+                let confidence = if rewritten {
+                    Confidence::Heuristic
+                } else if marks::suffix_fits(&data.suffixes, input[prefix.len()..].trim_start_matches('-')) {
+                    Confidence::Strict
+                } else {
+                    Confidence::Lax
+                };
+                self.candidate(data, &input, prefix, confidence)
+            })
+            .collect();
+        // Stable, so rows that compare equal keep dataset order
+        candidates.sort_by(|a, b| self.compare(a, b));
+        candidates
+    }
+
+    fn candidate(&self, data: &EntityData, input: &str, prefix: &str, confidence: Confidence) -> Candidate {
+        let entity = Self::entity(data, input);
+        let priority = self.priorities.get(holder(&entity)).copied().unwrap_or(data.priority);
+        Candidate { entity, priority, prefix: prefix.to_string(), confidence }
+    }
+
+    fn compare(&self, a: &Candidate, b: &Candidate) -> Ordering {
+        b.priority
+            .cmp(&a.priority)
+            .then_with(|| match &self.tie_break {
+                Some(tie_break) => tie_break(a, b),
+                None => Ordering::Equal,
+            })
+            .then_with(|| b.prefix.len().cmp(&a.prefix.len()))
+    }
+
+    /// The answer of `parse` for a registration, with the prefix that decided
+    /// it and whether the choice was ambiguous. Territories sharing a series,
+    /// like Bermuda's `VP-B` within the United Kingdom's `VP`, are told apart
+    /// by the longest prefix; rows with the same prefix and priority, like
+    /// China's and Taiwan's `B`, are not.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::{EntityResult, Parser};
+    ///
+    /// let parser = Parser::new();
+    /// let bermuda = parser.disambiguate("VP-BAA", false).unwrap();
+    /// assert_eq!(bermuda.prefix, "VP-B");
+    /// assert!(!bermuda.ambiguous);
+    /// assert!(matches!(bermuda.entity, EntityResult::Country { ref iso2, .. } if iso2 == "BM"));
+    ///
+    /// let china_or_taiwan = parser.disambiguate("B-1234", false).unwrap();
+    /// assert!(china_or_taiwan.ambiguous);
+    /// ```
+    pub fn disambiguate(&self, input: &str, strict: bool) -> Option<Disambiguation> {
+        let mut candidates = self.candidates(input, strict).into_iter();
+        let best = candidates.next()?;
+        let alternatives: Vec<Candidate> = candidates.collect();
+        Some(Disambiguation {
+            ambiguous: alternatives.first().is_some_and(|next| self.compare(&best, next) == Ordering::Equal),
+            entity: best.entity,
+            prefix: best.prefix,
+            alternatives,
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), ret))]
+    pub fn parse(&self, input: &str, strict: bool, icao24bit: bool) -> Option<EntityResult> {
+        if icao24bit {
+            let normalized_input = normalize_dashes(input);
+            self.parse_icao24bit(&normalized_input, strict)
+                .map(|(data, _)| Self::entity(data, &normalized_input))
         } else {
-            Some(matches)
+            self.candidates(input, strict).into_iter().next().map(|candidate| candidate.entity)
         }
     }
 
-    pub fn parse(&self, input: &str, strict: bool, icao24bit: bool) -> Option<EntityResult> {
+    /// Like `parse`, with how much the attribution can be trusted, e.g. to
+    /// weigh sources that disagree.
+    ///
+    /// Unless `strict`, a registration that no row's pattern allows is still
+    /// attributed by its longest known prefix, at `Confidence::Prefix`.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::{Confidence, Parser};
+    ///
+    /// let parser = Parser::new();
+    /// let confidence = |input| parser.parse_with_confidence(input, false, false).map(|(_, confidence)| confidence);
+    /// assert_eq!(confidence("PH-BHA"), Some(Confidence::Strict));
+    /// // Dutch registrations have three letters
+    /// assert_eq!(confidence("PH-12"), Some(Confidence::Lax));
+    /// assert_eq!(confidence("PH-BHA-X"), Some(Confidence::Prefix));
+    /// assert_eq!(confidence("VPBAA"), Some(Confidence::Heuristic));
+    ///
+    /// let (_, confidence) = parser.parse_with_confidence("AB8", false, true).unwrap();
+    /// assert_eq!(confidence, Confidence::Prefix);
+    /// ```
+    pub fn parse_with_confidence(&self, input: &str, strict: bool, icao24bit: bool) -> Option<(EntityResult, Confidence)> {
         let normalized_input = normalize_dashes(input);
-
         if icao24bit {
-            if let Some(matches) = self.parse_icao24bit(&normalized_input, strict) {
-                matches.first().map(|data| {
-                    match &data.entity_result {
-                        EntityResult::Country {
-                            nation,
-                            description,
-                            iso2,
-                            iso3,
-                            ..
-                        } => {
-                            let canonical =
-                                generate_canonical_form(&normalized_input, iso2, &data.callsigns);
-                            EntityResult::Country {
-                                nation: nation.clone(),
-                                description: description.clone(),
-                                iso2: iso2.clone(),
-                                iso3: iso3.clone(),
-                                canonical_callsign: canonical,
-                            }
-                        }
-                        EntityResult::Organization {
-                            name, description, ..
-                        } => {
-                            let canonical = normalized_input.to_string(); // Organizations keep normalized format
-                            EntityResult::Organization {
-                                name: name.clone(),
-                                description: description.clone(),
-                                canonical_callsign: canonical,
-                            }
-                        }
-                    }
-                })
-            } else {
-                None
-            }
-        } else if let Some(matches) = self.parse_registration(&normalized_input, strict) {
-            matches.first().map(|data| {
-                match &data.entity_result {
-                    EntityResult::Country {
-                        nation,
-                        description,
-                        iso2,
-                        iso3,
-                        ..
-                    } => {
-                        let canonical =
-                            generate_canonical_form(&normalized_input, iso2, &data.callsigns);
-                        EntityResult::Country {
-                            nation: nation.clone(),
-                            description: description.clone(),
-                            iso2: iso2.clone(),
-                            iso3: iso3.clone(),
-                            canonical_callsign: canonical,
-                        }
-                    }
-                    EntityResult::Organization {
-                        name, description, ..
-                    } => {
-                        let canonical = normalized_input.to_string(); // Organizations keep normalized format
-                        EntityResult::Organization {
-                            name: name.clone(),
-                            description: description.clone(),
-                            canonical_callsign: canonical,
-                        }
-                    }
-                }
-            })
-        } else {
-            None
+            return self
+                .parse_icao24bit(&normalized_input, strict)
+                .map(|(data, confidence)| (Self::entity(data, &normalized_input), confidence));
+        }
+        if let Some(best) = self.candidates(&normalized_input, strict).into_iter().next() {
+            return Some((best.entity, best.confidence));
         }
+        if strict {
+            return None;
+        }
+
+        let mut prefixes: Vec<Candidate> = self
+            .prefix_rows(&normalized_input)
+            .into_iter()
+            .map(|(prefix, data)| self.candidate(data, &normalized_input, prefix, Confidence::Prefix))
+            .collect();
+        prefixes.sort_by(|a, b| self.compare(a, b));
+        prefixes.into_iter().next().map(|best| (best.entity, best.confidence))
     }
 
     pub fn parse_simple(&self, input: &str) -> Option<EntityResult> {
@@ -441,13 +923,61 @@ impl Parser {
     }
 }
 
-impl Default for Parser {
-    fn default() -> Self {
-        Self::new()
+/// Iterator adapter returned by `Identify::identify`.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct Identified<'p, I> {
+    inputs: I,
+    parser: &'p Parser,
+}
+
+#[cfg(feature = "alloc")]
+impl<I, S> Iterator for Identified<'_, I>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = (S, Option<EntityResult>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.inputs.next()?;
+        let entity = self.parser.parse_simple(input.as_ref());
+        Some((input, entity))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inputs.size_hint()
     }
 }
 
-#[cfg(test)]
+/// Identify each registration of a stream with `Parser::parse_simple`, keeping
+/// the input alongside its result.
+///
+/// # Examples
+/// ```
+/// use flydent::{Identify, Parser};
+///
+/// let parser = Parser::new();
+/// let inputs = vec!["PH-BHA".to_string(), "ZZZZZZZ".to_string()];
+/// let identified: Vec<_> = inputs.into_iter().identify(&parser).collect();
+/// assert_eq!(identified[0].0, "PH-BHA");
+/// assert!(identified[0].1.is_some());
+/// assert!(identified[1].1.is_none());
+/// ```
+///
+/// Lines from `BufRead::lines` are `io::Result`s; `map_while(Result::ok)`
+/// stops at the first read error. A channel's `Receiver::iter` works as is.
+#[cfg(feature = "alloc")]
+pub trait Identify: Iterator + Sized {
+    fn identify(self, parser: &Parser) -> Identified<'_, Self> {
+        Identified { inputs: self, parser }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I: Iterator> Identify for I {}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
@@ -702,4 +1232,382 @@ mod tests {
             panic!("C-FAAA should match Canada");
         }
     }
+
+    #[test]
+    fn test_non_icao_hex_not_attributed() {
+        let parser = Parser::new();
+
+        assert!(parser.parse("700123", false, true).is_some());
+        assert!(parser.parse("~700123", false, true).is_none());
+        assert!(parser.parse("~700123", true, true).is_none());
+    }
+
+    #[test]
+    fn test_icao24bit_uses_allocation_table() {
+        let parser = Parser::new();
+
+        // Sub-blocks take precedence over the enclosing allocation
+        match parser.parse("789123", false, true) {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "HK"),
+            other => panic!("Expected Hong Kong for 789123, got {:?}", other),
+        }
+        match parser.parse("780123", false, true) {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "CN"),
+            other => panic!("Expected China for 780123, got {:?}", other),
+        }
+
+        // Allocations without hex prefixes in the ITU dataset are still found
+        match parser.parse("899123", false, true) {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "TW"),
+            other => panic!("Expected Taiwan for 899123, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_icao24bit_agrees_with_bit_table() {
+        let parser = Parser::new();
+
+        // Boundaries of allocations that do not end on a nibble: Austria (9 bits)
+        // and Antigua and Barbuda (14 bits)
+        for address in [0x440000, 0x447FFF, 0x448000, 0x0CA000, 0x0CA3FF, 0x0CA400] {
+            let hex = format!("{:06X}", address);
+            let iso2 = match parser.parse(&hex, true, true) {
+                Some(EntityResult::Country { iso2, .. }) => Some(iso2),
+                _ => None,
+            };
+            assert_eq!(
+                iso2.as_deref(),
+                icao::icao_u32_to_country(address),
+                "Parser and allocation table disagree on {}",
+                hex
+            );
+        }
+
+        // Any notation Icao24 accepts works in non-strict mode
+        for input in ["ab8e4f", "0xAB8E4F", "AB:8E:4F", "ab 8e 4f", "AB-8E-4F", "AB–8E–4F"] {
+            match parser.parse(input, false, true) {
+                Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "US"),
+                other => panic!("Expected United States for {}, got {:?}", input, other),
+            }
+        }
+
+        // Strict mode takes separated bytes too, but only in uppercase
+        for input in ["AB 8E 4F", "AB:8E:4F", "AB-8E-4F", "AB—8E—4F"] {
+            assert!(parser.parse(input, true, true).is_some(), "{}", input);
+        }
+        for input in ["ab:8e:4f", "AB8E-4F", "AB 8E 4F ", "AB::8E:4F", "AB:8E-4F", "AB 8E:4F"] {
+            assert!(parser.parse(input, true, true).is_none(), "{}", input);
+        }
+        assert!(parser.parse("ab:8e-4f", false, true).is_none());
+
+        // Partial prefixes resolve only when they lie inside a single allocation
+        assert!(parser.parse("447", false, true).is_some());
+        assert!(parser.parse("44", false, true).is_none());
+        assert!(parser.parse("F00000", false, true).is_none());
+    }
+
+    #[test]
+    fn test_overseas_territories_resolve_to_territory() {
+        let parser = Parser::new();
+
+        let cases = [
+            ("VP-BAA", "BM"), // Bermuda, not the UK "VP" series
+            ("VQ-BAB", "BM"),
+            ("VP-CAB", "KY"), // Cayman Islands
+            ("VP-GAA", "GI"), // Gibraltar
+            ("F-OHAB", "PF"), // French Polynesia
+            ("F-ODAB", "RE"), // Reunion
+            ("PJ-ABC", "CW"), // Curacao
+            ("B-HAA", "HK"),  // Hong Kong, not the China "B" series
+            ("B-MAA", "MO"),  // Macao
+        ];
+        for (callsign, expected) in cases {
+            match parser.parse_simple(callsign) {
+                Some(EntityResult::Country { iso2, .. }) => {
+                    assert_eq!(iso2, expected, "{} resolved to the wrong entity", callsign)
+                }
+                other => panic!("Expected country result for {}, got {:?}", callsign, other),
+            }
+        }
+
+        // The metropolitan series still resolves to the sovereign state
+        match parser.parse_simple("G-ABCD") {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "GB"),
+            other => panic!("Expected United Kingdom for G-ABCD, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_aircraft_category_from_registration_block() {
+        let parser = Parser::new();
+        let cases = [
+            ("D-1234", Some(AircraftCategory::Glider)),
+            ("D-MABC", Some(AircraftCategory::Ultralight)),
+            ("D-OABC", Some(AircraftCategory::Balloon)),
+            ("D-HABC", Some(AircraftCategory::Rotorcraft)),
+            ("OH-123", Some(AircraftCategory::Glider)),
+            ("OH-G123", Some(AircraftCategory::Gyroplane)),
+            ("OH-U123", Some(AircraftCategory::Ultralight)),
+            ("NX123", Some(AircraftCategory::Experimental)),
+            ("N123AB", None),
+            ("G-ABCD", None),
+        ];
+        for (registration, category) in cases {
+            let result = parser.parse_simple(registration).unwrap();
+            assert_eq!(result.aircraft_category(), category, "{}", registration);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Level, Metadata, Subscriber};
+
+        /// Records the level and message of every event, and the level and
+        /// name of every span.
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<(Level, String)>>>);
+
+        struct Message(String);
+
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = format!("{:?}", value);
+                }
+            }
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let metadata = span.metadata();
+                self.0.lock().unwrap().push((*metadata.level(), metadata.name().to_string()));
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut message = Message(String::new());
+                event.record(&mut message);
+                self.0.lock().unwrap().push((*event.metadata().level(), message.0));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let parser = Parser::new();
+            assert!(parser.parse("AB8E4", true, true).is_none());
+            assert!(parser.parse_simple("N8437D").is_some());
+        });
+        let records = recorder.0.lock().unwrap();
+        assert!(records.contains(&(Level::DEBUG, "parse".to_string())));
+        assert!(records.iter().any(|(level, message)| *level == Level::WARN && message.contains("hexadecimal")));
+        assert!(records.contains(&(Level::TRACE, "regex lookup".to_string())));
+    }
+
+    #[test]
+    fn test_candidates_best_first() {
+        let parser = Parser::new();
+        let holders = |candidates: Vec<Candidate>| -> Vec<String> {
+            candidates.iter().map(|candidate| holder(&candidate.entity).to_string()).collect()
+        };
+
+        // Dataset priorities first, then the longest prefix
+        let candidates = parser.candidates("F-OHAB", false);
+        assert_eq!(candidates.iter().map(|candidate| candidate.priority).collect::<Vec<_>>(), [2, 1, 0]);
+        assert_eq!(holders(candidates), ["PF", "FR", "FR"]);
+        assert_eq!(holders(parser.candidates("B-HAA", false)), ["HK", "CN", "TW"]);
+        assert!(parser.candidates("ZZZZZZZ", false).is_empty());
+
+        let candidates = parser.candidates("4Y-ABC", true);
+        assert_eq!(candidates[0].prefix, "4Y");
+        assert_eq!(holders(candidates), ["International Civil Aviation Organization"]);
+    }
+
+    #[test]
+    fn test_territory_prefixes_without_dash() {
+        let parser = Parser::new();
+        let cases = [
+            ("VPBAA", "BM", "VP-BAA"),
+            ("VQTAB", "TC", "VQ-TAB"),
+            ("VPCAB", "KY", "VP-CAB"),
+            ("FOHAB", "PF", "F-OHAB"),
+            ("BHAA", "HK", "B-HAA"),
+            ("VPXAB", "GB", "VP-XAB"), // No territory has VP-X
+        ];
+        for (callsign, expected, canonical) in cases {
+            match parser.parse_simple(callsign) {
+                Some(EntityResult::Country { iso2, canonical_callsign, .. }) => {
+                    assert_eq!(iso2, expected, "{} resolved to the wrong entity", callsign);
+                    assert_eq!(canonical_callsign, canonical);
+                }
+                other => panic!("Expected country result for {}, got {:?}", callsign, other),
+            }
+        }
+
+        // Strict parsing requires the dash
+        assert!(parser.candidates("VPBAA", true).iter().all(|c| holder(&c.entity) != "BM"));
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        let parser = Parser::new();
+
+        let bermuda = parser.disambiguate("VQ-BAB", false).unwrap();
+        assert_eq!(bermuda.prefix, "VQ-B");
+        assert!(!bermuda.ambiguous);
+        assert_eq!(bermuda.alternatives.len(), 1);
+        assert_eq!(holder(&bermuda.alternatives[0].entity), "GB");
+        assert_eq!(bermuda.alternatives[0].prefix, "VQ");
+
+        // The Faroe Islands and Greenland share "OY-H"
+        let shared = parser.disambiguate("OYH12", false).unwrap();
+        assert_eq!(shared.prefix, "OY-H");
+        assert!(shared.ambiguous);
+        // but Danish helicopters, "OY-HAA" to "OY-HZZ", have a higher priority
+        let helicopter = parser.disambiguate("OY-HAA", false).unwrap();
+        assert_eq!(helicopter.prefix, "OY");
+        assert!(!helicopter.ambiguous);
+        assert!(helicopter.alternatives.iter().any(|c| holder(&c.entity) == "GL"));
+
+        // China and Taiwan both register under "B"
+        assert!(parser.disambiguate("B-1234", false).unwrap().ambiguous);
+        assert!(!parser.disambiguate("B-HAA", false).unwrap().ambiguous);
+
+        // A tie-break settles it
+        let parser = Parser::new().with_tie_break(|a, b| (holder(&b.entity) == "GL").cmp(&(holder(&a.entity) == "GL")));
+        let greenland = parser.disambiguate("OY-H12", false).unwrap();
+        assert!(!greenland.ambiguous);
+        assert!(matches!(greenland.entity, EntityResult::Country { ref iso2, .. } if iso2 == "GL"));
+
+        assert!(parser.disambiguate("ZZZZZZZ", false).is_none());
+    }
+
+    #[test]
+    fn test_priority_overrides() {
+        let parser = Parser::new().with_priority("FR", 3).with_priority("TW", -1);
+        let candidates = parser.candidates("F-OHAB", false);
+        assert_eq!(candidates[0].priority, 3);
+        match parser.parse_simple("F-OHAB") {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "FR"),
+            other => panic!("Expected France for F-OHAB, got {:?}", other),
+        }
+
+        // Demoting one candidate leaves the others in prefix order
+        let holders: Vec<_> = parser.candidates("B-HAA", false).iter().map(|c| holder(&c.entity).to_string()).collect();
+        assert_eq!(holders, ["HK", "CN", "TW"]);
+
+        // Organizations are overridden by name
+        let parser = Parser::new().with_priority("United Nations", 7);
+        assert_eq!(parser.candidates("4U-ABC", false)[0].priority, 7);
+    }
+
+    #[test]
+    fn test_tie_break() {
+        // Prefer the sovereign state over its territories
+        let parser = Parser::new().with_tie_break(|a, b| {
+            let territory = |candidate: &Candidate| holder(&candidate.entity) != "GB";
+            territory(a).cmp(&territory(b))
+        });
+        match parser.parse_simple("VP-BAA") {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "GB"),
+            other => panic!("Expected United Kingdom for VP-BAA, got {:?}", other),
+        }
+        // Priorities still come first
+        match parser.parse_simple("F-OHAB") {
+            Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "PF"),
+            other => panic!("Expected French Polynesia for F-OHAB, got {:?}", other),
+        }
+        // Cloned parsers share the closure
+        assert!(matches!(parser.clone().parse_simple("VP-BAA"), Some(EntityResult::Country { iso2, .. }) if iso2 == "GB"));
+    }
+
+    #[test]
+    fn test_confidence() {
+        let parser = Parser::new();
+        let confidence = |input: &str, strict: bool| parser.parse_with_confidence(input, strict, false).map(|(_, c)| c);
+
+        assert_eq!(confidence("N8437D", false), Some(Confidence::Strict));
+        assert_eq!(confidence("D-AIMA", true), Some(Confidence::Strict));
+        assert_eq!(confidence("N0123", false), Some(Confidence::Lax));
+        assert_eq!(confidence("BHAA", false), Some(Confidence::Heuristic));
+        assert_eq!(confidence("PH-BHA-X", true), None);
+        assert_eq!(confidence("QQQ", false), None);
+
+        // Same answer as parse
+        match parser.parse_with_confidence("PH-BHA-X", false, false) {
+            Some((EntityResult::Country { iso2, .. }, Confidence::Prefix)) => assert_eq!(iso2, "NL"),
+            other => panic!("Expected a prefix-only attribution, got {:?}", other),
+        }
+        let (entity, confidence) = parser.parse_with_confidence("VP-BAA", false, false).unwrap();
+        assert_eq!(entity.canonical_callsign(), parser.parse_simple("VP-BAA").unwrap().canonical_callsign());
+        assert_eq!(confidence, Confidence::Strict);
+
+        assert_eq!(parser.parse_with_confidence("3C65A1", true, true).unwrap().1, Confidence::Strict);
+        assert_eq!(parser.parse_with_confidence("3C6", false, true).unwrap().1, Confidence::Prefix);
+        assert!(parser.parse_with_confidence("3C6", true, true).is_none());
+
+        assert!(Confidence::Heuristic < Confidence::Prefix && Confidence::Lax < Confidence::Strict);
+        assert!(parser.candidates("B-HAA", false).iter().all(|c| c.confidence >= Confidence::Lax));
+    }
+
+    #[test]
+    fn test_identify_adapter() {
+        let parser = Parser::new().with_priority("GB", 1);
+        let inputs = ["VP-BAA", "N8437D", ""];
+        let identified: Vec<(&&str, Option<EntityResult>)> = inputs.iter().identify(&parser).collect();
+        assert_eq!(identified.len(), 3);
+        // The parser's configuration applies
+        assert!(matches!(&identified[0].1, Some(EntityResult::Country { iso2, .. }) if iso2 == "GB"));
+        assert!(identified[1].1.is_some());
+        assert_eq!((*identified[2].0, identified[2].1.is_none()), ("", true));
+        assert_eq!(inputs.iter().identify(&parser).size_hint(), (3, Some(3)));
+
+        #[cfg(feature = "std")]
+        {
+            use std::io::BufRead;
+            let reader = std::io::Cursor::new("D-AIMA\nG-EUPT\n");
+            let iso2: Vec<String> = reader
+                .lines()
+                .map_while(Result::ok)
+                .identify(&parser)
+                .filter_map(|(_, entity)| match entity? {
+                    EntityResult::Country { iso2, .. } => Some(iso2),
+                    EntityResult::Organization { .. } => None,
+                })
+                .collect();
+            assert_eq!(iso2, ["DE", "GB"]);
+
+            let (sender, receiver) = std::sync::mpsc::channel();
+            sender.send("PH-BHA").unwrap();
+            drop(sender);
+            assert_eq!(receiver.iter().identify(&parser).count(), 1);
+        }
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv_classification_output() {
+        let parser = Parser::new();
+        let results: Vec<Option<EntityResult>> = ["PH-BHA", "4Y-ABC", "ZZZZZZZ"]
+            .iter()
+            .map(|callsign| parser.parse_simple(callsign))
+            .collect();
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&results).unwrap();
+        let archived = rkyv::access::<rkyv::Archived<Vec<Option<EntityResult>>>, rkyv::rancor::Error>(&bytes).unwrap();
+        match archived[0].as_ref() {
+            Some(ArchivedEntityResult::Country { iso2, .. }) => assert_eq!(iso2, "NL"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(archived[1].as_ref(), Some(ArchivedEntityResult::Organization { .. })));
+        assert!(archived[2].is_none());
+    }
 }
@@ -1,6 +1,34 @@
+//! Command-line interface.
+//!
+//! `flydent reg`, `flydent hex` and `flydent convert` print one line per
+//! argument, human-readable or as JSON objects with `--json`, and exit with
+//! status 1 if any argument could not be identified. Without a subcommand the
+//! arguments are parsed like the original flydenity CLI and printed as a single
+//! JSON object.
+//!
+//! `flydent batch` streams one identifier per line from a file or stdin and
+//! writes CSV or JSON Lines, holding only the current line in memory.
+//!
+//! `flydent sbs` enriches an SBS-1 BaseStation feed piped to it, e.g. from
+//! port 30003, line by line.
+//!
+//! `flydent aircraft-json` annotates a readsb/dump1090 `aircraft.json`, once or
+//! every few seconds, see `flydent::readsb`.
+
+use flydent::icao::{self, Icao24};
+use flydent::readsb;
+use flydent::registration;
+use flydent::sbs::EnrichSbs;
 use flydent::{EntityResult, Parser};
+use serde_json::json;
 use std::collections::HashMap;
 use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
 
 fn entity_result_to_json(result: &EntityResult) -> serde_json::Value {
     match result {
@@ -48,9 +76,413 @@ fn entity_result_to_json(result: &EntityResult) -> serde_json::Value {
     }
 }
 
+/// Result of a subcommand for one argument: the JSON object and the
+/// human-readable line, or `None` if it could not be identified.
+type Output = (serde_json::Value, Option<String>);
+
+fn identify_registration(parser: &Parser, input: &str) -> Output {
+    match parser.parse_simple(input) {
+        Some(entity) => {
+            let line = match &entity {
+                EntityResult::Country { nation, description, iso2, canonical_callsign, .. } => {
+                    format!("{}: {} ({}), {}", canonical_callsign, nation, iso2, description)
+                }
+                EntityResult::Organization { name, description, canonical_callsign } => {
+                    format!("{}: {}, {}", canonical_callsign, name, description)
+                }
+            };
+            (json!({ "input": input, "entity": entity_result_to_json(&entity) }), Some(line))
+        }
+        None => (json!({ "input": input, "entity": null }), None),
+    }
+}
+
+fn identify_hex(input: &str) -> Output {
+    let icao = match Icao24::parse_hex(input) {
+        Ok(icao) => icao,
+        Err(err) => return (json!({ "input": input, "error": err.to_string() }), None),
+    };
+    let Some(allocation) = icao::lookup(icao.value()) else {
+        return (json!({ "input": input, "hex": icao.to_string(), "allocation": null }), None);
+    };
+    let registration = registration::icao_to_registration(icao.to_bytes()).ok();
+
+    let mut line = match (allocation.iso2, allocation.special_use) {
+        (Some(iso2), _) => format!("{}: {} ({})", icao, allocation.name, iso2),
+        (None, Some(special_use)) => format!("{}: {}", icao, special_use),
+        (None, None) => format!("{}: {}", icao, allocation.name),
+    };
+    line.push_str(&format!(", block {}-{}", allocation.start_hex(), allocation.end_hex()));
+    if let Some(registration) = &registration {
+        line.push_str(&format!(", registration {}", registration));
+    }
+    let value = json!({
+        "input": input,
+        "hex": icao.to_string(),
+        "allocation": {
+            "iso2": allocation.iso2,
+            "name": allocation.name,
+            "special_use": allocation.special_use.map(|special_use| special_use.description()),
+            "start": allocation.start_hex(),
+            "end": allocation.end_hex(),
+        },
+        "registration": registration,
+    });
+    (value, Some(line))
+}
+
+fn convert(input: &str) -> Output {
+    match registration::convert(input) {
+        Ok(converted) => {
+            let line = format!(
+                "{}: {} {} ({}, {})",
+                input, converted.icao, converted.registration, converted.iso2, converted.scheme
+            );
+            let value = json!({
+                "input": input,
+                "hex": converted.icao.to_string(),
+                "registration": converted.registration,
+                "iso2": converted.iso2,
+                "scheme": converted.scheme,
+            });
+            (value, Some(line))
+        }
+        Err(err) => (json!({ "input": input, "error": err.to_string() }), None),
+    }
+}
+
+/// Run `reg`, `hex` or `convert` over its arguments and exit.
+fn run_subcommand(program: &str, command: &str, args: &[String]) -> ! {
+    let json_output = args.iter().any(|arg| arg == "--json");
+    let inputs: Vec<&String> = args.iter().filter(|arg| *arg != "--json").collect();
+    if inputs.is_empty() {
+        eprintln!("Usage: {} {} [--json] <value>...", program, command);
+        process::exit(2);
+    }
+
+    let parser = Parser::new();
+    let mut all_identified = true;
+    for input in inputs {
+        let (value, line) = match command {
+            "reg" => identify_registration(&parser, input),
+            "hex" => identify_hex(input),
+            _ => convert(input),
+        };
+        all_identified &= line.is_some();
+        if json_output {
+            println!("{}", value);
+        } else {
+            match (line, value.get("error").and_then(|error| error.as_str())) {
+                (Some(line), _) => println!("{}", line),
+                (None, Some(error)) => println!("{}: {}", input, error),
+                (None, None) => println!("{}: unknown", input),
+            }
+        }
+    }
+    process::exit(if all_identified { 0 } else { 1 });
+}
+
+/// Output format of `flydent batch`.
+#[derive(Clone, Copy, PartialEq)]
+enum BatchFormat {
+    Csv,
+    Jsonl,
+}
+
+const BATCH_CSV_HEADER: &str = "input,kind,iso2,name,hex,registration";
+
+/// Classification of one `flydent batch` input line.
+struct BatchRecord<'a> {
+    input: &'a str,
+    /// `"hex"`, `"registration"` or `"unknown"`
+    kind: &'static str,
+    iso2: Option<String>,
+    name: Option<String>,
+    hex: Option<String>,
+    registration: Option<String>,
+}
+
+fn classify_line<'a>(parser: &Parser, input: &'a str) -> BatchRecord<'a> {
+    let mut record = BatchRecord { input, kind: "unknown", iso2: None, name: None, hex: None, registration: None };
+
+    if let Ok(icao) = Icao24::parse_hex(input) {
+        record.kind = "hex";
+        record.hex = Some(icao.to_string());
+        if let Some(allocation) = icao::lookup(icao.value()) {
+            record.iso2 = allocation.iso2.map(str::to_string);
+            record.name = Some(allocation.name.to_string());
+        }
+        record.registration = registration::icao_to_registration(icao.to_bytes()).ok();
+    } else if let Some(entity) = parser.parse_simple(input) {
+        record.kind = "registration";
+        record.hex = registration::registration_to_icao(input).ok().map(|bytes| Icao24::from_bytes(bytes).to_string());
+        match entity {
+            EntityResult::Country { nation, iso2, canonical_callsign, .. } => {
+                record.iso2 = Some(iso2);
+                record.name = Some(nation);
+                record.registration = Some(canonical_callsign);
+            }
+            EntityResult::Organization { name, canonical_callsign, .. } => {
+                record.name = Some(name);
+                record.registration = Some(canonical_callsign);
+            }
+        }
+    }
+    record
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl BatchRecord<'_> {
+    fn write(&self, out: &mut impl Write, format: BatchFormat) -> io::Result<()> {
+        match format {
+            BatchFormat::Csv => {
+                let fields = [
+                    Some(self.input),
+                    Some(self.kind),
+                    self.iso2.as_deref(),
+                    self.name.as_deref(),
+                    self.hex.as_deref(),
+                    self.registration.as_deref(),
+                ];
+                let fields: Vec<String> = fields.iter().map(|field| csv_field(field.unwrap_or(""))).collect();
+                writeln!(out, "{}", fields.join(","))
+            }
+            BatchFormat::Jsonl => {
+                let value = json!({
+                    "input": self.input,
+                    "kind": self.kind,
+                    "iso2": self.iso2,
+                    "name": self.name,
+                    "hex": self.hex,
+                    "registration": self.registration,
+                });
+                writeln!(out, "{}", value)
+            }
+        }
+    }
+}
+
+fn batch_usage(program: &str) -> ! {
+    eprintln!("Usage: {} batch [--in <FILE>] [--format csv|jsonl]", program);
+    eprintln!();
+    eprintln!("Classifies one hex address or registration per line of <FILE> (default: stdin,");
+    eprintln!("also with `-`) and prints the country, hex address and registration of each.");
+    eprintln!("    --in <FILE>       Input file");
+    eprintln!("    --format <FMT>    csv (default, with a header row) or jsonl");
+    process::exit(2);
+}
+
+/// Stream `flydent batch` input to stdout, one record per non-empty line, and exit.
+fn run_batch(program: &str, args: &[String]) -> ! {
+    let mut path = None;
+    let mut format = BatchFormat::Csv;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--in" => path = Some(args.next().unwrap_or_else(|| batch_usage(program)).clone()),
+            "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("csv") => BatchFormat::Csv,
+                    Some("jsonl") => BatchFormat::Jsonl,
+                    _ => batch_usage(program),
+                }
+            }
+            _ => batch_usage(program),
+        }
+    }
+
+    let input: Box<dyn BufRead> = match path.as_deref() {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("Error: {}: {}", path, err);
+                process::exit(2);
+            }
+        },
+    };
+
+    let parser = Parser::new();
+    let mut out = BufWriter::new(io::stdout().lock());
+    let result = (|| -> io::Result<()> {
+        if format == BatchFormat::Csv {
+            writeln!(out, "{}", BATCH_CSV_HEADER)?;
+        }
+        for line in input.lines() {
+            let line = line?;
+            let input = line.trim();
+            if input.is_empty() {
+                continue;
+            }
+            classify_line(&parser, input).write(&mut out, format)?;
+        }
+        out.flush()
+    })();
+
+    match result {
+        Ok(()) => process::exit(0),
+        // The reader went away, e.g. `flydent batch | head`
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(2);
+        }
+    }
+}
+
+fn sbs_usage(program: &str) -> ! {
+    eprintln!("Usage: {} sbs [--format csv|jsonl]", program);
+    eprintln!();
+    eprintln!("Enriches SBS-1 BaseStation messages read from stdin, e.g.");
+    eprintln!("`nc localhost 30003 | {} sbs`, writing each line as soon as it arrives.", program);
+    eprintln!("    --format <FMT>    csv (default): the message with iso2 and registration");
+    eprintln!("                      appended as two more fields; or jsonl");
+    process::exit(2);
+}
+
+/// Stream `flydent sbs` input to stdout and exit.
+fn run_sbs(program: &str, args: &[String]) -> ! {
+    let format = match args {
+        [] => BatchFormat::Csv,
+        [flag, format] if flag == "--format" => match format.as_str() {
+            "csv" => BatchFormat::Csv,
+            "jsonl" => BatchFormat::Jsonl,
+            _ => sbs_usage(program),
+        },
+        _ => sbs_usage(program),
+    };
+
+    // stdout is line-buffered, so every record goes out as soon as it is enriched
+    let mut out = io::stdout().lock();
+    let result = (|| -> io::Result<()> {
+        for record in io::stdin().lock().lines().map_while(Result::ok).enrich_sbs() {
+            match format {
+                BatchFormat::Csv => writeln!(out, "{}", record.to_csv())?,
+                BatchFormat::Jsonl => {
+                    let value = json!({
+                        "message": record.line,
+                        "hex": record.icao.map(|icao| icao.to_string()),
+                        "iso2": record.iso2,
+                        "registration": record.registration,
+                    });
+                    writeln!(out, "{}", value)?
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => process::exit(0),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(2);
+        }
+    }
+}
+
+fn aircraft_json_usage(program: &str) -> ! {
+    eprintln!("Usage: {} aircraft-json [--in <FILE>] [--out <FILE>] [--interval <SECS>]", program);
+    eprintln!();
+    eprintln!("Adds the country, flag and derived registration of each aircraft to a");
+    eprintln!("readsb/dump1090 aircraft.json.");
+    eprintln!("    --in <FILE>         Input file (default: stdin)");
+    eprintln!("    --out <FILE>        Output file, replaced atomically (default: stdout)");
+    eprintln!("    --interval <SECS>   Repeat every SECS seconds, e.g. for /run/readsb/aircraft.json");
+    process::exit(2);
+}
+
+/// Read, annotate and write one `aircraft.json`.
+fn enrich_aircraft_json(input: Option<&str>, output: Option<&str>) -> io::Result<usize> {
+    let json = match input {
+        None | Some("-") => {
+            let mut json = String::new();
+            io::stdin().read_to_string(&mut json)?;
+            json
+        }
+        Some(path) => fs::read_to_string(path)?,
+    };
+    let mut document: serde_json::Value = serde_json::from_str(&json)?;
+    let annotated = readsb::enrich(&mut document);
+    let enriched = serde_json::to_string(&document)?;
+
+    match output {
+        None | Some("-") => println!("{}", enriched),
+        Some(path) => {
+            // Web servers must never serve a half-written file
+            let tmp = format!("{}.tmp", path);
+            fs::write(&tmp, enriched)?;
+            fs::rename(&tmp, Path::new(path))?;
+        }
+    }
+    Ok(annotated)
+}
+
+/// Run `flydent aircraft-json` and exit.
+fn run_aircraft_json(program: &str, args: &[String]) -> ! {
+    let mut input = None;
+    let mut output = None;
+    let mut interval = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| aircraft_json_usage(program));
+        match arg.as_str() {
+            "--in" => input = Some(value.as_str()),
+            "--out" => output = Some(value.as_str()),
+            "--interval" => match value.parse::<f64>() {
+                Ok(secs) if secs > 0.0 => interval = Some(Duration::from_secs_f64(secs)),
+                _ => aircraft_json_usage(program),
+            },
+            _ => aircraft_json_usage(program),
+        }
+    }
+
+    let Some(interval) = interval else {
+        match enrich_aircraft_json(input, output) {
+            Ok(_) => process::exit(0),
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                process::exit(1);
+            }
+        }
+    };
+    if matches!(input, None | Some("-")) {
+        eprintln!("Error: --interval needs --in <FILE>");
+        process::exit(2);
+    }
+    loop {
+        // The receiver may be restarting; keep going until it is back
+        if let Err(err) = enrich_aircraft_json(input, output) {
+            eprintln!("Error: {}", err);
+        }
+        thread::sleep(interval);
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
+    if let Some(command @ ("reg" | "hex" | "convert")) = args.get(1).map(String::as_str) {
+        run_subcommand(&args[0], command, &args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("batch") {
+        run_batch(&args[0], &args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("sbs") {
+        run_sbs(&args[0], &args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("aircraft-json") {
+        run_aircraft_json(&args[0], &args[2..]);
+    }
+
     if args.len() < 2 {
         eprintln!(
             "Usage: {} [--icao24bit] <callsign1> [callsign2] ...",
@@ -65,12 +497,27 @@ fn main() {
         println!();
         println!("USAGE:");
         println!("    {} [OPTIONS] <callsign>...", args[0]);
+        println!("    {} reg [--json] <registration>...", args[0]);
+        println!("    {} hex [--json] <hex>...", args[0]);
+        println!("    {} convert [--json] <registration-or-hex>...", args[0]);
+        println!("    {} batch [--in <FILE>] [--format csv|jsonl]", args[0]);
+        println!("    {} sbs [--format csv|jsonl]", args[0]);
+        println!("    {} aircraft-json [--in <FILE>] [--out <FILE>] [--interval <SECS>]", args[0]);
+        println!();
+        println!("SUBCOMMANDS:");
+        println!("    reg        Identify the country or organization of registrations");
+        println!("    hex        Look up the allocation block and registration of ICAO addresses");
+        println!("    convert    Convert between registrations and ICAO addresses");
+        println!("    batch      Classify one registration or hex address per input line");
+        println!("    sbs        Enrich an SBS-1 BaseStation feed read from stdin");
+        println!("    aircraft-json  Annotate a readsb/dump1090 aircraft.json");
         println!();
         println!("ARGS:");
         println!("    <callsign>...    Aircraft callsign(s) or ICAO 24-bit identifier(s) to parse");
         println!();
         println!("OPTIONS:");
         println!("        --icao24bit    Parse arguments as ICAO 24-bit identifiers instead of callsigns");
+        println!("        --json         Print one JSON object per argument (subcommands)");
         println!("    -h, --help         Print help information");
         println!();
         println!("EXAMPLES:");
@@ -82,6 +529,8 @@ fn main() {
             "    {} --icao24bit 700123   Parse ICAO 24-bit identifier",
             args[0]
         );
+        println!("    {} reg PH-BHA           Identify a registration", args[0]);
+        println!("    {} convert --json N8437D", args[0]);
         return;
     }
 
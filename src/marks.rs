@@ -0,0 +1,572 @@
+//! Nationality Marks
+//!
+//! The ITU allocates each state whole call sign series (the Netherlands holds
+//! PA through PI), but aircraft carry a single nationality mark out of them
+//! (PH). This module knows that mark per state and renders the registration
+//! format from the suffix blocks of the ITU dataset, e.g. for input hints and
+//! validation messages, or as example registrations. The mark table is
+//! generated by `build.rs` from `data/nationality-marks.csv`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::{generate_canonical_form, EntityData, EntityResult, Parser, DATA};
+
+// Marks generated by `build.rs` from `data/nationality-marks.csv`, sorted by
+// ISO2 code.
+include!(concat!(env!("OUT_DIR"), "/nationality_marks.rs"));
+
+/// The nationality mark aircraft of a state carry, e.g. `"PH"` for `"NL"`.
+///
+/// # Examples
+/// ```
+/// use flydent::marks::nationality_mark;
+///
+/// assert_eq!(nationality_mark("NL"), Some("PH"));
+/// assert_eq!(nationality_mark("us"), Some("N"));
+/// assert_eq!(nationality_mark("BM"), Some("VP-B"));
+/// assert_eq!(nationality_mark("XX"), None);
+/// ```
+pub fn nationality_mark(iso2: &str) -> Option<&'static str> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    NATIONALITY_MARKS
+        .binary_search_by(|(code, _)| (*code).cmp(iso2.as_str()))
+        .ok()
+        .map(|index| NATIONALITY_MARKS[index].1)
+}
+
+/// Human-readable registration format of a state, built from the suffix blocks
+/// the dataset lists for its nationality mark, e.g. `"PH-AAA…PH-ZZZ"`.
+///
+/// Blocks of the general series are used where the mark has one; otherwise all
+/// blocks of the mark (e.g. Luxembourg's balloons, gliders and ultralights).
+/// Several blocks are separated by `", "`.
+///
+/// # Examples
+/// ```
+/// use flydent::marks::format_template;
+///
+/// assert_eq!(format_template("NL").unwrap(), "PH-AAA…PH-ZZZ");
+/// assert_eq!(format_template("US").unwrap(), "N1…N99999, N1A…N9999Z, N1AA…N999ZZ");
+/// assert_eq!(format_template("AT").unwrap(), "OE-AAA…OE-KZZ");
+/// assert_eq!(format_template("XX"), None);
+/// ```
+pub fn format_template(iso2: &str) -> Option<String> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    let (mark, rows) = template_rows(&iso2)?;
+    let marks = [mark.to_string()];
+
+    let registration = |suffix: &str| generate_canonical_form(&format!("{}{}", mark, suffix), &iso2, &marks);
+    let mut blocks: Vec<String> = Vec::new();
+    for suffix in rows.iter().flat_map(|data| &data.suffixes) {
+        let block = match suffix.split_once('-') {
+            Some((first, last)) if first != last => format!("{}…{}", registration(first), registration(last)),
+            Some((first, _)) => registration(first),
+            None => registration(suffix),
+        };
+        if !blocks.contains(&block) {
+            blocks.push(block);
+        }
+    }
+
+    if blocks.is_empty() {
+        None
+    } else {
+        Some(blocks.join(", "))
+    }
+}
+
+/// The nationality mark of a state and the dataset rows `format_template`
+/// renders: those of the general series, or all rows of the mark.
+fn template_rows(iso2: &str) -> Option<(&'static str, Vec<&'static EntityData>)> {
+    let mark = nationality_mark(iso2)?;
+    let rows: Vec<&EntityData> = DATA
+        .iter()
+        .filter(|data| match &data.entity_result {
+            EntityResult::Country { iso2: code, .. } => *code == iso2,
+            EntityResult::Organization { .. } => false,
+        })
+        .filter(|data| data.callsigns.iter().any(|callsign| callsign == mark))
+        .collect();
+    let general: Vec<&EntityData> = rows.iter().copied().filter(|data| data.priority == 0).collect();
+    Some((mark, if general.is_empty() { rows } else { general }))
+}
+
+/// A representative suffix of the block from `first` to `last`: ascending
+/// letters and digits (`ABC`, `12345`) as long as `last`, kept within the
+/// bounds where they have the same length, e.g. `FBCD` for `FAAA-FZZZ`. A `*`
+/// stands for any letter or digit.
+fn example_suffix(first: &str, last: &str) -> String {
+    let first = first.as_bytes();
+    let same_length = first.len() == last.len();
+    last.bytes()
+        .enumerate()
+        .map(|(i, high)| {
+            let example = if high.is_ascii_digit() { b'1' + (i % 9) as u8 } else { b'A' + (i % 26) as u8 };
+            match first.get(i) {
+                // Any letter or digit
+                _ if high == b'*' => example,
+                Some(&low) if low == high => high,
+                Some(&low) if same_length && low.is_ascii_digit() == high.is_ascii_digit() => example.clamp(low, high),
+                _ => example,
+            }
+        })
+        .map(char::from)
+        .collect()
+}
+
+/// A representative registration of a state, e.g. `"PH-ABC"` or `"N12345"`,
+/// for documentation, input placeholders and test fixtures.
+///
+/// It is synthesized from the first block of `format_template` and matches the
+/// state's patterns, though a shared series may parse as another state first
+/// (Switzerland's `HB` is also Liechtenstein's). `None` if the state has no
+/// nationality mark.
+///
+/// # Examples
+/// ```
+/// use flydent::marks::example_registration;
+///
+/// assert_eq!(example_registration("NL").unwrap(), "PH-ABC");
+/// assert_eq!(example_registration("US").unwrap(), "N12345");
+/// assert_eq!(example_registration("CA").unwrap(), "C-FBCD");
+/// assert_eq!(example_registration("XX"), None);
+/// ```
+pub fn example_registration(iso2: &str) -> Option<String> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    let (mark, rows) = template_rows(&iso2)?;
+    let marks = [mark.to_string()];
+    let parser = Parser::new();
+
+    let registration = |suffix: &str| generate_canonical_form(&format!("{}{}", mark, suffix), &iso2, &marks);
+    let matches_state = |registration: &String| {
+        let candidates = parser.candidates(registration, false);
+        candidates.iter().any(|candidate| matches!(&candidate.entity, EntityResult::Country { iso2: code, .. } if *code == iso2))
+    };
+    // The synthesized suffix of each block, then its bounds
+    let examples = rows.iter().flat_map(|data| &data.suffixes).flat_map(|suffix| {
+        let (first, last) = suffix.split_once('-').unwrap_or((suffix, suffix));
+        [example_suffix(first, last), first.to_string(), last.to_string()]
+    });
+    examples.map(|suffix| registration(&suffix)).find(matches_state)
+}
+
+/// One position of a suffix block: a run of digits counted as a number,
+/// zero-padded to `width` if the block's bounds have the same length, or a
+/// single character out of a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Run {
+    Number { low: u32, high: u32, width: usize },
+    Chars(Vec<u8>),
+}
+
+impl Run {
+    fn len(&self) -> u64 {
+        match self {
+            Run::Number { low, high, .. } => u64::from(high - low) + 1,
+            Run::Chars(chars) => chars.len() as u64,
+        }
+    }
+
+    /// Length of the part of `suffix` this run accepts at its start, if any.
+    fn accepts(&self, suffix: &[u8]) -> Option<usize> {
+        match self {
+            Run::Number { low, high, width } => {
+                let len = suffix.iter().take_while(|byte| byte.is_ascii_digit()).count();
+                let digits = core::str::from_utf8(&suffix[..len]).ok()?;
+                let padded = if *width > 0 { len == *width } else { !digits.starts_with('0') };
+                let value: u32 = digits.parse().ok()?;
+                (padded && (*low..=*high).contains(&value)).then_some(len)
+            }
+            Run::Chars(chars) => suffix.first().filter(|byte| chars.contains(byte)).map(|_| 1),
+        }
+    }
+}
+
+/// Digit runs and single characters of a suffix, e.g. `1`, `A` for `1A`.
+fn tokens(bound: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = bound;
+    while let Some(first) = rest.bytes().next() {
+        let len = if first.is_ascii_digit() { rest.bytes().take_while(u8::is_ascii_digit).count() } else { 1 };
+        let (token, tail) = rest.split_at(len);
+        tokens.push(token);
+        rest = tail;
+    }
+    tokens
+}
+
+/// Whether `suffix` lies in one of the suffix blocks of a dataset row, e.g.
+/// `BHA` in `AAA-ZZZ` but not `L350`. Rows without expandable blocks accept
+/// nothing.
+pub(crate) fn suffix_fits(blocks: &[String], suffix: &str) -> bool {
+    blocks.iter().filter_map(|block| block_runs(block)).any(|runs| {
+        let mut rest = suffix.as_bytes();
+        for run in &runs {
+            match run.accepts(rest) {
+                Some(len) => rest = &rest[len..],
+                None => return false,
+            }
+        }
+        rest.is_empty()
+    })
+}
+
+/// The runs of a suffix block such as `AAA-KZZ`, `1A-9999Z` or `1***-3***`,
+/// where `*` is any letter or digit. `None` if the bounds have different
+/// shapes.
+fn block_runs(block: &str) -> Option<Vec<Run>> {
+    let (first, last) = block.split_once('-').unwrap_or((block, block));
+    let (first, last) = (tokens(first), tokens(last));
+    if first.len() != last.len() {
+        return None;
+    }
+    first
+        .into_iter()
+        .zip(last)
+        .map(|(low, high)| match (low.as_bytes()[0], high.as_bytes()[0]) {
+            (b'*', b'*') => Some(Run::Chars((b'A'..=b'Z').chain(b'0'..=b'9').collect())),
+            (l, h) if l.is_ascii_digit() && h.is_ascii_digit() => {
+                let (low_value, high_value) = (low.parse().ok()?, high.parse().ok()?);
+                let width = if low.len() == high.len() { low.len() } else { 0 };
+                (low_value <= high_value).then_some(Run::Number { low: low_value, high: high_value, width })
+            }
+            (l, h) if l.is_ascii_uppercase() && h.is_ascii_uppercase() && l <= h => Some(Run::Chars((l..=h).collect())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Number of suffixes of a block, before the strict pattern is applied.
+fn suffix_count(runs: &[Run]) -> u64 {
+    runs.iter().map(Run::len).product()
+}
+
+/// The `index`-th suffix of a block, the last run changing fastest.
+fn nth_suffix(runs: &[Run], index: u64) -> String {
+    let mut parts = Vec::with_capacity(runs.len());
+    let mut rest = index;
+    for run in runs.iter().rev() {
+        let offset = rest % run.len();
+        rest /= run.len();
+        parts.push(match run {
+            Run::Number { low, width, .. } => format!("{:0width$}", u64::from(*low) + offset, width = *width),
+            Run::Chars(chars) => char::from(chars[offset as usize]).to_string(),
+        });
+    }
+    parts.reverse();
+    parts.concat()
+}
+
+/// A suffix block of a state's registration format.
+#[derive(Debug, Clone)]
+struct Block {
+    iso2: &'static str,
+    mark: &'static str,
+    row: &'static EntityData,
+    runs: Vec<Run>,
+}
+
+impl Block {
+    /// The registration with `suffix`, if the strict pattern of the row allows it.
+    fn registration(&self, suffix: &str) -> Option<String> {
+        // Strict patterns always separate the suffix with a dash
+        if !self.row.is_match(&format!("{}-{}", self.mark, suffix), true) {
+            return None;
+        }
+        let marks = [self.mark.to_string()];
+        Some(generate_canonical_form(&format!("{}{}", self.mark, suffix), self.iso2, &marks))
+    }
+}
+
+/// The blocks of `format_template` that can be expanded.
+fn template_blocks(iso2: &str) -> Vec<Block> {
+    let Some((mark, rows)) = template_rows(iso2) else {
+        return Vec::new();
+    };
+    rows.into_iter()
+        .flat_map(|row| {
+            let iso2 = match &row.entity_result {
+                EntityResult::Country { iso2, .. } => iso2.as_str(),
+                EntityResult::Organization { .. } => unreachable!("template rows are countries"),
+            };
+            row.suffixes
+                .iter()
+                .filter_map(|suffix| block_runs(suffix))
+                .map(move |runs| Block { iso2, mark, row, runs })
+        })
+        .collect()
+}
+
+/// Every suffix of a block, in order.
+struct Suffixes {
+    runs: Vec<Run>,
+    index: u64,
+    total: u64,
+}
+
+impl Suffixes {
+    fn new(runs: Vec<Run>) -> Self {
+        let total = suffix_count(&runs);
+        Suffixes { runs, index: 0, total }
+    }
+}
+
+impl Iterator for Suffixes {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.index >= self.total {
+            return None;
+        }
+        let suffix = nth_suffix(&self.runs, self.index);
+        self.index += 1;
+        Some(suffix)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.total - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Lazily yield every registration of a state: each suffix of the blocks of
+/// `format_template` that the strict pattern of its row allows, in block order.
+/// Nothing for states without a nationality mark.
+///
+/// Useful to pre-generate validation dictionaries, or to count the namespace
+/// of a state.
+///
+/// # Examples
+/// ```
+/// use flydent::marks::enumerate_registrations;
+///
+/// let mut registrations = enumerate_registrations("NL");
+/// assert_eq!(registrations.next().as_deref(), Some("PH-AAA"));
+/// assert_eq!(registrations.next().as_deref(), Some("PH-AAB"));
+/// assert_eq!(enumerate_registrations("NL").count(), 26 * 26 * 26);
+/// assert_eq!(enumerate_registrations("US").nth(99_999).as_deref(), Some("N1A"));
+/// assert_eq!(enumerate_registrations("XX").next(), None);
+/// ```
+pub fn enumerate_registrations(iso2: &str) -> impl Iterator<Item = String> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    template_blocks(&iso2).into_iter().flat_map(|block| {
+        Suffixes::new(block.runs.clone()).filter_map(move |suffix| block.registration(&suffix))
+    })
+}
+
+/// Draws before `random_registration` gives up on a state whose blocks its
+/// strict patterns mostly reject.
+#[cfg(feature = "rand")]
+const RANDOM_ATTEMPTS: usize = 1000;
+
+/// A uniformly random registration of a state, out of those yielded by
+/// `enumerate_registrations`, e.g. for fuzzing or synthetic traffic. Requires
+/// the `rand` feature.
+///
+/// Suffixes are drawn uniformly over all blocks and redrawn while the strict
+/// pattern rejects them. `None` if the state has no nationality mark, or if no
+/// valid registration turns up in 1000 draws.
+///
+/// # Examples
+/// ```
+/// use flydent::marks::random_registration;
+/// use flydent::{EntityResult, Parser};
+/// use rand::SeedableRng;
+///
+/// let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+/// let registration = random_registration("NL", &mut rng).unwrap();
+/// assert!(registration.starts_with("PH-"));
+/// match Parser::new().parse(&registration, true, false) {
+///     Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "NL"),
+///     other => panic!("{} parsed as {:?}", registration, other),
+/// }
+/// ```
+#[cfg(feature = "rand")]
+pub fn random_registration<R: rand::Rng + ?Sized>(iso2: &str, rng: &mut R) -> Option<String> {
+    let blocks = template_blocks(&iso2.trim().to_ascii_uppercase());
+    let total: u64 = blocks.iter().map(|block| suffix_count(&block.runs)).sum();
+    if total == 0 {
+        return None;
+    }
+
+    (0..RANDOM_ATTEMPTS).find_map(|_| {
+        let mut index = rng.random_range(0..total);
+        let block = blocks.iter().find(|block| {
+            let count = suffix_count(&block.runs);
+            let found = index < count;
+            if !found {
+                index -= count;
+            }
+            found
+        })?;
+        block.registration(&nth_suffix(&block.runs, index))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_are_dataset_callsigns() {
+        for (iso2, mark) in NATIONALITY_MARKS {
+            let listed = DATA.iter().any(|data| match &data.entity_result {
+                EntityResult::Country { iso2: code, .. } => code == iso2 && data.callsigns.iter().any(|c| c == mark),
+                EntityResult::Organization { .. } => false,
+            });
+            assert!(listed, "{} is not a callsign of {}", mark, iso2);
+        }
+    }
+
+    #[test]
+    fn every_mark_has_a_template() {
+        for (iso2, _) in NATIONALITY_MARKS {
+            assert!(format_template(iso2).is_some(), "no template for {}", iso2);
+        }
+    }
+
+    #[test]
+    fn every_mark_has_an_example() {
+        let parser = Parser::new();
+        for (iso2, mark) in NATIONALITY_MARKS {
+            let example = example_registration(iso2).unwrap_or_else(|| panic!("no example for {}", iso2));
+            assert!(example.starts_with(mark), "{} does not start with {}", example, mark);
+            let holders: Vec<_> = parser
+                .candidates(&example, false)
+                .into_iter()
+                .filter_map(|candidate| match candidate.entity {
+                    EntityResult::Country { iso2, .. } => Some(iso2),
+                    EntityResult::Organization { .. } => None,
+                })
+                .collect();
+            assert!(holders.iter().any(|code| code == iso2), "{} matches {:?}", example, holders);
+        }
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(example_registration("de").unwrap(), "D-ABC");
+        assert_eq!(example_registration("JP").unwrap(), "JA1234");
+        assert_eq!(example_registration("AT").unwrap(), "OE-ABC");
+        assert_eq!(example_registration("BM").unwrap(), "VP-BAB");
+        assert_eq!(example_suffix("HAA", "HZZ"), "HBC");
+        assert_eq!(example_suffix("0001", "9999"), "1234");
+        assert_eq!(example_suffix("1A", "9999Z"), "1234E");
+        assert_eq!(example_suffix("81", "8999"), "8234");
+        assert_eq!(example_suffix("A**", "A**"), "ABC");
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(tokens("1A"), ["1", "A"]);
+        assert_eq!(tokens("0001"), ["0001"]);
+        assert_eq!(tokens("1***"), ["1", "*", "*", "*"]);
+
+        let runs = block_runs("1A-9999Z").unwrap();
+        assert_eq!(runs[0], Run::Number { low: 1, high: 9999, width: 0 });
+        assert_eq!(runs[1].len(), 26);
+        assert_eq!(block_runs("0001-9999").unwrap(), [Run::Number { low: 1, high: 9999, width: 4 }]);
+        assert_eq!(block_runs("AAA-KZZ").unwrap()[0].len(), 11);
+        assert_eq!(block_runs("A**").unwrap()[2].len(), 36);
+        assert_eq!(block_runs("AAA-9999"), None);
+        assert_eq!(block_runs("ZZ-AA"), None);
+
+        let suffixes: Vec<String> = Suffixes::new(block_runs("08-10").unwrap()).collect();
+        assert_eq!(suffixes, ["08", "09", "10"]);
+        assert_eq!(Suffixes::new(block_runs("1A-2B").unwrap()).collect::<Vec<_>>(), ["1A", "1B", "2A", "2B"]);
+    }
+
+    #[test]
+    fn suffixes_fit_blocks() {
+        let blocks = |blocks: &[&str]| blocks.iter().map(|block| block.to_string()).collect::<Vec<_>>();
+        let us = blocks(&["1-99999", "1A-9999Z", "1AA-999ZZ"]);
+        assert!(suffix_fits(&us, "8437D"));
+        assert!(suffix_fits(&us, "1"));
+        assert!(!suffix_fits(&us, "0123"));
+        assert!(!suffix_fits(&us, "123ABC"));
+        assert!(suffix_fits(&blocks(&["AAA-KZZ"]), "BHA"));
+        assert!(!suffix_fits(&blocks(&["AAA-KZZ"]), "LHA"));
+        assert!(suffix_fits(&blocks(&["0001-9999"]), "0042"));
+        assert!(!suffix_fits(&blocks(&["0001-9999"]), "42"));
+        assert!(suffix_fits(&blocks(&["***"]), "A12"));
+        assert!(!suffix_fits(&blocks(&["AAAA-ZZZZ"]), "L350"));
+    }
+
+    #[test]
+    fn enumerations() {
+        let registrations: Vec<String> = enumerate_registrations("AT").collect();
+        assert_eq!(registrations.len(), 11 * 26 * 26);
+        assert_eq!(registrations.last().unwrap(), "OE-KZZ");
+
+        // N1 to N99999, then N1A to N9999Z and N1AA to N999ZZ
+        let us = enumerate_registrations("us");
+        assert_eq!(us.count(), 99_999 + 9_999 * 26 + 999 * 26 * 26);
+
+        let japan: Vec<String> = enumerate_registrations("JP").take(2).collect();
+        assert_eq!(japan, ["JA0001", "JA0002"]);
+        assert_eq!(enumerate_registrations("BM").next().unwrap(), "VP-BAA");
+
+        let parser = Parser::new();
+        for registration in enumerate_registrations("CA").step_by(997) {
+            match parser.parse(&registration, true, false) {
+                Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "CA", "{}", registration),
+                other => panic!("{} parsed as {:?}", registration, other),
+            }
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_registrations() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let parser = Parser::new();
+        for iso2 in ["US", "JP", "DE", "CA", "LU", "PL"] {
+            for _ in 0..200 {
+                let registration = random_registration(iso2, &mut rng).unwrap();
+                let holders: Vec<_> = parser
+                    .candidates(&registration, false)
+                    .into_iter()
+                    .filter_map(|candidate| match candidate.entity {
+                        EntityResult::Country { iso2, .. } => Some(iso2),
+                        EntityResult::Organization { .. } => None,
+                    })
+                    .collect();
+                assert!(holders.iter().any(|code| code == iso2), "{} matches {:?}", registration, holders);
+            }
+        }
+        assert_eq!(random_registration("XX", &mut rng), None);
+
+        // Uniform over all blocks: nine in ten N-numbers end with a letter
+        let lettered = (0..1000)
+            .filter(|_| random_registration("US", &mut rng).unwrap().ends_with(|c: char| c.is_ascii_uppercase()))
+            .count();
+        assert!(lettered > 850, "{}", lettered);
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(format_template("DE").unwrap(), "D-AAA…D-ZZZ");
+        assert_eq!(format_template("CA").unwrap(), "C-FAAA…C-FZZZ, C-GAAA…C-GZZZ, C-AAA…C-ZZZ");
+        assert_eq!(format_template("BM").unwrap(), "VP-BAA…VP-BZZ");
+        assert_eq!(format_template("JP").unwrap(), "JA0001…JA9999, JA001A…JA999Z, JA01AA…JA99ZZ");
+        // No general series: all of Luxembourg's blocks
+        assert!(format_template("LU").unwrap().starts_with("LX-BAA…LX-BZZ, LX-CAA…LX-CZZ"));
+    }
+
+    #[test]
+    fn template_bounds_parse_as_their_country() {
+        let parser = Parser::new();
+        for iso2 in ["NL", "US", "DE", "GB", "BR", "AU"] {
+            let template = format_template(iso2).unwrap();
+            for registration in template.split(", ").flat_map(|block| block.split('…')) {
+                match parser.parse(registration, false, false) {
+                    Some(EntityResult::Country { iso2: code, .. }) => assert_eq!(code, iso2, "{}", registration),
+                    other => panic!("{} parsed as {:?}", registration, other),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Military and Government Callsigns
+//!
+//! Maps well-known military callsign prefixes, both ICAO designators ("RCH",
+//! "GAF") and tactical words ("ASCOT", "DUKE"), to the operator, branch and
+//! country. The ITU registration data cannot explain these callsigns. The table
+//! is generated by `build.rs` from `data/military-callsigns.csv`.
+//!
+//! Also recognizes military serial numbers, the military counterpart of civil
+//! registrations (see `parse_military_serial`).
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+/// Service branch operating under a callsign prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Branch {
+    AirForce,
+    Army,
+    Navy,
+    MarineCorps,
+    CoastGuard,
+    /// Operated jointly by several states, e.g. NATO
+    Multinational,
+}
+
+/// A military callsign prefix and its operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MilitaryPrefix {
+    /// The prefix as transmitted, e.g. `"RCH"`
+    pub prefix: &'static str,
+    /// Operating service or unit
+    pub operator: &'static str,
+    /// Service branch
+    pub branch: Branch,
+    /// ISO 3166-1 alpha-2 code of the operating state; `None` for multinational
+    /// operators
+    pub iso2: Option<&'static str>,
+}
+
+// Prefixes generated by `build.rs` from `data/military-callsigns.csv`, longest
+// first.
+include!(concat!(env!("OUT_DIR"), "/military_callsigns.rs"));
+
+/// A military callsign split into its prefix and mission number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MilitaryCallsign {
+    /// The matched prefix
+    pub prefix: &'static MilitaryPrefix,
+    /// The part after the prefix, e.g. `"4471"` for `"RCH4471"`
+    pub number: String,
+}
+
+/// Recognize a military callsign such as "RCH4471" or "ASCOT123".
+///
+/// The callsign must be a known prefix followed by one to five letters or digits,
+/// the first of which is a digit. Case and surrounding whitespace are ignored.
+///
+/// # Examples
+/// ```
+/// use flydent::military::{parse_military_callsign, Branch};
+///
+/// let reach = parse_military_callsign("RCH4471").unwrap();
+/// assert_eq!(reach.prefix.iso2, Some("US"));
+/// assert_eq!(reach.prefix.branch, Branch::AirForce);
+/// assert_eq!(reach.number, "4471");
+///
+/// assert_eq!(parse_military_callsign("ASCOT123").unwrap().prefix.iso2, Some("GB"));
+/// assert!(parse_military_callsign("BAW2276").is_none());
+/// ```
+pub fn parse_military_callsign(callsign: &str) -> Option<MilitaryCallsign> {
+    let callsign = callsign.trim().to_ascii_uppercase();
+    MILITARY_PREFIXES.iter().find_map(|prefix| {
+        let number = callsign.strip_prefix(prefix.prefix)?;
+        let valid = (1..=5).contains(&number.len())
+            && number.starts_with(|c: char| c.is_ascii_digit())
+            && number.chars().all(|c| c.is_ascii_alphanumeric());
+        valid.then(|| MilitaryCallsign {
+            prefix,
+            number: number.to_string(),
+        })
+    })
+}
+
+/// Look up a military callsign prefix exactly (case-insensitive).
+pub fn lookup_prefix(prefix: &str) -> Option<&'static MilitaryPrefix> {
+    let prefix = prefix.trim();
+    MILITARY_PREFIXES.iter().find(|entry| entry.prefix.eq_ignore_ascii_case(prefix))
+}
+
+/// All military callsign prefixes in the table, longest first.
+pub fn military_prefixes() -> &'static [MilitaryPrefix] {
+    MILITARY_PREFIXES
+}
+
+/// A recognized military serial number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MilitarySerial {
+    /// ISO 3166-1 alpha-2 code of the operating state
+    pub iso2: &'static str,
+    /// Name of the serial scheme, e.g. `"USAF"`
+    pub scheme: &'static str,
+    /// The serial in its canonical form, e.g. `"86-0001"`
+    pub serial: String,
+}
+
+/// Recognize a military serial number and the state operating the aircraft.
+///
+/// Supported formats:
+/// - USAF fiscal-year serials, `86-0001` (two-digit fiscal year, hyphen, four or
+///   five digits), and the shortened form `AF86001` used in flight plans and
+///   ADS-B. The short form drops the leading digits of the number, so it
+///   canonicalizes to `86-001`.
+/// - UK military serials, two letters and three digits (`ZZ999`)
+/// - German Bundeswehr serials, `98+01`
+///
+/// Case and surrounding whitespace are ignored.
+///
+/// # Examples
+/// ```
+/// use flydent::military::parse_military_serial;
+///
+/// let serial = parse_military_serial("86-0001").unwrap();
+/// assert_eq!((serial.iso2, serial.scheme), ("US", "USAF"));
+///
+/// assert_eq!(parse_military_serial("zz999").unwrap().iso2, "GB");
+/// assert_eq!(parse_military_serial("98+01").unwrap().iso2, "DE");
+/// assert!(parse_military_serial("N8437D").is_none());
+/// ```
+pub fn parse_military_serial(serial: &str) -> Option<MilitarySerial> {
+    let serial = serial.trim().to_ascii_uppercase();
+    let digits = |s: &str, len: core::ops::RangeInclusive<usize>| {
+        len.contains(&s.len()) && s.chars().all(|c| c.is_ascii_digit())
+    };
+
+    let (iso2, scheme, canonical) = if let Some((year, number)) = serial.split_once('-') {
+        (digits(year, 2..=2) && digits(number, 4..=5)).then(|| ("US", "USAF", serial.clone()))?
+    } else if let Some(rest) = serial.strip_prefix("AF").filter(|rest| digits(rest, 5..=7)) {
+        ("US", "USAF", format!("{}-{}", &rest[..2], &rest[2..]))
+    } else if let Some((first, second)) = serial.split_once('+') {
+        (digits(first, 2..=2) && digits(second, 2..=2)).then(|| ("DE", "Bundeswehr", serial.clone()))?
+    } else {
+        let (letters, number) = serial.split_at_checked(2)?;
+        (letters.chars().all(|c| c.is_ascii_uppercase()) && digits(number, 3..=3))
+            .then(|| ("GB", "UK military", serial.clone()))?
+    };
+
+    Some(MilitarySerial {
+        iso2,
+        scheme,
+        serial: canonical,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_first() {
+        assert!(MILITARY_PREFIXES.windows(2).all(|pair| pair[0].prefix.len() >= pair[1].prefix.len()));
+        for entry in military_prefixes() {
+            assert_eq!(lookup_prefix(entry.prefix), Some(entry));
+            assert_eq!(entry.iso2.is_none(), entry.branch == Branch::Multinational, "{}", entry.prefix);
+        }
+    }
+
+    #[test]
+    fn military_callsigns() {
+        let cases = [
+            ("RCH4471", "RCH", "4471"),
+            ("rch4a3", "RCH", "4A3"),
+            ("ASCOT123", "ASCOT", "123"),
+            ("DUKE01", "DUKE", "01"),
+            ("GAF686", "GAF", "686"),
+            (" NATO01 ", "NATO", "01"),
+            ("SAM44", "SAM", "44"),
+        ];
+        for (callsign, prefix, number) in cases {
+            let parsed = parse_military_callsign(callsign).unwrap();
+            assert_eq!(parsed.prefix.prefix, prefix, "{}", callsign);
+            assert_eq!(parsed.number, number, "{}", callsign);
+        }
+        assert_eq!(parse_military_callsign("NATO01").unwrap().prefix.iso2, None);
+
+        for callsign in ["", "RCH", "RCHA12", "RCH123456", "ASCOTX", "N8437D", "RCH-12"] {
+            assert!(parse_military_callsign(callsign).is_none(), "{}", callsign);
+        }
+    }
+
+    #[test]
+    fn military_serials() {
+        let cases = [
+            ("86-0001", "US", "86-0001"),
+            ("05-5140", "US", "05-5140"),
+            ("92-03292", "US", "92-03292"),
+            ("AF86001", "US", "86-001"),
+            ("af05140", "US", "05-140"),
+            ("ZZ999", "GB", "ZZ999"),
+            (" zk001 ", "GB", "ZK001"),
+            ("98+01", "DE", "98+01"),
+        ];
+        for (input, iso2, serial) in cases {
+            let parsed = parse_military_serial(input).unwrap_or_else(|| panic!("{}", input));
+            assert_eq!(parsed.iso2, iso2, "{}", input);
+            assert_eq!(parsed.serial, serial, "{}", input);
+        }
+
+        for input in ["", "8-0001", "86-001", "86-000001", "AF8600", "ZZ99", "Z9999", "98+1", "D-AIMA", "N8437D", "ÄB123"] {
+            assert!(parse_military_serial(input).is_none(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Maritime Mobile Service Identities (MMSI)
+//!
+//! Attributes nine-digit MMSIs, as broadcast by AIS, to a country through the
+//! ITU Maritime Identification Digits (MID) and classifies the station type from
+//! the leading digits. The MID table is generated by `build.rs` from
+//! `data/mmsi-mid.csv`.
+
+use alloc::format;
+
+/// A Maritime Identification Digits allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mid {
+    /// The three-digit MID, e.g. `244`
+    pub mid: u16,
+    /// ISO 3166-1 alpha-2 code of the country or territory
+    pub iso2: &'static str,
+    /// Name of the country or territory, e.g. `"Netherlands"` or `"Azores"`
+    pub name: &'static str,
+}
+
+// MIDs generated by `build.rs` from `data/mmsi-mid.csv`, sorted by MID.
+include!(concat!(env!("OUT_DIR"), "/mmsi_mids.rs"));
+
+/// Kind of station an MMSI identifies, from its leading digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StationType {
+    /// `MIDXXXXXX`: individual ship
+    Ship,
+    /// `0MIDXXXXX`: group of ships
+    GroupOfShips,
+    /// `00MIDXXXX`: coast station
+    CoastStation,
+    /// `111MIDXXX`: search and rescue aircraft
+    SarAircraft,
+    /// `8MIDXXXXX`: handheld VHF transceiver
+    HandheldVhf,
+    /// `98MIDXXXX`: craft associated with a parent ship, e.g. a tender
+    AssociatedCraft,
+    /// `99MIDXXXX`: aid to navigation
+    AidToNavigation,
+    /// `970XXXXXX`: AIS search and rescue transmitter
+    Sart,
+    /// `972XXXXXX`: man-overboard device
+    ManOverboard,
+    /// `974XXXXXX`: AIS EPIRB
+    Epirb,
+}
+
+/// An MMSI broken down into station type and country.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mmsi {
+    /// What kind of station the MMSI identifies
+    pub station: StationType,
+    /// The MID allocation, if the MMSI embeds a known MID. Distress devices
+    /// (SART, MOB, EPIRB) carry none.
+    pub mid: Option<&'static Mid>,
+}
+
+/// Look up a MID allocation.
+pub fn lookup_mid(mid: u16) -> Option<&'static Mid> {
+    MIDS.binary_search_by_key(&mid, |entry| entry.mid).ok().map(|idx| &MIDS[idx])
+}
+
+/// Classify an MMSI by its leading digits and resolve its MID.
+///
+/// Returns `None` for values with more than nine digits and for leading digits
+/// that no station type uses.
+///
+/// # Examples
+/// ```
+/// use flydent::mmsi::{classify, StationType};
+///
+/// let ship = classify(244123456).unwrap();
+/// assert_eq!(ship.station, StationType::Ship);
+/// assert_eq!(ship.mid.unwrap().name, "Netherlands");
+///
+/// // Coast station 002320001 (United Kingdom)
+/// let coast = classify(2320001).unwrap();
+/// assert_eq!(coast.station, StationType::CoastStation);
+/// assert_eq!(coast.mid.unwrap().iso2, "GB");
+///
+/// assert_eq!(classify(970123456).unwrap().station, StationType::Sart);
+/// ```
+pub fn classify(mmsi: u32) -> Option<Mmsi> {
+    if mmsi > 999_999_999 {
+        return None;
+    }
+    let digits = format!("{:09}", mmsi);
+    let mid_at = |start: usize| digits[start..start + 3].parse().ok().and_then(lookup_mid);
+
+    let (station, mid) = match digits.as_bytes() {
+        [b'0', b'0', ..] => (StationType::CoastStation, mid_at(2)),
+        [b'0', ..] => (StationType::GroupOfShips, mid_at(1)),
+        [b'1', b'1', b'1', ..] => (StationType::SarAircraft, mid_at(3)),
+        [b'2'..=b'7', ..] => (StationType::Ship, mid_at(0)),
+        [b'8', ..] => (StationType::HandheldVhf, mid_at(1)),
+        [b'9', b'7', b'0', ..] => (StationType::Sart, None),
+        [b'9', b'7', b'2', ..] => (StationType::ManOverboard, None),
+        [b'9', b'7', b'4', ..] => (StationType::Epirb, None),
+        [b'9', b'8', ..] => (StationType::AssociatedCraft, mid_at(2)),
+        [b'9', b'9', ..] => (StationType::AidToNavigation, mid_at(2)),
+        _ => return None,
+    };
+    Some(Mmsi { station, mid })
+}
+
+/// ISO2 code of the country an MMSI is registered in, from its MID.
+///
+/// # Examples
+/// ```
+/// use flydent::mmsi::country;
+///
+/// assert_eq!(country(244123456), Some("NL"));
+/// assert_eq!(country(366999999), Some("US"));
+/// assert_eq!(country(970123456), None);
+/// ```
+pub fn country(mmsi: u32) -> Option<&'static str> {
+    classify(mmsi)?.mid.map(|mid| mid.iso2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mids_are_sorted() {
+        assert!(MIDS.windows(2).all(|pair| pair[0].mid < pair[1].mid));
+        for entry in MIDS {
+            assert_eq!(lookup_mid(entry.mid), Some(entry));
+        }
+        assert_eq!(lookup_mid(200), None);
+    }
+
+    #[test]
+    fn station_types() {
+        let cases = [
+            (211234567, StationType::Ship, Some("DE")),
+            (24412345, StationType::GroupOfShips, Some("NL")),
+            (3669991, StationType::CoastStation, Some("US")),
+            (111232001, StationType::SarAircraft, Some("GB")),
+            (822712345, StationType::HandheldVhf, Some("FR")),
+            (972123456, StationType::ManOverboard, None),
+            (974123456, StationType::Epirb, None),
+            (982351234, StationType::AssociatedCraft, Some("GB")),
+            (992351234, StationType::AidToNavigation, Some("GB")),
+        ];
+        for (mmsi, station, iso2) in cases {
+            let classified = classify(mmsi).unwrap_or_else(|| panic!("{:09}", mmsi));
+            assert_eq!(classified.station, station, "{:09}", mmsi);
+            assert_eq!(classified.mid.map(|mid| mid.iso2), iso2, "{:09}", mmsi);
+        }
+    }
+
+    #[test]
+    fn unknown_and_invalid() {
+        assert_eq!(classify(1_000_000_000), None);
+        assert_eq!(classify(123456789), None);
+        assert_eq!(classify(975123456), None);
+        // Valid ship prefix, unallocated MID
+        assert_eq!(classify(200123456).unwrap().mid, None);
+        assert_eq!(country(200123456), None);
+    }
+}
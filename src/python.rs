@@ -0,0 +1,118 @@
+//! Python Bindings
+//!
+//! A PyO3 extension module named `flydenity` that mirrors the Python package
+//! this crate was ported from, so existing code keeps working unchanged:
+//!
+//! ```python
+//! from flydenity import Parser
+//!
+//! parser = Parser()
+//! parser.parse("PH-BHA")                    # {'nation': 'Netherlands', 'description': 'general', 'iso2': 'NL', 'iso3': 'NLD'}
+//! parser.parse("4Y-ABC")                    # {'name': 'International Civil Aviation Organization', 'description': 'general'}
+//! parser.parse("AB8E4F", icao24bit=True)    # {'nation': 'United States', ...}
+//! parser.parse("ZZZZZZZ")                   # None
+//! ```
+//!
+//! Enabled with the `python` feature; `maturin build --release` builds the
+//! wheel using `pyproject.toml`.
+
+use crate::EntityResult;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Python `flydenity.Parser`.
+#[pyclass(name = "Parser", module = "flydenity")]
+#[derive(Default)]
+pub struct PyParser {
+    parser: crate::Parser,
+}
+
+#[pymethods]
+impl PyParser {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Country or organization of a callsign, or of a hex address with
+    /// `icao24bit`, as a dict; `None` if it cannot be identified.
+    #[pyo3(signature = (string, strict = false, icao24bit = false))]
+    fn parse<'py>(
+        &self,
+        py: Python<'py>,
+        string: &str,
+        strict: bool,
+        icao24bit: bool,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(entity) = self.parser.parse(string, strict, icao24bit) else {
+            return Ok(None);
+        };
+        // Same keys as flydenity, which has no canonical callsign
+        let dict = PyDict::new(py);
+        match entity {
+            EntityResult::Country { nation, description, iso2, iso3, .. } => {
+                dict.set_item("nation", nation)?;
+                dict.set_item("description", description)?;
+                dict.set_item("iso2", iso2)?;
+                dict.set_item("iso3", iso3)?;
+            }
+            EntityResult::Organization { name, description, .. } => {
+                dict.set_item("name", name)?;
+                dict.set_item("description", description)?;
+            }
+        }
+        Ok(Some(dict))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "flydenity")]
+fn flydenity_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyParser>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+
+    fn parse(string: &str, strict: bool, icao24bit: bool) -> Option<Vec<(String, String)>> {
+        Python::initialize();
+        Python::attach(|py| {
+            let dict = PyParser::new().parse(py, string, strict, icao24bit).unwrap()?;
+            Some(dict.iter().map(|(key, value)| (key.extract().unwrap(), value.extract().unwrap())).collect())
+        })
+    }
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn flydenity_shapes() {
+        assert_eq!(
+            parse("PH-BHA", false, false).unwrap(),
+            pairs(&[("nation", "Netherlands"), ("description", "general"), ("iso2", "NL"), ("iso3", "NLD")])
+        );
+        assert_eq!(
+            parse("4Y-ABC", false, false).unwrap(),
+            pairs(&[("name", "International Civil Aviation Organization"), ("description", "general")])
+        );
+        assert_eq!(parse("AB8E4F", true, true).unwrap()[2], ("iso2".to_string(), "US".to_string()));
+        assert_eq!(parse("ZZZZZZZ", false, false), None);
+    }
+
+    #[test]
+    fn module_exports_parser() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "flydenity").unwrap();
+            flydenity_module(&module).unwrap();
+            let parser = module.getattr("Parser").unwrap().call0().unwrap();
+            let kwargs = [("icao24bit", true)].into_py_dict(py).unwrap();
+            let result = parser.call_method("parse", ("700123",), Some(&kwargs)).unwrap();
+            assert_eq!(result.get_item("iso2").unwrap().extract::<String>().unwrap(), "AF");
+        });
+    }
+}
@@ -0,0 +1,160 @@
+//! readsb and dump1090 `aircraft.json`
+//!
+//! Annotates the `aircraft.json` that readsb, dump1090-fa and their forks write
+//! every second, so receiver web interfaces can show where each aircraft is
+//! registered without a database of their own. Enabled with the `readsb`
+//! feature; `flydent aircraft-json` applies it to a file.
+//!
+//! Each aircraft with an ICAO address gets a `flydent` member; the document is
+//! otherwise left untouched, including readsb's own `r` registration:
+//!
+//! ```json
+//! { "hex": "3c65a1", "flight": "DLH4AB  ",
+//!   "flydent": { "iso2": "DE", "country": "Germany", "flag": "🇩🇪", "registration": "D-AIMA" } }
+//! ```
+//!
+//! Non-ICAO addresses, which readsb prefixes with `~`, are not annotated.
+
+use crate::country;
+use crate::icao::{self, Icao24};
+use crate::registration;
+use serde_json::{json, Value};
+
+/// What flydent derives from an aircraft's ICAO address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// ISO2 code of the state the address is allocated to
+    pub iso2: Option<&'static str>,
+    /// Name of that state, or of the ICAO special-use block
+    pub country: &'static str,
+    /// Flag emoji of the state
+    pub flag: Option<String>,
+    /// Registration, for countries with an algorithmic mapping
+    pub registration: Option<String>,
+}
+
+impl Annotation {
+    fn to_json(&self) -> Value {
+        json!({
+            "iso2": self.iso2,
+            "country": self.country,
+            "flag": self.flag,
+            "registration": self.registration,
+        })
+    }
+}
+
+/// Annotation of an aircraft from its `hex` member, or `None` for non-ICAO and
+/// unallocated addresses.
+///
+/// # Examples
+/// ```
+/// use flydent::readsb::annotate;
+///
+/// let annotation = annotate("ab8e4f").unwrap();
+/// assert_eq!(annotation.iso2, Some("US"));
+/// assert_eq!(annotation.flag.as_deref(), Some("🇺🇸"));
+/// assert_eq!(annotation.registration.as_deref(), Some("N8437D"));
+/// assert_eq!(annotate("~3c65a1"), None);
+/// ```
+pub fn annotate(hex: &str) -> Option<Annotation> {
+    let icao = Icao24::parse_hex(hex).ok()?;
+    let allocation = icao::lookup(icao.value())?;
+    let country = allocation.iso2.and_then(country::lookup);
+    Some(Annotation {
+        iso2: allocation.iso2,
+        country: country.map_or(allocation.name, |country| country.name),
+        flag: country.map(country::Country::flag),
+        registration: registration::icao_to_registration(icao.to_bytes()).ok(),
+    })
+}
+
+/// Add a `flydent` member to every aircraft of a parsed `aircraft.json`,
+/// replacing any from an earlier pass, and return how many were annotated.
+pub fn enrich(document: &mut Value) -> usize {
+    let Some(aircraft) = document.get_mut("aircraft").and_then(Value::as_array_mut) else {
+        return 0;
+    };
+    let mut annotated = 0;
+    for plane in aircraft.iter_mut().filter_map(Value::as_object_mut) {
+        let annotation = plane.get("hex").and_then(Value::as_str).and_then(annotate);
+        match annotation {
+            Some(annotation) => {
+                plane.insert("flydent".to_string(), annotation.to_json());
+                annotated += 1;
+            }
+            None => {
+                plane.remove("flydent");
+            }
+        }
+    }
+    annotated
+}
+
+/// Like `enrich`, but on the text of an `aircraft.json`.
+///
+/// # Examples
+/// ```
+/// use flydent::readsb::enrich_str;
+///
+/// let enriched = enrich_str(r#"{"now": 1700000000.0, "aircraft": [{"hex": "3c65a1"}]}"#).unwrap();
+/// assert!(enriched.contains(r#""registration":"D-AIMA""#));
+/// ```
+pub fn enrich_str(json: &str) -> Result<String, serde_json::Error> {
+    let mut document: Value = serde_json::from_str(json)?;
+    enrich(&mut document);
+    serde_json::to_string(&document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations() {
+        let germany = annotate("3C65A1").unwrap();
+        assert_eq!(germany.country, "Germany");
+        assert_eq!(germany.registration.as_deref(), Some("D-AIMA"));
+
+        let france = annotate("3944ef").unwrap();
+        assert_eq!(france.iso2, Some("FR"));
+        assert_eq!(france.registration, None);
+
+        let special = annotate("f00000").unwrap();
+        assert_eq!(special.iso2, None);
+        assert_eq!(special.flag, None);
+
+        assert_eq!(annotate("~abcdef"), None);
+        assert_eq!(annotate(""), None);
+    }
+
+    #[test]
+    fn enrich_document() {
+        let mut document = json!({
+            "now": 1700000000.0,
+            "messages": 42,
+            "aircraft": [
+                { "hex": "ab8e4f", "flight": "UAL1    ", "r": "N8437D" },
+                { "hex": "~123456", "type": "tisb_other" },
+                { "hex": "3c65a1", "flydent": { "iso2": "XX" } },
+                { "flight": "NOHEX" },
+            ],
+        });
+        assert_eq!(enrich(&mut document), 2);
+
+        let aircraft = document["aircraft"].as_array().unwrap();
+        assert_eq!(aircraft[0]["flydent"]["iso2"], "US");
+        assert_eq!(aircraft[0]["flydent"]["registration"], "N8437D");
+        assert_eq!(aircraft[0]["r"], "N8437D");
+        assert!(aircraft[1].get("flydent").is_none());
+        assert_eq!(aircraft[2]["flydent"]["iso2"], "DE");
+        assert!(aircraft[3].get("flydent").is_none());
+        assert_eq!(document["messages"], 42);
+    }
+
+    #[test]
+    fn enrich_without_aircraft() {
+        assert_eq!(enrich(&mut json!({ "now": 0 })), 0);
+        assert!(enrich_str("not json").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! ICAO Air Navigation Regions
+//!
+//! Classifies states and ICAO 24-bit addresses into the ICAO air navigation
+//! regions (AFI, APAC, EUR/NAT, MID, NACC, SAM), following the accreditation of
+//! states to the ICAO regional offices. The state table is generated by
+//! `build.rs` from `data/icao-regions.csv`.
+
+use alloc::vec::Vec;
+use core::fmt;
+use crate::icao;
+
+/// An ICAO air navigation region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum Region {
+    /// Africa-Indian Ocean (Dakar and Nairobi offices)
+    Afi,
+    /// Asia and Pacific (Bangkok office)
+    Apac,
+    /// European and North Atlantic (Paris office)
+    EurNat,
+    /// Middle East (Cairo office)
+    Mid,
+    /// North American, Central American and Caribbean (Mexico City office)
+    Nacc,
+    /// South American (Lima office)
+    Sam,
+}
+
+impl Region {
+    /// All regions, in code order.
+    pub const ALL: [Region; 6] = [Region::Afi, Region::Apac, Region::EurNat, Region::Mid, Region::Nacc, Region::Sam];
+
+    /// The ICAO code, e.g. `"EUR/NAT"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Region::Afi => "AFI",
+            Region::Apac => "APAC",
+            Region::EurNat => "EUR/NAT",
+            Region::Mid => "MID",
+            Region::Nacc => "NACC",
+            Region::Sam => "SAM",
+        }
+    }
+
+    /// The region's full name, e.g. `"European and North Atlantic"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::Afi => "Africa-Indian Ocean",
+            Region::Apac => "Asia and Pacific",
+            Region::EurNat => "European and North Atlantic",
+            Region::Mid => "Middle East",
+            Region::Nacc => "North American, Central American and Caribbean",
+            Region::Sam => "South American",
+        }
+    }
+
+    /// Region with the given ICAO code, case-insensitively.
+    pub fn from_code(code: &str) -> Option<Region> {
+        let code = code.trim();
+        Region::ALL.into_iter().find(|region| region.code().eq_ignore_ascii_case(code))
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+// State regions generated by `build.rs` from `data/icao-regions.csv`, sorted by
+// ISO2 code.
+include!(concat!(env!("OUT_DIR"), "/regions.rs"));
+
+/// Region of a state, by ISO 3166-1 alpha-2 code.
+///
+/// Covers ICAO contracting states plus Hong Kong, Macao and Taiwan; other
+/// territories return `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::region::{region_of_country, Region};
+///
+/// assert_eq!(region_of_country("NL"), Some(Region::EurNat));
+/// assert_eq!(region_of_country("br"), Some(Region::Sam));
+/// assert_eq!(region_of_country("AQ"), None);
+/// ```
+pub fn region_of_country(iso2: &str) -> Option<Region> {
+    let iso2 = iso2.trim().to_ascii_uppercase();
+    STATE_REGIONS
+        .binary_search_by(|(code, _)| (*code).cmp(iso2.as_str()))
+        .ok()
+        .map(|index| STATE_REGIONS[index].1)
+}
+
+/// Region of the state an ICAO 24-bit address is allocated to.
+///
+/// Special-use blocks and unallocated addresses return `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::region::{region_of_address, Region};
+///
+/// assert_eq!(region_of_address(0x484000), Some(Region::EurNat)); // Netherlands
+/// assert_eq!(region_of_address(0x7C0000), Some(Region::Apac)); // Australia
+/// assert_eq!(region_of_address(0xA00001), Some(Region::Nacc)); // United States
+/// ```
+pub fn region_of_address(address: u32) -> Option<Region> {
+    region_of_country(icao::lookup(address)?.iso2?)
+}
+
+/// Region of every address in `addresses`, in order.
+pub fn classify_many(addresses: &[u32]) -> Vec<Option<Region>> {
+    addresses.iter().map(|&address| region_of_address(address)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_regions() {
+        assert_eq!(region_of_country("GB"), Some(Region::EurNat));
+        assert_eq!(region_of_country("MA"), Some(Region::EurNat));
+        assert_eq!(region_of_country("SA"), Some(Region::Mid));
+        assert_eq!(region_of_country("KE"), Some(Region::Afi));
+        assert_eq!(region_of_country("JP"), Some(Region::Apac));
+        assert_eq!(region_of_country("PA"), Some(Region::Sam));
+        assert_eq!(region_of_country(" us "), Some(Region::Nacc));
+        assert_eq!(region_of_country("XX"), None);
+    }
+
+    #[test]
+    fn every_allocated_state_has_a_region() {
+        for block in icao::allocations() {
+            if let Some(iso2) = block.iso2 {
+                assert!(region_of_country(iso2).is_some(), "{} has no region", iso2);
+            }
+        }
+    }
+
+    #[test]
+    fn addresses() {
+        assert_eq!(
+            classify_many(&[0x3C0000, 0x710000, 0xE40000, 0xF00000]),
+            vec![Some(Region::EurNat), Some(Region::Mid), Some(Region::Sam), None]
+        );
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for region in Region::ALL {
+            assert_eq!(Region::from_code(region.code()), Some(region));
+        }
+        assert_eq!(Region::from_code("eur/nat"), Some(Region::EurNat));
+        assert_eq!(Region::EurNat.to_string(), "EUR/NAT");
+        assert_eq!(Region::from_code("EUR"), None);
+    }
+}
@@ -1,15 +1,212 @@
-/// ICAO <-> Registration (US "N")
-///
-/// - US block: 0xA00001 ..= 0xADF7C7
-///
-/// ICAO identifiers represented as [u8; 3], big-endian.
-///
-/// This implementation is based on the algorithm from:
-/// https://github.com/guillaumemichel/icao-nnumber_converter
-/// Copyright (c) Guillaume Michel, licensed under GPLv3
+//! ICAO <-> Registration (US "N", Japan "JA", South Korea "HL", letter-stride countries)
+//!
+//! - US block: 0xA00001 ..= 0xADF7C7
+//! - Japanese block: 0x840000 ..= 0x874BCF (JA0000 ..= JA99ZZ, see `icao_u32_to_jp`)
+//! - South Korean blocks: see `KR_RANGES` (HL7200 ..= HL7799, HL8000 ..= HL8099, HL8200 ..= HL8299)
+//! - Letter-stride runs (see `STRIDE_MAPPINGS`):
+//!   - Australia: VH-
+//!   - Germany: D-A ..= D-I
+//!   - Belgium: OO-
+//!   - Denmark: OY-
+//!   - Finland: OH-
+//!   - Greece: SX-
+//!   - Portugal: CS-
+//!   - Turkey: TC-
+//!   - South Africa: ZS-
+//!   - Singapore: 9V-
+//!
+//! No deterministic address layout is known for these registries, so their
+//! addresses are not converted:
+//! - Hungary: HA-
+//! - Ireland: EI-
+//! - Iceland: TF-
+//! - Switzerland: HB-
+//!
+//! ICAO identifiers represented as [u8; 3], big-endian.
+//!
+//! This implementation is based on the algorithm from:
+//! https://github.com/guillaumemichel/icao-nnumber_converter
+//! Copyright (c) Guillaume Michel, licensed under GPLv3
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::format;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+#[cfg(feature = "alloc")]
+use crate::airlines::{self, Airline};
+#[cfg(feature = "alloc")]
+use crate::icao::Icao24;
+use once_cell::sync::Lazy;
+
+/// First address of the US allocation; N-numbers start at `US_BASE + 1` (N1).
+pub const US_BASE: u32 = 0xA00000;
+/// Last address that encodes an N-number (N99999).
+pub const US_MAX: u32 = 0xADF7C7;
+/// Last address of the US allocation. Addresses above `US_MAX` up to this value
+/// belong to the US but do not correspond to any N-number.
+pub const US_ALLOCATION_MAX: u32 = 0xAFFFFF;
+
+/// Error returned when a registration or ICAO address cannot be converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum ConversionError {
+    /// No converter handles the registration prefix or the country the address
+    /// is allocated to
+    UnsupportedCountry,
+    /// The registration is well-formed but outside the range its scheme encodes
+    OutOfRange,
+    /// The registration or hex address is malformed
+    InvalidFormat { reason: &'static str },
+    /// The US N-number is malformed
+    InvalidNNumber(NNumberError),
+    /// The address belongs to a supported country but does not encode a
+    /// registration (e.g. US addresses above N99999)
+    NotDeterministic,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::UnsupportedCountry => write!(f, "no registration converter for this country"),
+            ConversionError::OutOfRange => write!(f, "registration outside the range of its scheme"),
+            ConversionError::InvalidFormat { reason } => write!(f, "invalid registration: {}", reason),
+            ConversionError::InvalidNNumber(err) => write!(f, "invalid N-number: {}", err),
+            ConversionError::NotDeterministic => {
+                write!(f, "ICAO address does not encode a registration")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ConversionError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ConversionError::InvalidNNumber(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Why a string is not a valid US N-number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(all(feature = "defmt", target_os = "none"), derive(defmt::Format))]
+pub enum NNumberError {
+    /// The registration does not start with N
+    MissingPrefix,
+    /// Nothing follows the N
+    Empty,
+    /// More than five characters follow the N
+    TooLong,
+    /// The first character after the N is 0
+    LeadingZero,
+    /// I or O, which are never issued to avoid confusion with 1 and 0
+    ForbiddenLetter(char),
+    /// A character that is neither a digit nor a letter
+    InvalidCharacter(char),
+    /// More than two letters at the end
+    TooManyLetters,
+    /// A letter directly after the N or before a digit
+    MisplacedLetter,
+}
+
+impl fmt::Display for NNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NNumberError::MissingPrefix => write!(f, "must start with N"),
+            NNumberError::Empty => write!(f, "no characters after N"),
+            NNumberError::TooLong => write!(f, "more than five characters after N"),
+            NNumberError::LeadingZero => write!(f, "first character after N must not be 0"),
+            NNumberError::ForbiddenLetter(c) => write!(f, "letter {} is not used in N-numbers", c),
+            NNumberError::InvalidCharacter(c) => write!(f, "invalid character: {}", c),
+            NNumberError::TooManyLetters => write!(f, "more than two trailing letters"),
+            NNumberError::MisplacedLetter => write!(f, "letters may only appear at the end"),
+        }
+    }
+}
+
+impl core::error::Error for NNumberError {}
+
+impl From<NNumberError> for ConversionError {
+    fn from(err: NNumberError) -> Self {
+        ConversionError::InvalidNNumber(err)
+    }
+}
+
+fn invalid(reason: &'static str) -> ConversionError {
+    ConversionError::InvalidFormat { reason }
+}
 
-const US_BASE: u32 = 0xA00000;
-const US_MAX: u32 = 0xADF7C7;
+/// Bytes a `RegistrationBuf` holds, more than any registration the converters
+/// accept or produce.
+const REGISTRATION_CAPACITY: usize = 16;
+
+/// A registration stored inline, so that converting it needs no heap
+/// allocation. Dereferences to `&str`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegistrationBuf {
+    bytes: [u8; REGISTRATION_CAPACITY],
+    len: usize,
+}
+
+impl RegistrationBuf {
+    const fn new() -> Self {
+        RegistrationBuf { bytes: [0; REGISTRATION_CAPACITY], len: 0 }
+    }
+
+    /// The registration as a string slice.
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are ever written
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+
+    /// Append a character, or `None` if it does not fit.
+    fn push(&mut self, c: char) -> Option<()> {
+        let end = self.len + c.len_utf8();
+        c.encode_utf8(self.bytes.get_mut(self.len..end)?);
+        self.len = end;
+        Some(())
+    }
+
+    fn push_str(&mut self, s: &str) -> Option<()> {
+        s.chars().try_for_each(|c| self.push(c))
+    }
+}
+
+impl Deref for RegistrationBuf {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for RegistrationBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for RegistrationBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<&str> for RegistrationBuf {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
 
 // Charset excludes 'I' and 'O' to avoid confusion with digits
 const CHARSET: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
@@ -30,29 +227,28 @@ fn arr3_to_u32(a: [u8; 3]) -> u32 {
     ((a[0] as u32) << 16) | ((a[1] as u32) << 8) | (a[2] as u32)
 }
 
-/// Get the suffix string for a given offset (0-600)
+/// Append the suffix for a given offset (0-600)
 /// 0 -> ''
 /// 1 -> 'A'
 /// 2 -> 'AA'
 /// 3 -> 'AB'
 /// ...
 /// 600 -> 'ZZ'
-fn get_suffix(offset: u32) -> String {
+fn push_suffix(output: &mut RegistrationBuf, offset: u32) -> Option<()> {
     if offset == 0 {
-        return String::new();
+        return Some(());
     }
     let char0_idx = ((offset - 1) / 25) as usize;
     let rem = (offset - 1) % 25;
-    let char0 = CHARSET.chars().nth(char0_idx).unwrap();
+    output.push(CHARSET.chars().nth(char0_idx)?)?;
     if rem == 0 {
-        return char0.to_string();
+        return Some(());
     }
-    let char1 = CHARSET.chars().nth((rem - 1) as usize).unwrap();
-    format!("{}{}", char0, char1)
+    output.push(CHARSET.chars().nth((rem - 1) as usize)?)
 }
 
 /// Get the offset for a given suffix string
-/// Reverse of get_suffix()
+/// Reverse of push_suffix()
 fn suffix_offset(s: &str) -> Option<u32> {
     if s.is_empty() {
         return Some(0);
@@ -61,12 +257,12 @@ fn suffix_offset(s: &str) -> Option<u32> {
         return None;
     }
 
-    let chars: Vec<char> = s.chars().collect();
-    let idx0 = CHARSET.find(chars[0])?;
+    let mut chars = s.chars();
+    let idx0 = CHARSET.find(chars.next()?)?;
     let mut count = (25 * idx0 + 1) as u32;
 
-    if chars.len() == 2 {
-        let idx1 = CHARSET.find(chars[1])?;
+    if let Some(c) = chars.next() {
+        let idx1 = CHARSET.find(c)?;
         count += (idx1 + 1) as u32;
     }
 
@@ -74,152 +270,1019 @@ fn suffix_offset(s: &str) -> Option<u32> {
 }
 
 /// Convert a US N-Number to ICAO address (u32)
-fn us_n_to_icao_u32(nnumber: &str) -> Result<u32, String> {
-    let nnumber = nnumber.trim().to_ascii_uppercase();
-
-    // Must start with 'N'
-    if !nnumber.starts_with('N') {
-        return Err("Must start with N".into());
-    }
-
-    if nnumber.len() > 6 {
-        return Err("N-Number too long (max 6 chars)".into());
-    }
-
-    // Verify all characters are valid
-    for c in nnumber.chars() {
-        if !ALLCHARS.contains(c) {
-            return Err(format!("Invalid character: {}", c));
-        }
-    }
-
-    // Verify format: no letters in the middle (only at end)
-    if nnumber.len() > 3 {
-        let chars: Vec<char> = nnumber.chars().collect();
-        for i in 1..(nnumber.len() - 2) {
-            if CHARSET.contains(chars[i]) {
-                return Err("Letters can only appear as suffix".into());
-            }
-        }
-    }
+fn us_n_to_icao_u32(nnumber: &str) -> Result<u32, ConversionError> {
+    let rest = nnumber.strip_prefix('N').ok_or(NNumberError::MissingPrefix)?;
+    validate_n_number(rest)?;
 
     let mut count = 1u32; // Start at 1 (N1 = a00001)
 
-    if nnumber.len() == 1 {
-        // Just "N" = a00001
-        return Ok(US_BASE + count);
-    }
-
-    let rest = &nnumber[1..];
-    let chars: Vec<char> = rest.chars().collect();
-
-    for i in 0..chars.len() {
-        let c = chars[i];
-
+    // Validated N-numbers are ASCII, so byte positions count characters
+    for (i, c) in rest.char_indices() {
         if i == 4 {
             // Last possible character (position 5 in N-Number, position 4 in rest)
-            let idx = ALLCHARS.find(c).ok_or("Invalid character")?;
+            let idx = ALLCHARS.find(c).ok_or(NNumberError::InvalidCharacter(c))?;
             count += (idx + 1) as u32;
             break;
         } else if CHARSET.contains(c) {
             // First alphabetical character - this is the suffix
             let suffix = &rest[i..];
-            count += suffix_offset(suffix).ok_or("Invalid suffix")?;
+            count += suffix_offset(suffix).ok_or(NNumberError::TooManyLetters)?;
             break;
         } else {
             // Digit
-            let digit = c.to_digit(10).ok_or("Invalid digit")?;
+            let digit = c.to_digit(10).ok_or(NNumberError::InvalidCharacter(c))?;
             match i {
                 0 => count += (digit - 1) * BUCKET1_SIZE,
                 1 => count += digit * BUCKET2_SIZE + SUFFIX_SIZE,
                 2 => count += digit * BUCKET3_SIZE + SUFFIX_SIZE,
                 3 => count += digit * BUCKET4_SIZE + SUFFIX_SIZE,
-                _ => return Err("N-Number format error".into()),
+                _ => return Err(NNumberError::TooLong.into()),
             }
         }
     }
 
     let icao = US_BASE + count;
     if icao > US_MAX {
-        return Err("Out of US range".into());
+        return Err(ConversionError::OutOfRange);
     }
 
     Ok(icao)
 }
 
+/// Check the part of an N-number after the N: one to five characters, starting
+/// with a non-zero digit, with at most two trailing letters and no I or O.
+fn validate_n_number(rest: &str) -> Result<(), NNumberError> {
+    if rest.is_empty() {
+        return Err(NNumberError::Empty);
+    }
+    for c in rest.chars() {
+        match c {
+            'I' | 'O' => return Err(NNumberError::ForbiddenLetter(c)),
+            c if !ALLCHARS.contains(c) => return Err(NNumberError::InvalidCharacter(c)),
+            _ => {}
+        }
+    }
+    if rest.len() > 5 {
+        return Err(NNumberError::TooLong);
+    }
+
+    let digits = rest.trim_end_matches(|c: char| c.is_ascii_uppercase());
+    if digits.is_empty() || digits.contains(|c: char| c.is_ascii_uppercase()) {
+        return Err(NNumberError::MisplacedLetter);
+    }
+    if digits.starts_with('0') {
+        return Err(NNumberError::LeadingZero);
+    }
+    if rest.len() - digits.len() > 2 {
+        return Err(NNumberError::TooManyLetters);
+    }
+    Ok(())
+}
+
 /// Convert ICAO address (u32) to US N-Number
-fn icao_u32_to_us(icao: u32) -> Result<String, String> {
+fn icao_u32_to_us(icao: u32) -> Option<RegistrationBuf> {
     if !(US_BASE + 1..=US_MAX).contains(&icao) {
-        return Err("Not in US allocation".into());
+        return None;
     }
 
     let i = icao - US_BASE - 1;
-    let mut output = String::from("N");
+    let mut output = RegistrationBuf::new();
+    output.push('N')?;
 
     // Digit 1
     let dig1 = i / BUCKET1_SIZE + 1;
     let mut rem = i % BUCKET1_SIZE;
-    output.push_str(&dig1.to_string());
+    output.push(char::from_digit(dig1, 10)?)?;
 
     if rem < SUFFIX_SIZE {
-        return Ok(output + &get_suffix(rem));
+        push_suffix(&mut output, rem)?;
+        return Some(output);
     }
 
     // Digit 2
     rem -= SUFFIX_SIZE;
     let dig2 = rem / BUCKET2_SIZE;
-    rem = rem % BUCKET2_SIZE;
-    output.push_str(&dig2.to_string());
+    rem %= BUCKET2_SIZE;
+    output.push(char::from_digit(dig2, 10)?)?;
 
     if rem < SUFFIX_SIZE {
-        return Ok(output + &get_suffix(rem));
+        push_suffix(&mut output, rem)?;
+        return Some(output);
     }
 
     // Digit 3
     rem -= SUFFIX_SIZE;
     let dig3 = rem / BUCKET3_SIZE;
-    rem = rem % BUCKET3_SIZE;
-    output.push_str(&dig3.to_string());
+    rem %= BUCKET3_SIZE;
+    output.push(char::from_digit(dig3, 10)?)?;
 
     if rem < SUFFIX_SIZE {
-        return Ok(output + &get_suffix(rem));
+        push_suffix(&mut output, rem)?;
+        return Some(output);
     }
 
     // Digit 4
     rem -= SUFFIX_SIZE;
     let dig4 = rem / BUCKET4_SIZE;
-    rem = rem % BUCKET4_SIZE;
-    output.push_str(&dig4.to_string());
+    rem %= BUCKET4_SIZE;
+    output.push(char::from_digit(dig4, 10)?)?;
 
     if rem == 0 {
-        return Ok(output);
+        return Some(output);
     }
 
     // Last character
-    let last_char = ALLCHARS.chars().nth((rem - 1) as usize)
-        .ok_or("Invalid last character")?;
-    output.push(last_char);
+    output.push(ALLCHARS.chars().nth((rem - 1) as usize)?)?;
+
+    Some(output)
+}
+
+/// A run of addresses assigned in order to the three-character suffixes of one
+/// registration prefix, e.g. `D-EAAA` ..= `D-EZZZ`.
+///
+/// The address of a suffix is `start + idx1 * stride1 + idx2 * stride2 + idx3 -
+/// offset(first)`, where `idx` is the character's position in `alphabet`. A stride
+/// larger than the alphabet leaves unused addresses between suffixes. Countries
+/// with such a sequential scheme are supported by adding a row to `STRIDE_MAPPINGS`.
+struct StrideMapping {
+    /// Address of the `first` suffix
+    start: u32,
+    /// Address step between consecutive first characters
+    stride1: u32,
+    /// Address step between consecutive second characters
+    stride2: u32,
+    /// ISO 3166-1 alpha-2 code of the registering state
+    iso2: &'static str,
+    /// Registration prefix including the hyphen, e.g. `"D-E"`
+    prefix: &'static str,
+    /// Human-readable scheme name reported by the `*_detailed` conversions
+    scheme: &'static str,
+    /// Characters of the suffix, in address order
+    alphabet: &'static str,
+    /// First suffix in the run
+    first: &'static str,
+    /// Last suffix in the run (inclusive)
+    last: &'static str,
+}
+
+const STRIDE_LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// A run covering every letter suffix `AAA` ..= `ZZZ` of `prefix`. Schemes with
+/// another alphabet override it: `StrideMapping { alphabet: "...", ..stride(...) }`.
+const fn stride(
+    start: u32,
+    stride1: u32,
+    stride2: u32,
+    iso2: &'static str,
+    prefix: &'static str,
+    scheme: &'static str,
+) -> StrideMapping {
+    StrideMapping {
+        start,
+        stride1,
+        stride2,
+        iso2,
+        prefix,
+        scheme,
+        alphabet: STRIDE_LETTERS,
+        first: "AAA",
+        last: "ZZZ",
+    }
+}
+
+impl StrideMapping {
+    /// Restrict the run to the suffixes `first` ..= `last`, with `start` being the
+    /// address of `first`.
+    const fn span(self, first: &'static str, last: &'static str) -> Self {
+        StrideMapping { first, last, ..self }
+    }
+}
+
+// Layouts as used by the tar1090/dump1090 registration decoders
+const STRIDE_MAPPINGS: &[StrideMapping] = &[
+    // Australia: a 36x36 grid per leading letter, of which 26x26 is used
+    stride(0x7C0000, 36 * 36, 36, "AU", "VH-", "Australia VH-"),
+    // Germany: D-A and D-B are split into two runs with different layouts
+    stride(0x3C4421, 1024, 32, "DE", "D-A", "Germany D-A").span("AAA", "OZZ"),
+    stride(0x3C0001, 26 * 26, 26, "DE", "D-A", "Germany D-A").span("PAA", "ZZZ"),
+    stride(0x3C8421, 1024, 32, "DE", "D-B", "Germany D-B").span("AAA", "OZZ"),
+    stride(0x3C2001, 26 * 26, 26, "DE", "D-B", "Germany D-B").span("PAA", "ZZZ"),
+    stride(0x3CC000, 26 * 26, 26, "DE", "D-C", "Germany D-C"),
+    stride(0x3D04A8, 26 * 26, 26, "DE", "D-E", "Germany D-E"),
+    stride(0x3D4950, 26 * 26, 26, "DE", "D-F", "Germany D-F"),
+    stride(0x3D8DF8, 26 * 26, 26, "DE", "D-G", "Germany D-G"),
+    stride(0x3DD2A0, 26 * 26, 26, "DE", "D-H", "Germany D-H"),
+    stride(0x3E1748, 26 * 26, 26, "DE", "D-I", "Germany D-I"),
+    stride(0x448421, 1024, 32, "BE", "OO-", "Belgium OO-"),
+    stride(0x458421, 1024, 32, "DK", "OY-", "Denmark OY-"),
+    stride(0x460000, 26 * 26, 26, "FI", "OH-", "Finland OH-"),
+    stride(0x468421, 1024, 32, "GR", "SX-", "Greece SX-"),
+    stride(0x490421, 1024, 32, "PT", "CS-", "Portugal CS-"),
+    stride(0x4B8421, 1024, 32, "TR", "TC-", "Turkey TC-"),
+    stride(0x008011, 26 * 26, 26, "ZA", "ZS-", "South Africa ZS-"),
+    stride(0x768421, 1024, 32, "SG", "9V-", "Singapore 9V-"),
+];
+
+impl StrideMapping {
+    /// Position of a three-character suffix in the mapping's address layout.
+    fn suffix_offset(&self, suffix: &str) -> Option<u32> {
+        if suffix.chars().count() != 3 {
+            return None;
+        }
+
+        let mut offset = 0;
+        for (c, step) in suffix.chars().zip([self.stride1, self.stride2, 1]) {
+            offset += self.alphabet.find(c)? as u32 * step;
+        }
+        Some(offset)
+    }
+
+    /// Last address of the mapping.
+    fn end(&self) -> u32 {
+        // Both bounds are valid suffixes by construction
+        self.start + self.suffix_offset(self.last).unwrap() - self.suffix_offset(self.first).unwrap()
+    }
+
+    fn to_icao_u32(&self, suffix: &str) -> Option<u32> {
+        let first = self.suffix_offset(self.first)?;
+        let offset = self.suffix_offset(suffix)?;
+        if offset < first || offset > self.suffix_offset(self.last)? {
+            return None;
+        }
+        Some(self.start + offset - first)
+    }
+
+    fn to_registration(&self, icao: u32) -> Option<RegistrationBuf> {
+        if icao < self.start || icao > self.end() {
+            return None;
+        }
+
+        let offset = icao - self.start + self.suffix_offset(self.first)?;
+        let mut output = RegistrationBuf::new();
+        output.push_str(self.prefix)?;
+        for idx in [offset / self.stride1, offset % self.stride1 / self.stride2, offset % self.stride2] {
+            output.push(self.alphabet.chars().nth(idx as usize)?)?;
+        }
+        Some(output)
+    }
+}
+
+/// Convert a registration from a letter-stride country to ICAO address (u32).
+/// Expects a normalized registration, see `normalize_registration`.
+fn stride_to_icao_u32(reg: &str) -> Result<(u32, &'static StrideMapping), ConversionError> {
+    let mut prefix_matched = false;
+    for mapping in STRIDE_MAPPINGS {
+        if let Some(suffix) = strip_compact_prefix(reg, mapping.prefix) {
+            prefix_matched = true;
+            if let Some(icao) = mapping.to_icao_u32(suffix) {
+                return Ok((icao, mapping));
+            }
+        }
+    }
+
+    if prefix_matched {
+        Err(invalid("Registration suffix must be exactly three letters"))
+    } else {
+        Err(ConversionError::UnsupportedCountry)
+    }
+}
+
+/// Strip a registration prefix from a hyphen-free registration, ignoring the
+/// hyphens in the prefix.
+fn strip_compact_prefix<'a>(compact: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut rest = compact;
+    for c in prefix.chars().filter(|&c| c != '-') {
+        rest = rest.strip_prefix(c)?;
+    }
+    Some(rest)
+}
+
+/// South Korean numeric registrations, as (first_address, last_address, first_number)
+/// tuples. The address's low hex digits spell the registration's decimal digits,
+/// e.g. HL7611 is 0x71BA00 + (0x7611 - 0x7200) = 0x71BE11.
+const KR_RANGES: &[(u32, u32, u32)] = &[
+    (0x71BA00, 0x71BF99, 0x7200),
+    (0x71C000, 0x71C099, 0x8000),
+    (0x71C200, 0x71C299, 0x8200),
+];
+
+/// Convert a South Korean HL registration to ICAO address (u32)
+fn kr_hl_to_icao_u32(reg: &str) -> Result<u32, ConversionError> {
+    let digits = reg.strip_prefix("HL").ok_or_else(|| invalid("Must start with HL"))?;
+    let digits = digits.strip_prefix('-').unwrap_or(digits);
+
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("HL registration must have exactly four digits"));
+    }
+
+    // Read the decimal digits as hex, mirroring how they appear in the address
+    let number = u32::from_str_radix(digits, 16).map_err(|_| invalid("HL registration must have exactly four digits"))?;
+    KR_RANGES
+        .iter()
+        .find(|&&(start, end, first)| (first..=first + (end - start)).contains(&number))
+        .map(|&(start, _, first)| start + (number - first))
+        .ok_or(ConversionError::OutOfRange)
+}
+
+/// Convert ICAO address (u32) to a South Korean HL registration
+fn icao_u32_to_kr(icao: u32) -> Option<RegistrationBuf> {
+    let &(start, _, first) = KR_RANGES
+        .iter()
+        .find(|&&(start, end, _)| (start..=end).contains(&icao))?;
+    let number = icao - start + first;
 
-    Ok(output)
+    let mut output = RegistrationBuf::new();
+    output.push_str("HL")?;
+    for shift in [12, 8, 4, 0] {
+        // Addresses whose low hex digits are not all decimal have no registration
+        output.push(char::from_digit((number >> shift) & 0xF, 10)?)?;
+    }
+    Some(output)
+}
+
+/// First address of the Japanese JA sequence (JA0000).
+const JP_BASE: u32 = 0x840000;
+// Japanese marks are JA + two digits + (digit, digit-or-letter | letter, letter),
+// with letters drawn from `CHARSET`
+const JP_DIGIT1_SIZE: u32 = 22984;
+const JP_DIGIT2_SIZE: u32 = 916;
+const JP_DIGIT3_SIZE: u32 = 34; // 10 digits + 24 letters in the last position
+const JP_NUMERIC_SIZE: u32 = 10 * JP_DIGIT3_SIZE; // 340
+const JP_SIZE: u32 = 10 * JP_DIGIT1_SIZE;
+
+/// Convert a Japanese JA registration to ICAO address (u32)
+fn jp_ja_to_icao_u32(reg: &str) -> Result<u32, ConversionError> {
+    let rest = reg.strip_prefix("JA").ok_or_else(|| invalid("Must start with JA"))?;
+    let rest = rest.strip_prefix('-').unwrap_or(rest);
+
+    let mut chars = ['\0'; 4];
+    if rest.chars().count() != chars.len() {
+        return Err(invalid("JA registration must have exactly four characters after JA"));
+    }
+    for (slot, c) in chars.iter_mut().zip(rest.chars()) {
+        *slot = c;
+    }
+
+    let digit = |c: char| c.to_digit(10).ok_or_else(|| invalid("Expected a digit"));
+    let letter = |c: char| {
+        CHARSET
+            .find(c)
+            .map(|idx| idx as u32)
+            .ok_or_else(|| invalid("Invalid character"))
+    };
+
+    let mut offset = digit(chars[0])? * JP_DIGIT1_SIZE + digit(chars[1])? * JP_DIGIT2_SIZE;
+    if chars[2].is_ascii_digit() {
+        // Third is a digit, fourth a digit or letter
+        offset += digit(chars[2])? * JP_DIGIT3_SIZE;
+        offset += if chars[3].is_ascii_digit() {
+            digit(chars[3])?
+        } else {
+            10 + letter(chars[3])?
+        };
+    } else {
+        // Third and fourth are letters
+        offset += JP_NUMERIC_SIZE + letter(chars[2])? * 24 + letter(chars[3])?;
+    }
+
+    Ok(JP_BASE + offset)
+}
+
+/// Convert ICAO address (u32) to a Japanese JA registration
+///
+/// Port of the decoder used by tar1090/dump1090. Each leading digit owns a run of
+/// `JP_DIGIT1_SIZE` addresses, of which only the part for a numeric second
+/// character is used.
+fn icao_u32_to_jp(icao: u32) -> Option<RegistrationBuf> {
+    if !(JP_BASE..JP_BASE + JP_SIZE).contains(&icao) {
+        return None;
+    }
+
+    let mut offset = icao - JP_BASE;
+    let digit1 = offset / JP_DIGIT1_SIZE;
+    offset %= JP_DIGIT1_SIZE;
+    let digit2 = offset / JP_DIGIT2_SIZE;
+    if digit2 > 9 {
+        return None;
+    }
+    offset %= JP_DIGIT2_SIZE;
+
+    let mut output = RegistrationBuf::new();
+    output.push_str("JA")?;
+    output.push(char::from_digit(digit1, 10)?)?;
+    output.push(char::from_digit(digit2, 10)?)?;
+    if offset < JP_NUMERIC_SIZE {
+        output.push(char::from_digit(offset / JP_DIGIT3_SIZE, 10)?)?;
+        let last = offset % JP_DIGIT3_SIZE;
+        if last < 10 {
+            output.push(char::from_digit(last, 10)?)?;
+        } else {
+            output.push(CHARSET.chars().nth((last - 10) as usize)?)?;
+        }
+    } else {
+        offset -= JP_NUMERIC_SIZE;
+        output.push(CHARSET.chars().nth((offset / 24) as usize)?)?;
+        output.push(CHARSET.chars().nth((offset % 24) as usize)?)?;
+    }
+
+    Some(output)
+}
+
+/// Decoder responsible for a run of addresses.
+#[derive(Clone, Copy)]
+enum Decoder {
+    Us,
+    Japan,
+    Korea,
+    Stride(&'static StrideMapping),
+}
+
+/// Number of address runs with a decoder: the US and Japanese blocks, the
+/// Korean ranges and the letter-stride runs.
+const DECODER_COUNT: usize = 2 + KR_RANGES.len() + STRIDE_MAPPINGS.len();
+
+/// `(first_address, last_address, decoder)` for every scheme, ordered by address,
+/// so an address finds its decoder with one binary search.
+static DECODERS: Lazy<[(u32, u32, Decoder); DECODER_COUNT]> = Lazy::new(|| {
+    let mut decoders = [(0, 0, Decoder::Us); DECODER_COUNT];
+    decoders[0] = (US_BASE + 1, US_MAX, Decoder::Us);
+    decoders[1] = (JP_BASE, JP_BASE + JP_SIZE - 1, Decoder::Japan);
+    let runs = KR_RANGES
+        .iter()
+        .map(|&(start, end, _)| (start, end, Decoder::Korea))
+        .chain(STRIDE_MAPPINGS.iter().map(|mapping| (mapping.start, mapping.end(), Decoder::Stride(mapping))));
+    for (slot, run) in decoders[2..].iter_mut().zip(runs) {
+        *slot = run;
+    }
+    decoders.sort_unstable_by_key(|&(start, _, _)| start);
+    decoders
+});
+
+/// Decode an address with whichever scheme covers it.
+fn decode_buf(icao: u32) -> Option<Conversion<RegistrationBuf>> {
+    let idx = DECODERS.partition_point(|&(_, end, _)| end < icao);
+    let &(start, _, decoder) = DECODERS.get(idx)?;
+    if icao < start {
+        return None;
+    }
+
+    match decoder {
+        Decoder::Us => icao_u32_to_us(icao).map(|reg| Conversion::new(reg, "US", US_SCHEME)),
+        Decoder::Japan => icao_u32_to_jp(icao).map(|reg| Conversion::new(reg, "JP", JP_SCHEME)),
+        Decoder::Korea => icao_u32_to_kr(icao).map(|reg| Conversion::new(reg, "KR", KR_SCHEME)),
+        Decoder::Stride(mapping) => mapping
+            .to_registration(icao)
+            .map(|reg| Conversion::new(reg, mapping.iso2, mapping.scheme)),
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn decode_u32(icao: u32) -> Option<Conversion<String>> {
+    decode_buf(icao).map(|conversion| Conversion::new(conversion.value.to_string(), conversion.iso2, conversion.scheme))
+}
+
+/// Error for an address none of the converters decoded.
+fn unconverted_address_error(icao: u32) -> ConversionError {
+    let supported = match crate::icao::icao_u32_to_country(icao) {
+        Some(country) => {
+            ["US", "JP", "KR"].contains(&country) || STRIDE_MAPPINGS.iter().any(|mapping| mapping.iso2 == country)
+        }
+        None => false,
+    };
+    if supported {
+        ConversionError::NotDeterministic
+    } else {
+        ConversionError::UnsupportedCountry
+    }
+}
+
+/// Bring a registration into the form the converters expect: surrounding
+/// whitespace removed, uppercase, and without hyphens (Unicode dashes included),
+/// so "n-8437d" and " D-AIMA " are accepted.
+fn normalize_registration(reg: &str) -> Result<RegistrationBuf, ConversionError> {
+    let mut normalized = RegistrationBuf::new();
+    for c in reg.trim().chars().filter(|c| !matches!(c, '-' | '–' | '—' | '−')) {
+        normalized.push(c.to_ascii_uppercase()).ok_or(invalid("Registration is too long"))?;
+    }
+    Ok(normalized)
 }
 
 // === Public API ===
 
-pub fn registration_to_icao(reg: &str) -> Result<[u8; 3], String> {
+const US_SCHEME: &str = "US N-number";
+const JP_SCHEME: &str = "Japan JA";
+const KR_SCHEME: &str = "South Korea HL";
+
+pub fn registration_to_icao(reg: &str) -> Result<[u8; 3], ConversionError> {
+    registration_to_icao_detailed(reg).map(|conversion| conversion.value)
+}
+
+#[cfg(feature = "alloc")]
+pub fn icao_to_registration(icao: [u8; 3]) -> Result<String, ConversionError> {
+    icao_to_registration_detailed(icao).map(|conversion| conversion.value)
+}
+
+/// Like `icao_to_registration`, but writes the registration into a fixed-size
+/// buffer instead of allocating, for targets without a heap.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::icao_to_registration_buf;
+///
+/// let registration = icao_to_registration_buf([0x3C, 0x65, 0xA1]).unwrap();
+/// assert_eq!(registration.as_str(), "D-AIMA");
+/// assert!(icao_to_registration_buf([0xF0, 0x00, 0x00]).is_err());
+/// ```
+#[cfg(feature = "core-only")]
+pub fn icao_to_registration_buf(icao: [u8; 3]) -> Result<RegistrationBuf, ConversionError> {
+    let icao_u32 = arr3_to_u32(icao);
+    decode_buf(icao_u32)
+        .map(|conversion| conversion.value)
+        .ok_or_else(|| unconverted_address_error(icao_u32))
+}
+
+/// Like `icao_to_registration`, but takes the address as a hex string in any
+/// notation `Icao24::parse_hex` accepts, e.g. `"ab8e4f"` or `"0xAB8E4F"`.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::icao_hex_to_registration;
+///
+/// assert_eq!(icao_hex_to_registration(" ab8e4f ").unwrap(), "N8437D");
+/// assert!(icao_hex_to_registration("ab8e4").is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn icao_hex_to_registration(hex: &str) -> Result<String, ConversionError> {
+    let icao = Icao24::parse_hex(hex).map_err(|_| invalid("Not a hex ICAO address"))?;
+    icao_to_registration(icao.to_bytes())
+}
+
+/// A converted value together with the country and scheme that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversion<T> {
+    /// The converted registration or address
+    pub value: T,
+    /// ISO 3166-1 alpha-2 code of the registering state
+    pub iso2: &'static str,
+    /// Name of the scheme used, e.g. `"US N-number"` or `"Germany D-A"`
+    pub scheme: &'static str,
+}
+
+impl<T> Conversion<T> {
+    fn new(value: T, iso2: &'static str, scheme: &'static str) -> Self {
+        Conversion { value, iso2, scheme }
+    }
+}
+
+/// Like `registration_to_icao`, but also reports the country and scheme used.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::registration_to_icao_detailed;
+///
+/// let conversion = registration_to_icao_detailed("D-AIMA").unwrap();
+/// assert_eq!(conversion.value, [0x3C, 0x65, 0xA1]);
+/// assert_eq!(conversion.iso2, "DE");
+/// assert_eq!(conversion.scheme, "Germany D-A");
+/// ```
+pub fn registration_to_icao_detailed(reg: &str) -> Result<Conversion<[u8; 3]>, ConversionError> {
+    let reg = &normalize_registration(reg)?;
     if reg.starts_with('N') {
-        us_n_to_icao_u32(reg).map(u32_to_arr3)
+        us_n_to_icao_u32(reg).map(|icao| Conversion::new(u32_to_arr3(icao), "US", US_SCHEME))
+    } else if reg.starts_with("HL") {
+        kr_hl_to_icao_u32(reg).map(|icao| Conversion::new(u32_to_arr3(icao), "KR", KR_SCHEME))
+    } else if reg.starts_with("JA") {
+        jp_ja_to_icao_u32(reg).map(|icao| Conversion::new(u32_to_arr3(icao), "JP", JP_SCHEME))
     } else {
-        Err("Unsupported registration prefix (only US 'N' supported)".into())
+        stride_to_icao_u32(reg)
+            .map(|(icao, mapping)| Conversion::new(u32_to_arr3(icao), mapping.iso2, mapping.scheme))
     }
 }
 
-pub fn icao_to_registration(icao: [u8; 3]) -> Result<String, String> {
+#[cfg(feature = "alloc")]
+/// Like `icao_to_registration`, but also reports the country and scheme used.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::icao_to_registration_detailed;
+///
+/// let conversion = icao_to_registration_detailed([0xAB, 0x8E, 0x4F]).unwrap();
+/// assert_eq!(conversion.value, "N8437D");
+/// assert_eq!(conversion.iso2, "US");
+/// assert_eq!(conversion.scheme, "US N-number");
+/// ```
+pub fn icao_to_registration_detailed(icao: [u8; 3]) -> Result<Conversion<String>, ConversionError> {
+    let icao_u32 = arr3_to_u32(icao);
+    decode_u32(icao_u32).ok_or_else(|| unconverted_address_error(icao_u32))
+}
+
+#[cfg(feature = "alloc")]
+/// The most specific identity that can be derived from an ICAO address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Registration {
+    /// The address encodes this registration
+    Exact(String),
+    /// The address is allocated to this country (ISO2 code), but its registration
+    /// cannot be derived from it
+    CountryOnly(&'static str),
+}
+
+#[cfg(feature = "alloc")]
+/// Like `icao_to_registration`, but falls back to the allocating country when no
+/// registration can be derived.
+///
+/// Returns `None` only for addresses that are unallocated or reserved for
+/// special use.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::{icao_to_registration_or_country, Registration};
+///
+/// assert_eq!(
+///     icao_to_registration_or_country([0xAB, 0x8E, 0x4F]),
+///     Some(Registration::Exact("N8437D".to_string()))
+/// );
+/// assert_eq!(
+///     icao_to_registration_or_country([0x39, 0x44, 0xEF]),
+///     Some(Registration::CountryOnly("FR"))
+/// );
+/// ```
+pub fn icao_to_registration_or_country(icao: [u8; 3]) -> Option<Registration> {
+    let icao_u32 = arr3_to_u32(icao);
+    match decode_u32(icao_u32) {
+        Some(conversion) => Some(Registration::Exact(conversion.value)),
+        None => crate::icao::icao_u32_to_country(icao_u32).map(Registration::CountryOnly),
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Convert many ICAO addresses to registrations at once.
+///
+/// Equivalent to calling `icao_to_registration` on every element, but takes
+/// addresses as u32 and finds each one's scheme with a binary search. Values with
+/// more than 24 bits or without a registration yield `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::registrations_for;
+///
+/// let registrations = registrations_for(&[0xAB8E4F, 0x3C65A1, 0x400000]);
+/// assert_eq!(registrations, vec![Some("N8437D".to_string()), Some("D-AIMA".to_string()), None]);
+/// ```
+pub fn registrations_for(addresses: &[u32]) -> Vec<Option<String>> {
+    addresses
+        .iter()
+        .map(|&icao| decode_u32(icao).map(|conversion| conversion.value))
+        .collect()
+}
+
+#[cfg(feature = "alloc")]
+/// Convert many registrations to ICAO addresses at once.
+///
+/// Equivalent to calling `registration_to_icao` on every element, returning the
+/// addresses as u32. Registrations that cannot be converted yield `None`.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::addresses_for;
+///
+/// let addresses = addresses_for(&["N8437D", "D-AIMA", "G-ABCD"]);
+/// assert_eq!(addresses, vec![Some(0xAB8E4F), Some(0x3C65A1), None]);
+/// ```
+pub fn addresses_for<S: AsRef<str>>(registrations: &[S]) -> Vec<Option<u32>> {
+    registrations
+        .iter()
+        .map(|reg| {
+            registration_to_icao_detailed(reg.as_ref())
+                .ok()
+                .map(|conversion| arr3_to_u32(conversion.value))
+        })
+        .collect()
+}
+
+#[cfg(feature = "alloc")]
+/// Every address in `range` that encodes a registration, in ascending order,
+/// together with its conversion.
+///
+/// Only the parts of `range` covered by a scheme are visited, so a range
+/// spanning the whole address space is cheap to walk.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::registrations_in;
+///
+/// let first: Vec<_> = registrations_in(0xA00000..=0xA00003)
+///     .map(|(address, conversion)| (address, conversion.value))
+///     .collect();
+/// assert_eq!(
+///     first,
+///     vec![(0xA00001, "N1".to_string()), (0xA00002, "N1A".to_string()), (0xA00003, "N1AA".to_string())]
+/// );
+/// ```
+pub fn registrations_in(range: RangeInclusive<u32>) -> impl Iterator<Item = (u32, Conversion<String>)> {
+    let (low, high) = (*range.start(), *range.end());
+    DECODERS
+        .iter()
+        .filter(move |&&(start, end, _)| start <= high && end >= low)
+        .flat_map(move |&(start, end, _)| start.max(low)..=end.min(high))
+        .filter_map(|icao| decode_u32(icao).map(|conversion| (icao, conversion)))
+}
+
+#[cfg(feature = "alloc")]
+/// Both forms of an aircraft identity, as produced by `convert`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converted {
+    /// The ICAO address
+    pub icao: Icao24,
+    /// The registration in its canonical form, e.g. `D-AIMA`
+    pub registration: String,
+    /// ISO 3166-1 alpha-2 code of the registering state
+    pub iso2: &'static str,
+    /// Name of the scheme used, e.g. `"US N-number"` or `"Germany D-A"`
+    pub scheme: &'static str,
+}
+
+#[cfg(feature = "alloc")]
+/// Convert a hex address to its registration or a registration to its address,
+/// detecting which one the input is.
+///
+/// Input that `Icao24::parse_hex` accepts is treated as an address; anything else
+/// as a registration. No supported registration is also a valid hex address.
+/// Either way both forms are returned, with the registration canonicalized by
+/// decoding it back from its address.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::convert;
+///
+/// let from_hex = convert("3C65A1").unwrap();
+/// assert_eq!(from_hex.registration, "D-AIMA");
+///
+/// let from_reg = convert("DAIMA").unwrap();
+/// assert_eq!(from_reg.icao.to_string(), "3C65A1");
+/// assert_eq!(from_reg.registration, "D-AIMA");
+/// assert_eq!(from_reg, from_hex);
+/// ```
+pub fn convert(input: &str) -> Result<Converted, ConversionError> {
+    let icao = match Icao24::parse_hex(input) {
+        Ok(icao) => icao,
+        Err(_) => Icao24::from_bytes(registration_to_icao(input)?),
+    };
+    let conversion = icao_to_registration_detailed(icao.to_bytes())?;
+    Ok(Converted {
+        icao,
+        registration: conversion.value,
+        iso2: conversion.iso2,
+        scheme: conversion.scheme,
+    })
+}
+
+#[cfg(feature = "alloc")]
+/// How an ICAO address relates to the US allocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsAddress {
+    /// The address encodes this N-number
+    NNumber(String),
+    /// The address is US-allocated (`US_BASE ..= US_ALLOCATION_MAX`) but has no N-number
+    NonNNumber,
+    /// The address is outside the US allocation
+    NotUs,
+}
+
+#[cfg(feature = "alloc")]
+/// Classify an ICAO address against the US allocation.
+///
+/// Distinguishes addresses that decode to an N-number from US addresses outside the
+/// N-number scheme and from addresses that are not American at all.
+pub fn us_address(icao: [u8; 3]) -> UsAddress {
     let icao_u32 = arr3_to_u32(icao);
     if (US_BASE + 1..=US_MAX).contains(&icao_u32) {
-        icao_u32_to_us(icao_u32)
+        match icao_u32_to_us(icao_u32) {
+            Some(reg) => UsAddress::NNumber(reg.to_string()),
+            None => UsAddress::NonNNumber,
+        }
+    } else if (US_BASE..=US_ALLOCATION_MAX).contains(&icao_u32) {
+        UsAddress::NonNNumber
     } else {
-        Err("ICAO not in US range".into())
+        UsAddress::NotUs
+    }
+}
+
+/// Airworthiness category letter that US registrations carried after the N until
+/// 1948, e.g. `NC12345`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsCategory {
+    /// `NC`: standard/commercial
+    Commercial,
+    /// `NR`: restricted
+    Restricted,
+    /// `NX`: experimental
+    Experimental,
+    /// `NL`: limited
+    Limited,
+}
+
+impl UsCategory {
+    /// The letter written after the N
+    pub fn letter(&self) -> char {
+        match self {
+            UsCategory::Commercial => 'C',
+            UsCategory::Restricted => 'R',
+            UsCategory::Experimental => 'X',
+            UsCategory::Limited => 'L',
+        }
     }
+
+    #[cfg(feature = "alloc")]
+    fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'C' => Some(UsCategory::Commercial),
+            'R' => Some(UsCategory::Restricted),
+            'X' => Some(UsCategory::Experimental),
+            'L' => Some(UsCategory::Limited),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// A registration with any historical US category letter removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedRegistration {
+    /// The registration in its modern form, e.g. `N12345` for `NC12345`
+    pub registration: String,
+    /// The category letter that was removed, if any
+    pub us_category: Option<UsCategory>,
+}
+
+#[cfg(feature = "alloc")]
+/// Rewrite a vintage US registration such as `NC12345` to its modern N-number.
+///
+/// The converters and the parser only understand modern registrations; this
+/// normalization is opt-in. A category letter is only removed when a non-zero
+/// digit follows it, since no modern N-number has a letter directly after the N.
+/// Other registrations are returned unchanged.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::{normalize_us_category, registration_to_icao, UsCategory};
+///
+/// let normalized = normalize_us_category("NX211");
+/// assert_eq!(normalized.registration, "N211");
+/// assert_eq!(normalized.us_category, Some(UsCategory::Experimental));
+/// assert!(registration_to_icao(&normalized.registration).is_ok());
+/// ```
+pub fn normalize_us_category(reg: &str) -> NormalizedRegistration {
+    let mut chars = reg.chars();
+    if let (Some('N'), Some(letter), Some('1'..='9')) = (chars.next(), chars.next(), chars.next()) {
+        if let Some(category) = UsCategory::from_letter(letter) {
+            return NormalizedRegistration {
+                registration: format!("N{}", &reg[2..]),
+                us_category: Some(category),
+            };
+        }
+    }
+    NormalizedRegistration { registration: reg.to_string(), us_category: None }
+}
+
+// N-number suffixes reserved by US air carriers, generated by `build.rs` from
+// `data/faa-carrier-series.csv`, sorted by suffix.
+#[cfg(feature = "alloc")]
+include!(concat!(env!("OUT_DIR"), "/faa_carrier_series.rs"));
+
+#[cfg(feature = "alloc")]
+/// FAA assignment class of a syntactically valid N-number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NNumberClass {
+    /// N1 through N99, reserved for FAA internal use
+    FaaInternal,
+    /// One digit followed by letters (N1A through N9ZZ), only assigned as a
+    /// special N-number with FAA approval
+    SingleDigit,
+    /// Digits followed by a two-letter suffix an air carrier reserves for its
+    /// fleet, e.g. N###UA for United. The carrier holds the series; an
+    /// individual number in it may still be unassigned.
+    AirCarrier(&'static Airline),
+    /// Available for general assignment
+    General,
+}
+
+#[cfg(feature = "alloc")]
+impl NNumberClass {
+    /// Whether the number is outside the pool open to general assignment.
+    pub fn is_reserved(&self) -> bool {
+        !matches!(self, NNumberClass::General)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Classify an N-number under the FAA's reserved and special blocks.
+///
+/// Accepts the same spellings as the converters ("n-1ua" is N1UA) and fails
+/// with the `NNumberError` for numbers that are not valid N-numbers at all.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::{n_number_class, NNumberClass, NNumberError};
+///
+/// assert_eq!(n_number_class("N42").unwrap(), NNumberClass::FaaInternal);
+/// assert_eq!(n_number_class("N7A").unwrap(), NNumberClass::SingleDigit);
+/// match n_number_class("N401UA").unwrap() {
+///     NNumberClass::AirCarrier(airline) => assert_eq!(airline.icao, "UAL"),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// assert_eq!(n_number_class("N8437D").unwrap(), NNumberClass::General);
+/// assert!(!n_number_class("N8437D").unwrap().is_reserved());
+/// assert_eq!(n_number_class("N0A"), Err(NNumberError::LeadingZero));
+/// ```
+pub fn n_number_class(reg: &str) -> Result<NNumberClass, NNumberError> {
+    let reg = normalize_registration(reg).map_err(|_| NNumberError::TooLong)?;
+    let rest = reg.strip_prefix('N').ok_or(NNumberError::MissingPrefix)?;
+    validate_n_number(rest)?;
+
+    let digits = rest.trim_end_matches(|c: char| c.is_ascii_uppercase());
+    let letters = &rest[digits.len()..];
+    if letters.is_empty() && digits.len() <= 2 {
+        return Ok(NNumberClass::FaaInternal);
+    }
+    if !letters.is_empty() && digits.len() == 1 {
+        return Ok(NNumberClass::SingleDigit);
+    }
+    let carrier = CARRIER_SERIES
+        .binary_search_by(|(suffix, _)| (*suffix).cmp(letters))
+        .ok()
+        .and_then(|index| airlines::lookup_icao(CARRIER_SERIES[index].1));
+    Ok(carrier.map_or(NNumberClass::General, NNumberClass::AirCarrier))
+}
+
+#[cfg(feature = "alloc")]
+/// A country whose registrations the converters can derive from ICAO addresses
+/// and vice versa.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedCountry {
+    /// ISO 3166-1 alpha-2 country code
+    pub iso2: &'static str,
+    /// Registration prefixes covered, e.g. `["D-A", "D-B", ...]` for Germany
+    pub prefixes: Vec<&'static str>,
+    /// Address ranges the schemes are laid out in, ordered by start. Not every
+    /// address inside a range necessarily encodes a registration.
+    pub ranges: Vec<RangeInclusive<u32>>,
+    /// Whether `registration_to_icao` handles the country's registrations
+    pub to_icao: bool,
+    /// Whether `icao_to_registration` handles the country's addresses
+    pub to_registration: bool,
+}
+
+#[cfg(feature = "alloc")]
+/// List the countries supported by the converters, ordered by ISO2 code.
+///
+/// Applications can use this to decide up front whether an address or
+/// registration can be converted locally or needs an external registry.
+///
+/// # Examples
+/// ```
+/// use flydent::registration::supported_countries;
+///
+/// let countries = supported_countries();
+/// let us = countries.iter().find(|country| country.iso2 == "US").unwrap();
+/// assert_eq!(us.prefixes, vec!["N"]);
+/// assert!(us.ranges[0].contains(&0xAB8E4F));
+/// assert!(us.to_icao && us.to_registration);
+/// ```
+pub fn supported_countries() -> Vec<SupportedCountry> {
+    let mut schemes: Vec<(&'static str, &'static str, RangeInclusive<u32>)> = vec![
+        ("US", "N", US_BASE + 1..=US_MAX),
+        ("JP", "JA", JP_BASE..=JP_BASE + JP_SIZE - 1),
+    ];
+    for &(start, end, _) in KR_RANGES {
+        schemes.push(("KR", "HL", start..=end));
+    }
+    for mapping in STRIDE_MAPPINGS {
+        schemes.push((mapping.iso2, mapping.prefix, mapping.start..=mapping.end()));
+    }
+
+    let mut countries: BTreeMap<&'static str, SupportedCountry> = BTreeMap::new();
+    for (iso2, prefix, range) in schemes {
+        let country = countries.entry(iso2).or_insert_with(|| SupportedCountry {
+            iso2,
+            prefixes: Vec::new(),
+            ranges: Vec::new(),
+            to_icao: true,
+            to_registration: true,
+        });
+        if !country.prefixes.contains(&prefix) {
+            country.prefixes.push(prefix);
+        }
+        country.ranges.push(range);
+    }
+
+    countries
+        .into_values()
+        .map(|mut country| {
+            country.ranges.sort_by_key(|range| *range.start());
+            country
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -227,6 +1290,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn us_roundtrip() {
         let reg = "N456TS";
         let icao = registration_to_icao(reg).unwrap();
@@ -234,6 +1298,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn us_icao_to_registration_ab8e4f() {
         // Convert ICAO code AB8E4F to registration N8437D
         let icao = [0xAB, 0x8E, 0x4F];
@@ -250,12 +1315,14 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn reject_other() {
         assert!(registration_to_icao("G-ABCD").is_err());
         assert!(icao_to_registration([0x00, 0x12, 0x34]).is_err());
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn test_n1() {
         // N1 should be the first valid registration
         let icao = registration_to_icao("N1").unwrap();
@@ -264,6 +1331,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn test_n99999() {
         // N99999 should be the last valid registration
         let reg = "N99999";
@@ -271,4 +1339,641 @@ mod tests {
         assert_eq!(icao, [0xAD, 0xF7, 0xC7]);
         assert_eq!(icao_to_registration(icao).unwrap(), reg);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn au_known_registrations() {
+        // Qantas A380 and Virgin Australia 737
+        assert_eq!(registration_to_icao("VH-OQA").unwrap(), [0x7C, 0x49, 0x20]);
+        assert_eq!(icao_to_registration([0x7C, 0x49, 0x20]).unwrap(), "VH-OQA");
+        assert_eq!(icao_to_registration([0x7C, 0x7A, 0xA0]).unwrap(), "VH-YIA");
+        assert_eq!(registration_to_icao("VHOQA").unwrap(), [0x7C, 0x49, 0x20]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn au_block_boundaries() {
+        assert_eq!(icao_to_registration([0x7C, 0x00, 0x00]).unwrap(), "VH-AAA");
+        assert_eq!(icao_to_registration([0x7C, 0x82, 0x2D]).unwrap(), "VH-ZZZ");
+        // Unused grid cells and the rest of the allocation have no registration
+        assert!(icao_to_registration(u32_to_arr3(0x7C0000 + 26)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x7C0000 + 26 * 36)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x7C822E)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x7FFFFF)).is_err());
+    }
+
+    #[test]
+    fn au_rejects_malformed() {
+        assert!(registration_to_icao("VH-OQ").is_err());
+        assert!(registration_to_icao("VH-OQAA").is_err());
+        assert!(registration_to_icao("VH-OQ1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn de_known_registrations() {
+        // Lufthansa A380
+        assert_eq!(registration_to_icao("D-AIMA").unwrap(), [0x3C, 0x65, 0xA1]);
+        assert_eq!(icao_to_registration([0x3C, 0x65, 0xA1]).unwrap(), "D-AIMA");
+        assert_eq!(registration_to_icao("DAIMA").unwrap(), [0x3C, 0x65, 0xA1]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn de_block_boundaries() {
+        let cases = [
+            ("D-AAAA", 0x3C4421),
+            ("D-AOZZ", 0x3C4421 + 14 * 1024 + 25 * 32 + 25),
+            ("D-APAA", 0x3C0001),
+            ("D-AZZZ", 0x3C0001 + 10 * 676 + 25 * 26 + 25),
+            ("D-BAAA", 0x3C8421),
+            ("D-BPAA", 0x3C2001),
+            ("D-CAAA", 0x3CC000),
+            ("D-EAAA", 0x3D04A8),
+            ("D-FAAA", 0x3D4950),
+            ("D-IZZZ", 0x3E1748 + 17575),
+        ];
+        for (reg, address) in cases {
+            assert_eq!(registration_to_icao(reg).unwrap(), u32_to_arr3(address), "{}", reg);
+            assert_eq!(icao_to_registration(u32_to_arr3(address)).unwrap(), reg);
+        }
+
+        // Consecutive single-layout runs are contiguous
+        assert_eq!(icao_to_registration(u32_to_arr3(0x3D04A7)).unwrap(), "D-CZZZ");
+        // Unused cells of the 32-wide layout and D-D have no registration
+        assert!(icao_to_registration(u32_to_arr3(0x3C4421 + 26)).is_err());
+        assert!(registration_to_icao("D-DABC").is_err());
+        assert!(registration_to_icao("D-EAB").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn be_block_boundaries() {
+        // Brussels Airlines A320
+        assert_eq!(registration_to_icao("OO-SNA").unwrap(), [0x44, 0xCD, 0xC1]);
+        assert_eq!(icao_to_registration(u32_to_arr3(0x448421)).unwrap(), "OO-AAA");
+        assert_eq!(icao_to_registration(u32_to_arr3(0x44EB5A)).unwrap(), "OO-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x448420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x44EB5B)).is_err());
+        assert!(registration_to_icao("OO-SN").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn dk_block_boundaries() {
+        assert_eq!(registration_to_icao("OY-AAA").unwrap(), [0x45, 0x84, 0x21]);
+        assert_eq!(registration_to_icao("OY-KBO").unwrap(), u32_to_arr3(0x458421 + 10 * 1024 + 32 + 14));
+        assert_eq!(icao_to_registration(u32_to_arr3(0x45EB5A)).unwrap(), "OY-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x458420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x45EB5B)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn fi_block_boundaries() {
+        // Finnair A350
+        assert_eq!(registration_to_icao("OH-LWA").unwrap(), [0x46, 0x1F, 0x48]);
+        assert_eq!(icao_to_registration(u32_to_arr3(0x460000)).unwrap(), "OH-AAA");
+        assert_eq!(icao_to_registration(u32_to_arr3(0x4644A7)).unwrap(), "OH-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x4644A8)).is_err());
+        assert!(registration_to_icao("OH-LW1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn gr_block_boundaries() {
+        assert_eq!(icao_to_registration(u32_to_arr3(0x468421)).unwrap(), "SX-AAA");
+        assert_eq!(registration_to_icao("SX-DGA").unwrap(), u32_to_arr3(0x468421 + 3 * 1024 + 6 * 32));
+        assert_eq!(icao_to_registration(u32_to_arr3(0x46EB5A)).unwrap(), "SX-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x468420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x46EB5B)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn hu_not_converted() {
+        // Hungarian addresses are assigned without a known letter layout
+        assert!(registration_to_icao("HA-LYA").is_err());
+        assert!(icao_to_registration([0x47, 0x1F, 0x00]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ie_not_converted() {
+        // Irish addresses follow registration order, not the letters of the mark
+        assert!(registration_to_icao("EI-DCL").is_err());
+        assert!(icao_to_registration([0x4C, 0xA2, 0xD6]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn is_not_converted() {
+        assert!(registration_to_icao("TF-FIA").is_err());
+        assert!(icao_to_registration([0x4C, 0xC2, 0x00]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn pt_block_boundaries() {
+        assert_eq!(icao_to_registration(u32_to_arr3(0x490421)).unwrap(), "CS-AAA");
+        assert_eq!(registration_to_icao("CS-TNP").unwrap(), u32_to_arr3(0x490421 + 19 * 1024 + 13 * 32 + 15));
+        assert_eq!(icao_to_registration(u32_to_arr3(0x496B5A)).unwrap(), "CS-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x490420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x496B5B)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn ch_not_converted() {
+        assert!(registration_to_icao("HB-JNA").is_err());
+        assert!(icao_to_registration([0x4B, 0x19, 0x01]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn tr_block_boundaries() {
+        assert_eq!(icao_to_registration(u32_to_arr3(0x4B8421)).unwrap(), "TC-AAA");
+        assert_eq!(registration_to_icao("TC-JJE").unwrap(), u32_to_arr3(0x4B8421 + 9 * 1024 + 9 * 32 + 4));
+        assert_eq!(icao_to_registration(u32_to_arr3(0x4BEB5A)).unwrap(), "TC-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x4B8420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x4BEB5B)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn za_block_boundaries() {
+        assert_eq!(icao_to_registration(u32_to_arr3(0x008011)).unwrap(), "ZS-AAA");
+        assert_eq!(icao_to_registration(u32_to_arr3(0x00C4B8)).unwrap(), "ZS-ZZZ");
+        assert_eq!(registration_to_icao("ZS-SXA").unwrap(), u32_to_arr3(0x008011 + 18 * 676 + 23 * 26));
+        assert!(icao_to_registration(u32_to_arr3(0x008010)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x00C4B9)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn sg_block_boundaries() {
+        assert_eq!(icao_to_registration(u32_to_arr3(0x768421)).unwrap(), "9V-AAA");
+        assert_eq!(registration_to_icao("9V-SKA").unwrap(), u32_to_arr3(0x768421 + 18 * 1024 + 10 * 32));
+        assert_eq!(icao_to_registration(u32_to_arr3(0x76EB5A)).unwrap(), "9V-ZZZ");
+        assert!(icao_to_registration(u32_to_arr3(0x768420)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x76EB5B)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn kr_known_registrations() {
+        // Korean Air 747-8I
+        assert_eq!(registration_to_icao("HL7630").unwrap(), [0x71, 0xBE, 0x30]);
+        assert_eq!(icao_to_registration([0x71, 0xBE, 0x30]).unwrap(), "HL7630");
+        assert_eq!(registration_to_icao("HL-7630").unwrap(), [0x71, 0xBE, 0x30]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn kr_block_boundaries() {
+        let cases = [
+            ("HL7200", 0x71BA00),
+            ("HL7799", 0x71BF99),
+            ("HL8000", 0x71C000),
+            ("HL8099", 0x71C099),
+            ("HL8200", 0x71C200),
+            ("HL8299", 0x71C299),
+        ];
+        for (reg, address) in cases {
+            assert_eq!(registration_to_icao(reg).unwrap(), u32_to_arr3(address), "{}", reg);
+            assert_eq!(icao_to_registration(u32_to_arr3(address)).unwrap(), reg);
+        }
+
+        // Non-decimal low digits and numbers outside the ranges
+        assert!(icao_to_registration(u32_to_arr3(0x71BA0A)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(0x71C100)).is_err());
+        assert!(registration_to_icao("HL7199").is_err());
+        assert!(registration_to_icao("HL8100").is_err());
+        assert!(registration_to_icao("HL76A0").is_err());
+        assert!(registration_to_icao("HL763").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn jp_layout() {
+        let cases = [
+            ("JA0000", JP_BASE),
+            ("JA0009", JP_BASE + 9),
+            ("JA000A", JP_BASE + 10),
+            ("JA000Z", JP_BASE + 33),
+            ("JA0010", JP_BASE + 34),
+            ("JA00AA", JP_BASE + 340),
+            ("JA00ZZ", JP_BASE + 915),
+            ("JA0100", JP_BASE + 916),
+            ("JA1000", JP_BASE + 22984),
+            ("JA801A", JP_BASE + 8 * 22984 + 34 + 10),
+            ("JA99ZZ", 0x874BCF),
+        ];
+        for (reg, address) in cases {
+            assert_eq!(registration_to_icao(reg).unwrap(), u32_to_arr3(address), "{}", reg);
+            assert_eq!(icao_to_registration(u32_to_arr3(address)).unwrap(), reg);
+        }
+
+        // The rest of each leading digit's run is unused
+        assert!(icao_to_registration(u32_to_arr3(JP_BASE + 10 * 916)).is_err());
+        assert!(icao_to_registration(u32_to_arr3(JP_BASE + JP_SIZE)).is_err());
+        assert!(registration_to_icao("JA00IA").is_err());
+        assert!(registration_to_icao("JA0A00").is_err());
+        assert!(registration_to_icao("JA123").is_err());
+    }
+
+    #[test]
+    fn jp_round_trip() {
+        for address in JP_BASE..JP_BASE + JP_SIZE {
+            if let Some(reg) = icao_u32_to_jp(address) {
+                assert_eq!(jp_ja_to_icao_u32(&reg).unwrap(), address, "{}", reg);
+            }
+        }
+    }
+
+    #[test]
+    fn stride_mapping_custom_alphabet_and_span() {
+        let mapping = StrideMapping {
+            alphabet: "0123456789",
+            ..stride(0x100000, 100, 10, "XX", "X-", "Test X-").span("100", "899")
+        };
+        assert_eq!(mapping.to_icao_u32("100"), Some(0x100000));
+        assert_eq!(mapping.to_icao_u32("123"), Some(0x100000 + 23));
+        assert_eq!(mapping.to_icao_u32("099"), None);
+        assert_eq!(mapping.to_icao_u32("900"), None);
+        assert_eq!(mapping.to_icao_u32("1A3"), None);
+        assert_eq!(mapping.end(), 0x100000 + 799);
+        assert_eq!(mapping.to_registration(0x100000 + 799).as_deref(), Some("X-899"));
+        assert_eq!(mapping.to_registration(0x100000 + 800), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn supported_countries_cover_converters() {
+        let countries = supported_countries();
+        let codes: Vec<&str> = countries.iter().map(|country| country.iso2).collect();
+        assert_eq!(
+            codes,
+            ["AU", "BE", "DE", "DK", "FI", "GR", "JP", "KR", "PT", "SG", "TR", "US", "ZA"]
+        );
+
+        let de = countries.iter().find(|country| country.iso2 == "DE").unwrap();
+        assert_eq!(de.prefixes, ["D-A", "D-B", "D-C", "D-E", "D-F", "D-G", "D-H", "D-I"]);
+        assert_eq!(de.ranges.len(), 10);
+        assert!(de.ranges.windows(2).all(|pair| pair[0].start() < pair[1].start()));
+
+        // Every convertible address lies inside the advertised ranges and country
+        for country in &countries {
+            for range in &country.ranges {
+                for address in [*range.start(), *range.end()] {
+                    assert_eq!(
+                        crate::icao::icao_u32_to_country(address),
+                        Some(country.iso2),
+                        "{:06X}",
+                        address
+                    );
+                }
+            }
+        }
+        for address in [0x3C65A1, 0x7C4920, 0x71BE30] {
+            let covered = countries
+                .iter()
+                .any(|country| country.ranges.iter().any(|range| range.contains(&address)));
+            assert!(covered && icao_to_registration(u32_to_arr3(address)).is_ok());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn conversion_error_kinds() {
+        assert_eq!(registration_to_icao("G-ABCD"), Err(ConversionError::UnsupportedCountry));
+        assert_eq!(icao_to_registration([0x40, 0x00, 0x00]), Err(ConversionError::UnsupportedCountry));
+        assert_eq!(registration_to_icao("HL8100"), Err(ConversionError::OutOfRange));
+        // Unused cell of the Australian grid
+        assert_eq!(icao_to_registration([0x7C, 0x00, 0x1A]), Err(ConversionError::NotDeterministic));
+        assert_eq!(
+            registration_to_icao("N12I"),
+            Err(ConversionError::InvalidNNumber(NNumberError::ForbiddenLetter('I')))
+        );
+        assert!(matches!(
+            registration_to_icao("VH-AB"),
+            Err(ConversionError::InvalidFormat { .. })
+        ));
+
+        let err: Box<dyn core::error::Error> = Box::new(ConversionError::OutOfRange);
+        assert_eq!(err.to_string(), "registration outside the range of its scheme");
+    }
+
+    #[test]
+    fn n_number_error_kinds() {
+        let cases = [
+            ("N", NNumberError::Empty),
+            ("N0", NNumberError::LeadingZero),
+            ("N012", NNumberError::LeadingZero),
+            ("N1O", NNumberError::ForbiddenLetter('O')),
+            ("N1I2", NNumberError::ForbiddenLetter('I')),
+            ("N1.2", NNumberError::InvalidCharacter('.')),
+            ("N1ABC", NNumberError::TooManyLetters),
+            ("N12ABC", NNumberError::TooManyLetters),
+            ("NA", NNumberError::MisplacedLetter),
+            ("N1A2", NNumberError::MisplacedLetter),
+            ("N123456", NNumberError::TooLong),
+        ];
+        for (reg, kind) in cases {
+            assert_eq!(registration_to_icao(reg), Err(ConversionError::InvalidNNumber(kind)), "{}", reg);
+        }
+        for reg in ["N1", "N9ZZ", "N1234Z", "N12345", "N123AB"] {
+            assert!(registration_to_icao(reg).is_ok(), "{}", reg);
+        }
+
+        let err = ConversionError::from(NNumberError::LeadingZero);
+        assert_eq!(err.to_string(), "invalid N-number: first character after N must not be 0");
+        assert!(core::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn detailed_conversions_report_scheme() {
+        let cases = [
+            ("N8437D", "US", "US N-number"),
+            ("JA801A", "JP", "Japan JA"),
+            ("HL7630", "KR", "South Korea HL"),
+            ("VH-OQA", "AU", "Australia VH-"),
+            ("D-APAA", "DE", "Germany D-A"),
+            ("OO-SNA", "BE", "Belgium OO-"),
+        ];
+        for (reg, iso2, scheme) in cases {
+            let forward = registration_to_icao_detailed(reg).unwrap();
+            assert_eq!((forward.iso2, forward.scheme), (iso2, scheme), "{}", reg);
+
+            let reverse = icao_to_registration_detailed(forward.value).unwrap();
+            assert_eq!(reverse, Conversion::new(reg.to_string(), iso2, scheme));
+        }
+        assert!(icao_to_registration_detailed([0x40, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn batch_conversions_match_single() {
+        let addresses: Vec<u32> = (0x3C0000..0x3C9000)
+            .chain(0x71BA00..0x71C300)
+            .chain([0xAB8E4F, 0xAE1234, 0x840000, 0x01000000])
+            .collect();
+        let registrations = registrations_for(&addresses);
+        for (&address, reg) in addresses.iter().zip(&registrations) {
+            let single = if address > 0xFFFFFF {
+                None
+            } else {
+                icao_to_registration(u32_to_arr3(address)).ok()
+            };
+            assert_eq!(*reg, single, "{:06X}", address);
+        }
+
+        let regs: Vec<&str> = registrations.iter().flatten().map(String::as_str).collect();
+        let back = addresses_for(&regs);
+        let expected: Vec<Option<u32>> = addresses
+            .iter()
+            .zip(&registrations)
+            .filter(|(_, reg)| reg.is_some())
+            .map(|(&address, _)| Some(address))
+            .collect();
+        assert_eq!(back, expected);
+        assert_eq!(addresses_for(&["", "N1I", "XX-ABC"]), vec![None, None, None]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn registration_or_country_fallback() {
+        assert_eq!(
+            icao_to_registration_or_country([0x3C, 0x65, 0xA1]),
+            Some(Registration::Exact("D-AIMA".to_string()))
+        );
+        // US address above N99999
+        assert_eq!(icao_to_registration_or_country([0xAE, 0x12, 0x34]), Some(Registration::CountryOnly("US")));
+        // Country without a converter
+        assert_eq!(icao_to_registration_or_country([0x40, 0x00, 0x00]), Some(Registration::CountryOnly("GB")));
+        // ICAO temporary block
+        assert_eq!(icao_to_registration_or_country([0xF0, 0x00, 0x00]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn stride_mappings_round_trip() {
+        for mapping in STRIDE_MAPPINGS {
+            for address in mapping.start..=mapping.end() {
+                if let Some(reg) = mapping.to_registration(address) {
+                    assert_eq!(registration_to_icao(&reg).unwrap(), u32_to_arr3(address), "{}", reg);
+                    assert_eq!(icao_to_registration(u32_to_arr3(address)).unwrap(), reg.as_str());
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn us_non_nnumber_block() {
+        // Just above N99999 is still American but not an N-number
+        assert_eq!(us_address(u32_to_arr3(US_MAX + 1)), UsAddress::NonNNumber);
+        assert_eq!(us_address([0xAF, 0xFF, 0xFF]), UsAddress::NonNNumber);
+        assert_eq!(us_address([0xA0, 0x00, 0x00]), UsAddress::NonNNumber);
+        assert_eq!(
+            us_address([0xAB, 0x8E, 0x4F]),
+            UsAddress::NNumber("N8437D".to_string())
+        );
+        assert_eq!(us_address([0xB0, 0x00, 0x00]), UsAddress::NotUs);
+
+        // Nothing inside the allocation is classified as foreign
+        for address in (US_BASE..=US_ALLOCATION_MAX).step_by(0x7F) {
+            assert_ne!(us_address(u32_to_arr3(address)), UsAddress::NotUs, "{:06X}", address);
+        }
+
+        let err = icao_to_registration([0xAE, 0x12, 0x34]).unwrap_err();
+        assert_eq!(err, ConversionError::NotDeterministic);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn us_category_normalization() {
+        let cases = [
+            ("NC12345", "N12345", Some(UsCategory::Commercial)),
+            ("NR211", "N211", Some(UsCategory::Restricted)),
+            ("NX211", "N211", Some(UsCategory::Experimental)),
+            ("NL1A", "N1A", Some(UsCategory::Limited)),
+            // Not followed by a non-zero digit
+            ("NC0", "NC0", None),
+            ("NCA", "NCA", None),
+            // Other letters and modern registrations are left alone
+            ("NA123", "NA123", None),
+            ("N8437D", "N8437D", None),
+            ("D-AIMA", "D-AIMA", None),
+        ];
+        for (input, registration, us_category) in cases {
+            let normalized = normalize_us_category(input);
+            assert_eq!(normalized.registration, registration, "{}", input);
+            assert_eq!(normalized.us_category, us_category, "{}", input);
+        }
+        assert_eq!(
+            registration_to_icao(&normalize_us_category("NC12345").registration),
+            registration_to_icao("N12345")
+        );
+        assert_eq!(UsCategory::Experimental.letter(), 'X');
+    }
+
+    /// Golden hex/registration pairs shared with tar1090/dump1090's
+    /// `registrations.js`, kept in `data/registration-parity.csv`. New countries
+    /// should add pairs there when both decoders support them.
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parity_with_registrations_js() {
+        let csv = include_str!("../data/registration-parity.csv");
+        for line in csv.lines().skip(1).filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let (hex, reg, iso2) = (fields[0], fields[1], fields[2]);
+            let icao = u32::from_str_radix(hex, 16).unwrap_or_else(|_| panic!("bad hex {}", hex));
+
+            let decoded = icao_to_registration_detailed(u32_to_arr3(icao));
+            assert_eq!(decoded.as_ref().map(|c| c.value.as_str()), Ok(reg), "{} decodes differently", hex);
+            assert_eq!(decoded.map(|c| c.iso2), Ok(iso2), "{} reports another country", hex);
+            assert_eq!(
+                registration_to_icao(reg).map(arr3_to_u32),
+                Ok(icao),
+                "{} does not encode {}",
+                reg,
+                hex
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn convert_detects_direction() {
+        for input in ["AB8E4F", "ab8e4f", "0xAB8E4F", "N8437D"] {
+            let converted = convert(input).unwrap();
+            assert_eq!(converted.icao.value(), 0xAB8E4F, "{}", input);
+            assert_eq!(converted.registration, "N8437D", "{}", input);
+            assert_eq!(converted.iso2, "US");
+        }
+        assert_eq!(convert("VHOQA").unwrap().registration, "VH-OQA");
+        assert_eq!(convert("7C4920").unwrap().scheme, "Australia VH-");
+
+        assert_eq!(convert("400000"), Err(ConversionError::UnsupportedCountry));
+        assert_eq!(convert("G-ABCD"), Err(ConversionError::UnsupportedCountry));
+        assert_eq!(convert("AE1234"), Err(ConversionError::NotDeterministic));
+        assert!(matches!(convert("N0"), Err(ConversionError::InvalidNNumber(_))));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn registrations_in_matches_single_conversions() {
+        let range = 0x3C0000..=0x3C9000;
+        let expected: Vec<(u32, String)> = range
+            .clone()
+            .filter_map(|icao| icao_to_registration(u32_to_arr3(icao)).ok().map(|reg| (icao, reg)))
+            .collect();
+        let found: Vec<(u32, String)> =
+            registrations_in(range).map(|(icao, conversion)| (icao, conversion.value)).collect();
+        assert_eq!(found, expected);
+
+        // Spans all three South Korean blocks and the gaps between them
+        assert_eq!(registrations_in(0x71B000..=0x71CFFF).count(), 800);
+        assert_eq!(registrations_in(0x400000..=0x43FFFF).count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn tolerant_registration_input() {
+        let cases = [
+            ("n-8437d", 0xAB8E4F),
+            (" N8437D\t", 0xAB8E4F),
+            ("d-aima", 0x3C65A1),
+            ("DAIMA", 0x3C65A1),
+            ("vh\u{2013}oqa", 0x7C4920),
+            ("hl-7630", 0x71BE30),
+            ("ja-8089", 0x86CF59),
+        ];
+        for (input, icao) in cases {
+            assert_eq!(registration_to_icao(input).map(arr3_to_u32), Ok(icao), "{:?}", input);
+        }
+        assert_eq!(icao_hex_to_registration("3c65a1").unwrap(), "D-AIMA");
+        assert!(matches!(icao_hex_to_registration("XYZ123"), Err(ConversionError::InvalidFormat { .. })));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn n_number_classes() {
+        assert_eq!(n_number_class("N1"), Ok(NNumberClass::FaaInternal));
+        assert_eq!(n_number_class("N99"), Ok(NNumberClass::FaaInternal));
+        assert_eq!(n_number_class("N100"), Ok(NNumberClass::General));
+        assert_eq!(n_number_class("N1ZZ"), Ok(NNumberClass::SingleDigit));
+        assert_eq!(n_number_class("n-1ua"), Ok(NNumberClass::SingleDigit));
+        assert!(n_number_class("N12UA").unwrap().is_reserved());
+        assert_eq!(n_number_class("N12U"), Ok(NNumberClass::General));
+        assert_eq!(n_number_class("D-AIMA"), Err(NNumberError::MissingPrefix));
+        assert_eq!(n_number_class("N1O"), Err(NNumberError::ForbiddenLetter('O')));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn carrier_series_airlines_exist() {
+        for (suffix, designator) in CARRIER_SERIES {
+            let class = n_number_class(&format!("N123{}", suffix)).unwrap();
+            assert!(
+                matches!(class, NNumberClass::AirCarrier(airline) if airline.icao == *designator),
+                "{} -> {}",
+                suffix,
+                designator
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "core-only")]
+    fn fixed_buffer_registrations() {
+        let cases = [
+            (0xA00001, "N1"),
+            (0xAB8E4F, "N8437D"),
+            (US_MAX, "N99999"),
+            (0x840000, "JA0000"),
+            (0x71BE11, "HL7611"),
+            (0x3C65A1, "D-AIMA"),
+            (0x7C0000, "VH-AAA"),
+            (0x448421, "OO-AAA"),
+            (0x00C4B8, "ZS-ZZZ"),
+        ];
+        for (address, registration) in cases {
+            assert_eq!(icao_to_registration_buf(u32_to_arr3(address)).unwrap(), registration, "{:06X}", address);
+        }
+        assert_eq!(icao_to_registration_buf(u32_to_arr3(US_MAX + 1)), Err(ConversionError::NotDeterministic));
+        assert_eq!(icao_to_registration_buf(u32_to_arr3(0x394400)), Err(ConversionError::UnsupportedCountry));
+        assert_eq!(icao_to_registration_buf(u32_to_arr3(0xF00000)), Err(ConversionError::UnsupportedCountry));
+    }
+
+    #[test]
+    #[cfg(all(feature = "core-only", feature = "alloc"))]
+    fn fixed_buffer_matches_string_conversion() {
+        for address in (0..=0xFFFFFF).step_by(0x3FF) {
+            let icao = u32_to_arr3(address);
+            assert_eq!(
+                icao_to_registration_buf(icao).map(|reg| reg.to_string()),
+                icao_to_registration(icao),
+                "{:06X}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn registration_buf_capacity() {
+        let mut buf = RegistrationBuf::new();
+        assert_eq!(buf.push_str("N8437D"), Some(()));
+        assert_eq!(buf, "N8437D");
+        assert_eq!(buf.push_str("ABCDEFGHJK"), Some(()));
+        assert_eq!(buf.len(), REGISTRATION_CAPACITY);
+        assert_eq!(buf.push('A'), None);
+        assert_eq!(buf.as_str(), "N8437DABCDEFGHJK");
+        assert!(matches!(
+            registration_to_icao("N8437DABCDEFGHJKL"),
+            Err(ConversionError::InvalidFormat { .. })
+        ));
+    }
 }
@@ -0,0 +1,166 @@
+//! SBS-1 BaseStation Messages
+//!
+//! Enriches the CSV messages that dump1090, readsb and the original Kinetic
+//! SBS-1 serve on port 30003, e.g.
+//! `MSG,3,1,1,3C65A1,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,37000,...`.
+//! The fifth field, HexIdent, is the ICAO address; `SbsRecord` adds the country
+//! it is allocated to and the registration derived from it.
+//!
+//! `EnrichSbs::enrich_sbs` adapts any iterator of lines, e.g. a TCP stream read
+//! with `BufRead::lines`; `flydent sbs` does the same for a pipe.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use crate::icao::{self, Icao24};
+use crate::registration;
+
+/// Index of the HexIdent field.
+const HEX_IDENT_FIELD: usize = 4;
+
+/// The HexIdent field of an SBS message, or `None` if the line has too few
+/// fields or the field is empty.
+///
+/// # Examples
+/// ```
+/// use flydent::sbs::hex_ident;
+///
+/// assert_eq!(hex_ident("MSG,1,1,1,4CA2D6,1,2024/01/01,12:00:00.000,,,RYR1234,,,,,,,,,,,0"), Some("4CA2D6"));
+/// assert_eq!(hex_ident("CLK,,,,,,2024/01/01"), None);
+/// ```
+pub fn hex_ident(line: &str) -> Option<&str> {
+    let field = line.split(',').nth(HEX_IDENT_FIELD)?.trim();
+    (!field.is_empty()).then_some(field)
+}
+
+/// An SBS message together with what flydent derives from its address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbsRecord {
+    /// The message as received, without line ending
+    pub line: String,
+    /// The HexIdent field, if it is a valid ICAO address
+    pub icao: Option<Icao24>,
+    /// ISO2 code of the state the address is allocated to
+    pub iso2: Option<&'static str>,
+    /// Registration, for countries with an algorithmic mapping
+    pub registration: Option<String>,
+}
+
+impl SbsRecord {
+    /// Enrich one message. Lines that are not SBS messages, or have no valid
+    /// address, are kept with empty enrichment.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::sbs::SbsRecord;
+    ///
+    /// let record = SbsRecord::parse("MSG,3,1,1,3C65A1,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,37000\r\n");
+    /// assert_eq!(record.iso2, Some("DE"));
+    /// assert_eq!(record.registration.as_deref(), Some("D-AIMA"));
+    /// assert!(record.to_csv().ends_with(",37000,DE,D-AIMA"));
+    /// ```
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let icao = hex_ident(line).and_then(|hex| Icao24::parse_hex(hex).ok());
+        SbsRecord {
+            line: line.to_string(),
+            icao,
+            iso2: icao.and_then(|icao| icao::icao_u32_to_country(icao.value())),
+            registration: icao.and_then(|icao| registration::icao_to_registration(icao.to_bytes()).ok()),
+        }
+    }
+
+    /// The message with the ISO2 code and registration appended as two more
+    /// fields, empty when unknown. Consumers that read the standard 22 fields
+    /// ignore them.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.line,
+            self.iso2.unwrap_or(""),
+            self.registration.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Iterator adapter returned by `EnrichSbs::enrich_sbs`.
+#[derive(Debug, Clone)]
+pub struct Enriched<I> {
+    lines: I,
+}
+
+impl<I, S> Iterator for Enriched<I>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = SbsRecord;
+
+    fn next(&mut self) -> Option<SbsRecord> {
+        self.lines.next().map(|line| SbsRecord::parse(line.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+}
+
+/// Enrich a stream of SBS lines.
+///
+/// # Examples
+/// ```
+/// use flydent::sbs::EnrichSbs;
+///
+/// let feed = "MSG,8,1,1,AB8E4F,1,,,,,,,,,,,,,,,,0\nMSG,8,1,1,~0001AB,1,,,,,,,,,,,,,,,,0\n";
+/// let registrations: Vec<_> = feed.lines().enrich_sbs().map(|record| record.registration).collect();
+/// assert_eq!(registrations, vec![Some("N8437D".to_string()), None]);
+/// ```
+///
+/// Lines from `BufRead::lines` are `io::Result`s; `map_while(Result::ok)`
+/// stops at the first read error.
+pub trait EnrichSbs: Iterator + Sized {
+    fn enrich_sbs(self) -> Enriched<Self> {
+        Enriched { lines: self }
+    }
+}
+
+impl<I: Iterator> EnrichSbs for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn hex_idents() {
+        assert_eq!(hex_ident("MSG,5,1,1,a00001,1,,,,,,,,,,,,,,,,"), Some("a00001"));
+        assert_eq!(hex_ident("SEL,,333,2,3C65A1,1,,,,,DLH4AB"), Some("3C65A1"));
+        assert_eq!(hex_ident("MSG,5,1,1, ,1"), None);
+        assert_eq!(hex_ident("MSG,5,1"), None);
+        assert_eq!(hex_ident(""), None);
+    }
+
+    #[test]
+    fn records() {
+        let record = SbsRecord::parse("MSG,3,1,1,394400,1,,,,,,37000");
+        assert_eq!(record.icao, Icao24::new(0x394400));
+        assert_eq!(record.iso2, Some("FR"));
+        assert_eq!(record.registration, None);
+        assert_eq!(record.to_csv(), "MSG,3,1,1,394400,1,,,,,,37000,FR,");
+
+        let record = SbsRecord::parse("garbage\n");
+        assert_eq!(record.line, "garbage");
+        assert_eq!((record.icao, record.iso2), (None, None));
+        assert_eq!(record.to_csv(), "garbage,,");
+    }
+
+    #[test]
+    fn adapter_keeps_every_line() {
+        let lines = ["MSG,8,1,1,3C65A1,1", "", "MSG,8,1,1,F00000,1"];
+        let records: Vec<SbsRecord> = lines.iter().enrich_sbs().collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].registration.as_deref(), Some("D-AIMA"));
+        assert_eq!(records[1].icao, None);
+        assert_eq!(records[2].iso2, None);
+        assert_eq!(lines.iter().enrich_sbs().size_hint(), (3, Some(3)));
+    }
+}
@@ -0,0 +1,267 @@
+//! Free-Text Scanning
+//!
+//! Finds registrations and ICAO addresses in arbitrary text such as NOTAMs,
+//! incident reports or posts, where `Parser::parse` needs an identifier that is
+//! already isolated. `Parser::scan` reports each with its byte span.
+//!
+//! Words are runs of ASCII letters, digits and dashes, including the
+//! typographic dashes `Parser::parse` accepts. A word is a registration if it is
+//! written in capitals and its suffix lies in one of the ITU suffix blocks of a
+//! matching dataset row (so `PH-BHA` and `N8437D`, but not `FL350`), and an
+//! address if it is six hex digits, at least one of them a digit, allocated to
+//! a state or organization. Registrations with a dash are
+//! tried before addresses, and addresses before registrations without one.
+//!
+//! `Parser::extract_pairs` builds on the scan for logs that name an aircraft
+//! by both, such as readsb's `aircraft.json` (`"hex":"3c65a1",...,"r":"D-AIMA"`),
+//! key-value logs (`hex=3c65a1 reg=D-AIMA`) or CSV exports, and flags lines
+//! whose address and registration belong to different states.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::icao::{self, Icao24};
+use crate::{marks, EntityResult, Parser};
+
+/// What a word found by `Parser::scan` identifies.
+#[derive(Debug, Clone)]
+pub enum Identifier {
+    Registration(EntityResult),
+    Address(Icao24),
+}
+
+/// An identifier found by `Parser::scan`.
+#[derive(Debug, Clone)]
+pub struct ScanMatch {
+    /// Byte range of the identifier in the scanned text
+    pub span: Range<usize>,
+    pub identifier: Identifier,
+}
+
+fn is_dash(c: char) -> bool {
+    matches!(c, '-' | '–' | '—' | '−')
+}
+
+/// An ICAO address and a registration found on the same line.
+#[derive(Debug, Clone)]
+pub struct Pair {
+    /// Index of the line, from 0
+    pub line: usize,
+    pub address: Icao24,
+    /// Byte range of the address in the line
+    pub address_span: Range<usize>,
+    pub registration: EntityResult,
+    /// Byte range of the registration in the line
+    pub registration_span: Range<usize>,
+    /// Whether the address is allocated to another state than the
+    /// registration's. Organizations and special-use blocks never disagree.
+    pub disagrees: bool,
+}
+
+/// Byte ranges of the words of `text`, without leading and trailing dashes.
+fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    text.split(|c: char| !c.is_ascii_alphanumeric() && !is_dash(c)).filter_map(move |word| {
+        let trimmed = word.trim_matches(is_dash);
+        // `trimmed` is a slice of `text`
+        let start = trimmed.as_ptr() as usize - text.as_ptr() as usize;
+        (!trimmed.is_empty()).then_some(start..start + trimmed.len())
+    })
+}
+
+/// The allocated address a word spells, if it is six hex digits with a digit.
+fn address(word: &str) -> Option<Icao24> {
+    let hex = word.len() == 6 && word.bytes().all(|byte| byte.is_ascii_hexdigit());
+    if !hex || !word.bytes().any(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let address = Icao24::parse_hex(word).ok()?;
+    icao::lookup(address.value()).map(|_| address)
+}
+
+impl Parser {
+    /// The registration a word spells, among dataset rows whose suffix blocks
+    /// hold its suffix.
+    fn registration_in_text(&self, word: &str) -> Option<EntityResult> {
+        let capitals = word.bytes().any(|byte| byte.is_ascii_uppercase()) && !word.bytes().any(|byte| byte.is_ascii_lowercase());
+        if !capitals {
+            return None;
+        }
+        let candidates = self.candidates_where(word, false, |input, prefix, data| {
+            let suffix = input[prefix.len()..].trim_start_matches('-');
+            marks::suffix_fits(&data.suffixes, suffix)
+        });
+        candidates.into_iter().next().map(|candidate| candidate.entity)
+    }
+
+    /// Every registration and ICAO address in `text`, in order of appearance.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::scan::Identifier;
+    /// use flydent::{EntityResult, Parser};
+    ///
+    /// let text = "PH-BHA and N8437D (hex ab8e4f) held at FL350 until 1200Z.";
+    /// let found = Parser::new().scan(text);
+    /// assert_eq!(found.len(), 3);
+    /// assert_eq!(&text[found[0].span.clone()], "PH-BHA");
+    /// assert!(matches!(&found[1].identifier, Identifier::Registration(EntityResult::Country { iso2, .. }) if iso2 == "US"));
+    /// match &found[2].identifier {
+    ///     Identifier::Address(address) => assert_eq!(address.country(), Some("US")),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn scan(&self, text: &str) -> Vec<ScanMatch> {
+        words(text)
+            .filter_map(|span| {
+                let word = &text[span.clone()];
+                // Addresses may be all digits, registrations never are
+                let registration = || {
+                    word.bytes().any(|byte| byte.is_ascii_alphabetic()).then(|| self.registration_in_text(word)).flatten()
+                };
+                let dashed = || word.contains(is_dash).then(registration).flatten();
+                let undashed = || (!word.contains(is_dash)).then(registration).flatten();
+                let identifier = dashed()
+                    .map(Identifier::Registration)
+                    .or_else(|| address(word).map(Identifier::Address))
+                    .or_else(|| undashed().map(Identifier::Registration))?;
+                Some(ScanMatch { span, identifier })
+            })
+            .collect()
+    }
+
+    /// The first ICAO address and the first registration of each line of
+    /// `log` that has both, with a flag for lines where their states differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use flydent::Parser;
+    ///
+    /// let log = "12:00:01 hex=3c65a1 reg=D-AIMA alt=37000\n\
+    ///            12:00:02 hex=ab8e4f reg=G-EUPT alt=12000\n\
+    ///            12:00:03 hex=4ca2d6 flight=RYR1234\n";
+    /// let pairs = Parser::new().extract_pairs(log);
+    /// assert_eq!(pairs.len(), 2);
+    /// assert!(!pairs[0].disagrees);
+    /// // A US address on a British registration
+    /// assert!(pairs[1].disagrees);
+    /// assert_eq!(pairs[1].line, 1);
+    /// ```
+    pub fn extract_pairs(&self, log: &str) -> Vec<Pair> {
+        log.lines().enumerate().filter_map(|(line, text)| self.extract_pair(line, text)).collect()
+    }
+
+    fn extract_pair(&self, line: usize, text: &str) -> Option<Pair> {
+        let found = self.scan(text);
+        let (address, address_span) = found.iter().find_map(|found| match &found.identifier {
+            Identifier::Address(address) => Some((*address, found.span.clone())),
+            Identifier::Registration(_) => None,
+        })?;
+        let (registration, registration_span) = found.into_iter().find_map(|found| match found.identifier {
+            Identifier::Registration(entity) => Some((entity, found.span)),
+            Identifier::Address(_) => None,
+        })?;
+
+        let disagrees = match (&registration, address.country()) {
+            (EntityResult::Country { iso2, .. }, Some(country)) => iso2 != country,
+            _ => false,
+        };
+        Some(Pair { line, address, address_span, registration, registration_span, disagrees })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn found(text: &str) -> Vec<&str> {
+        Parser::new().scan(text).into_iter().map(|found| &text[found.span]).collect()
+    }
+
+    #[test]
+    fn word_spans() {
+        let text = "--PH-BHA, (N8437D)-- x";
+        assert_eq!(words(text).next(), Some(2..8));
+        let spans: Vec<&str> = words(text).map(|span| &text[span]).collect();
+        assert_eq!(spans, ["PH-BHA", "N8437D", "x"]);
+        assert_eq!(words("").count(), 0);
+        assert_eq!(words(" - -- ").count(), 0);
+    }
+
+    #[test]
+    fn addresses() {
+        assert_eq!(address("3c65a1"), Icao24::new(0x3C65A1));
+        assert_eq!(address("ACCEDE"), None);
+        assert_eq!(address("3C65A"), None);
+        // Unallocated
+        assert_eq!(address("FFFFF0"), None);
+    }
+
+    #[test]
+    fn all_digit_addresses() {
+        assert_eq!(found("HEX 484123"), ["484123"]);
+        assert_eq!(found("400000 at 1200 on 20240101"), ["400000"]);
+        match &Parser::new().scan("484123")[0].identifier {
+            Identifier::Address(address) => assert_eq!(address.country(), Some("NL")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn notam() {
+        let text = "A1234/24 NOTAMN Q) EHAA/QFALC/IV/NBO/A/000/999 E) ACFT D-AIMA AND G-EUPT \
+                    DIVERTED TO RWY 09. HEX 3C65A1 SQUAWK 7700.";
+        assert_eq!(found(text), ["D-AIMA", "G-EUPT", "3C65A1"]);
+    }
+
+    #[test]
+    fn registrations_without_dashes() {
+        assert_eq!(found("spotted N8437D, JA1234 and VPBAA at 0800"), ["N8437D", "JA1234", "VPBAA"]);
+        // Words and lowercase text are not registrations
+        assert!(found("The aircraft landed at FL350 ok").is_empty());
+        assert!(found("ph-bha").is_empty());
+
+        let scanned = Parser::new().scan("VPBAA");
+        match &scanned[0].identifier {
+            Identifier::Registration(EntityResult::Country { iso2, canonical_callsign, .. }) => {
+                assert_eq!((iso2.as_str(), canonical_callsign.as_str()), ("BM", "VP-BAA"))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pairs_from_log_formats() {
+        let parser = Parser::new();
+        let log = r#"{"hex":"3c65a1","type":"adsb_icao","flight":"DLH4AB  ","r":"D-AIMA","t":"A388"},
+{"hex":"~2a0001","type":"tisb_trackfile"},
+MSG,3,1,1,AB8E4F,1,2024/01/01,12:00:00.000,,,N8437D,37000
+2024-01-01T12:00:00Z icao=406B87 registration=VP-BAA
+hex=484123 reg=PH-BHA
+"#;
+        let pairs = parser.extract_pairs(log);
+        assert_eq!(pairs.iter().map(|pair| pair.line).collect::<Vec<_>>(), [0, 2, 3, 4]);
+        assert_eq!(&log.lines().next().unwrap()[pairs[0].registration_span.clone()], "D-AIMA");
+        assert_eq!(pairs[0].address, Icao24::new(0x3C65A1).unwrap());
+        assert!(!pairs[0].disagrees);
+        assert!(!pairs[1].disagrees);
+        // A British address on a Bermudian registration
+        assert!(pairs[2].disagrees);
+        // An address of digits only
+        assert_eq!(pairs[3].address, Icao24::new(0x484123).unwrap());
+        assert!(!pairs[3].disagrees);
+    }
+
+    #[test]
+    fn organizations_never_disagree() {
+        let pairs = Parser::new().extract_pairs("3C65A1 4Y-ABC\nF00000 D-AIMA");
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.iter().all(|pair| !pair.disagrees));
+    }
+
+    #[test]
+    fn spans_are_byte_offsets() {
+        let text = String::from("Überführung — PH–BHA—");
+        assert_eq!(found(&text), ["PH–BHA"]);
+        assert_eq!(Parser::new().scan(&text)[0].span, 18..26);
+    }
+}
@@ -0,0 +1,154 @@
+//! HTTP Lookup Service
+//!
+//! A small JSON API over the parser and converters, for running flydent as a
+//! sidecar next to services written in other languages. Enabled with the
+//! `server` feature; the `flydent-server` binary serves `router()`.
+//!
+//! - `GET /v1/registration/{reg}`: country or organization of a registration
+//! - `GET /v1/hex/{hex}`: allocation block and registration of an ICAO address
+//! - `GET /v1/convert/{value}`: registration for an address or address for a
+//!   registration
+//!
+//! Successful lookups answer 200. Input that cannot be identified answers 404,
+//! malformed input 400, both with an `error` member.
+
+use crate::icao::{self, Icao24};
+use crate::registration::{self, ConversionError};
+use crate::{EntityResult, Parser};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+
+type Response = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, input: &str, message: impl ToString) -> Response {
+    (status, Json(json!({ "input": input, "error": message.to_string() })))
+}
+
+fn entity_json(entity: &EntityResult) -> Value {
+    match entity {
+        EntityResult::Country { nation, description, iso2, iso3, canonical_callsign } => json!({
+            "nation": nation,
+            "description": description,
+            "iso2": iso2,
+            "iso3": iso3,
+            "canonical_callsign": canonical_callsign,
+        }),
+        EntityResult::Organization { name, description, canonical_callsign } => json!({
+            "name": name,
+            "description": description,
+            "canonical_callsign": canonical_callsign,
+        }),
+    }
+}
+
+async fn registration(Path(reg): Path<String>) -> Response {
+    match Parser::new().parse_simple(&reg) {
+        Some(entity) => (StatusCode::OK, Json(json!({ "input": reg, "entity": entity_json(&entity) }))),
+        None => error(StatusCode::NOT_FOUND, &reg, "unknown registration"),
+    }
+}
+
+async fn hex(Path(hex): Path<String>) -> Response {
+    let icao = match Icao24::parse_hex(&hex) {
+        Ok(icao) => icao,
+        Err(err) => return error(StatusCode::BAD_REQUEST, &hex, err),
+    };
+    let Some(allocation) = icao::lookup(icao.value()) else {
+        return error(StatusCode::NOT_FOUND, &hex, "address is not allocated");
+    };
+    let registration = registration::icao_to_registration(icao.to_bytes()).ok();
+    let body = json!({
+        "input": hex,
+        "hex": icao.to_string(),
+        "allocation": {
+            "iso2": allocation.iso2,
+            "name": allocation.name,
+            "special_use": allocation.special_use.map(|special_use| special_use.description()),
+            "start": allocation.start_hex(),
+            "end": allocation.end_hex(),
+        },
+        "registration": registration,
+    });
+    (StatusCode::OK, Json(body))
+}
+
+async fn convert(Path(value): Path<String>) -> Response {
+    match registration::convert(&value) {
+        Ok(converted) => {
+            let body = json!({
+                "input": value,
+                "hex": converted.icao.to_string(),
+                "registration": converted.registration,
+                "iso2": converted.iso2,
+                "scheme": converted.scheme,
+            });
+            (StatusCode::OK, Json(body))
+        }
+        Err(err @ ConversionError::InvalidFormat { .. }) | Err(err @ ConversionError::InvalidNNumber(_)) => {
+            error(StatusCode::BAD_REQUEST, &value, err)
+        }
+        Err(err) => error(StatusCode::NOT_FOUND, &value, err),
+    }
+}
+
+/// The service's routes, for mounting into an existing axum application.
+pub fn router() -> Router {
+    Router::new()
+        .route("/v1/registration/{reg}", get(registration))
+        .route("/v1/hex/{hex}", get(hex))
+        .route("/v1/convert/{value}", get(convert))
+}
+
+/// Serve `router()` on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn registration_lookup() {
+        let (status, Json(body)) = registration(Path("PH-BHA".to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["entity"]["iso2"], "NL");
+
+        let (status, Json(body)) = registration(Path("ZZZZZZZ".to_string())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn hex_lookup() {
+        let (status, Json(body)) = hex(Path("ab8e4f".to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["hex"], "AB8E4F");
+        assert_eq!(body["allocation"]["iso2"], "US");
+        assert_eq!(body["registration"], "N8437D");
+
+        let (status, _) = hex(Path("XYZ".to_string())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn conversion() {
+        let (status, Json(body)) = convert(Path("D-AIMA".to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["hex"], "3C65A1");
+
+        let (status, _) = convert(Path("G-EUPT".to_string())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn routes_are_valid() {
+        // axum panics on malformed route paths
+        let _router = router();
+    }
+}
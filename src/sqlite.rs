@@ -0,0 +1,112 @@
+//! SQLite Extension
+//!
+//! A loadable SQLite extension adding SQL functions for enriching aircraft
+//! tables in place. Enabled with the `sqlite` feature; build a shared library
+//! with `cargo rustc --lib --features sqlite --crate-type cdylib` and load it
+//! with `.load ./libflydent` in the `sqlite3` shell, or
+//! `SELECT load_extension('./libflydent')`:
+//!
+//! ```sql
+//! SELECT flydent_country('3C65A1');      -- 'DE'
+//! SELECT flydent_registration('3C65A1'); -- 'D-AIMA'
+//! SELECT flydent_parse('PH-BHA');        -- 'NL'
+//! ```
+//!
+//! - `flydent_country(hex)`: ISO2 code of the state an ICAO address is
+//!   allocated to
+//! - `flydent_registration(hex)`: registration derived from an ICAO address,
+//!   for countries with an algorithmic mapping
+//! - `flydent_parse(registration)`: ISO2 code of the country of a
+//!   registration, or the name of the international organization holding it
+//!
+//! Hex addresses are accepted in any notation `Icao24::parse_hex` accepts.
+//! Each function returns `NULL` for `NULL` input and for input that cannot be
+//! identified or converted.
+
+use crate::icao::{self, Icao24};
+use crate::registration;
+use crate::{EntityResult, Parser};
+use once_cell::sync::Lazy;
+use rusqlite::ffi;
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::{Connection, Result};
+use std::ffi::{c_char, c_int};
+
+static PARSER: Lazy<Parser> = Lazy::new(Parser::new);
+
+fn country_of_hex(hex: &str) -> Option<&'static str> {
+    icao::icao_u32_to_country(Icao24::parse_hex(hex).ok()?.value())
+}
+
+fn registration_of_hex(hex: &str) -> Option<String> {
+    let icao = Icao24::parse_hex(hex).ok()?;
+    registration::icao_to_registration(icao.to_bytes()).ok()
+}
+
+fn parse_registration(registration: &str) -> Option<String> {
+    match PARSER.parse_simple(registration)? {
+        EntityResult::Country { iso2, .. } => Some(iso2),
+        EntityResult::Organization { name, .. } => Some(name),
+    }
+}
+
+/// Apply `lookup` to the function's only argument, which may be `NULL`.
+fn text_function<T>(ctx: &Context<'_>, lookup: impl Fn(&str) -> Option<T>) -> Result<Option<T>> {
+    let value: Option<String> = ctx.get(0)?;
+    Ok(value.as_deref().and_then(lookup))
+}
+
+/// Register the flydent functions on `db`.
+pub fn register_functions(db: &Connection) -> Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC | FunctionFlags::SQLITE_INNOCUOUS;
+    db.create_scalar_function(c"flydent_country", 1, flags, |ctx| text_function(ctx, country_of_hex))?;
+    db.create_scalar_function(c"flydent_registration", 1, flags, |ctx| text_function(ctx, registration_of_hex))?;
+    db.create_scalar_function(c"flydent_parse", 1, flags, |ctx| text_function(ctx, parse_registration))?;
+    Ok(())
+}
+
+/// Entry point SQLite looks up when loading `libflydent`.
+///
+/// # Safety
+///
+/// Only SQLite may call this, with the arguments of an extension entry point.
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_flydent_init(
+    db: *mut ffi::sqlite3,
+    err_msg: *mut *mut c_char,
+    api: *mut ffi::sqlite3_api_routines,
+) -> c_int {
+    Connection::extension_init2(db, err_msg, api, |db| register_functions(&db).map(|()| false))
+}
+
+#[cfg(test)]
+mod tests {
+    // Calling SQLite needs a host that loaded the extension; these cover the
+    // lookups behind the functions.
+    use super::*;
+
+    #[test]
+    fn country_lookup() {
+        assert_eq!(country_of_hex("3C65A1"), Some("DE"));
+        assert_eq!(country_of_hex("0xab8e4f"), Some("US"));
+        assert_eq!(country_of_hex("F00000"), None);
+        assert_eq!(country_of_hex("nonsense"), None);
+    }
+
+    #[test]
+    fn registration_lookup() {
+        assert_eq!(registration_of_hex("3c65a1").as_deref(), Some("D-AIMA"));
+        assert_eq!(registration_of_hex("AB8E4F").as_deref(), Some("N8437D"));
+        assert_eq!(registration_of_hex("400000"), None);
+    }
+
+    #[test]
+    fn parse_lookup() {
+        assert_eq!(parse_registration("PH-BHA").as_deref(), Some("NL"));
+        assert_eq!(
+            parse_registration("4Y-ABC").as_deref(),
+            Some("International Civil Aviation Organization")
+        );
+        assert_eq!(parse_registration("ZZZZZZZ"), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Mode A (Squawk) Codes
+//!
+//! Explains the special-purpose Mode A transponder codes: the worldwide
+//! emergency codes and the regional conspicuity and operational codes. Codes are
+//! written as usual, four octal digits, so `classify(7700)` classifies 7700.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// What a special-purpose squawk code announces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Meaning {
+    /// 7700: general emergency
+    Emergency,
+    /// 7600: radio communication failure
+    RadioFailure,
+    /// 7500: unlawful interference (hijack)
+    UnlawfulInterference,
+    /// VFR flight not assigned a discrete code (7000 or 1200 depending on region)
+    VfrConspicuity,
+    /// 2000: flight entering an SSR area without an assigned code
+    NoAssignedCode,
+    /// 1000: Mode S flight identified by its aircraft identification instead of a
+    /// discrete code
+    ModeSConspicuity,
+    /// 7400: unmanned aircraft that lost its command and control link
+    LostLink,
+    /// 1277: VFR search and rescue flight
+    SearchAndRescue,
+    /// 1255: firefighting flight
+    Firefighting,
+    /// 4000: military flight operating in a restricted or warning area
+    MilitaryOperations,
+    /// 7777: military interceptor
+    MilitaryInterceptor,
+}
+
+impl Meaning {
+    /// Short human-readable description
+    pub fn description(&self) -> &'static str {
+        match self {
+            Meaning::Emergency => "emergency",
+            Meaning::RadioFailure => "radio failure",
+            Meaning::UnlawfulInterference => "unlawful interference",
+            Meaning::VfrConspicuity => "VFR conspicuity",
+            Meaning::NoAssignedCode => "no code assigned",
+            Meaning::ModeSConspicuity => "Mode S conspicuity",
+            Meaning::LostLink => "unmanned aircraft lost link",
+            Meaning::SearchAndRescue => "search and rescue",
+            Meaning::Firefighting => "firefighting",
+            Meaning::MilitaryOperations => "military operations",
+            Meaning::MilitaryInterceptor => "military interceptor",
+        }
+    }
+
+    /// Whether the code declares an emergency (7500, 7600 or 7700)
+    pub fn is_emergency(&self) -> bool {
+        matches!(self, Meaning::Emergency | Meaning::RadioFailure | Meaning::UnlawfulInterference)
+    }
+}
+
+impl fmt::Display for Meaning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// Airspace in which a code has its meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// ICAO worldwide assignment
+    Worldwide,
+    /// European (EUROCONTROL) airspace
+    Europe,
+    UnitedStates,
+    Canada,
+    Australia,
+}
+
+/// One assignment of a squawk code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assignment {
+    /// The code, e.g. `7700`
+    pub code: u16,
+    /// What the code announces
+    pub meaning: Meaning,
+    /// Where the assignment applies
+    pub region: Region,
+}
+
+const fn assign(code: u16, meaning: Meaning, region: Region) -> Assignment {
+    Assignment { code, meaning, region }
+}
+
+/// Special-purpose code assignments, ordered by code.
+const ASSIGNMENTS: &[Assignment] = &[
+    assign(1000, Meaning::ModeSConspicuity, Region::Europe),
+    assign(1200, Meaning::VfrConspicuity, Region::UnitedStates),
+    assign(1200, Meaning::VfrConspicuity, Region::Canada),
+    assign(1200, Meaning::VfrConspicuity, Region::Australia),
+    assign(1255, Meaning::Firefighting, Region::UnitedStates),
+    assign(1277, Meaning::SearchAndRescue, Region::UnitedStates),
+    assign(2000, Meaning::NoAssignedCode, Region::Worldwide),
+    assign(4000, Meaning::MilitaryOperations, Region::UnitedStates),
+    assign(7000, Meaning::VfrConspicuity, Region::Europe),
+    assign(7400, Meaning::LostLink, Region::Europe),
+    assign(7500, Meaning::UnlawfulInterference, Region::Worldwide),
+    assign(7600, Meaning::RadioFailure, Region::Worldwide),
+    assign(7700, Meaning::Emergency, Region::Worldwide),
+    assign(7777, Meaning::MilitaryInterceptor, Region::UnitedStates),
+];
+
+/// Whether `code` is a valid squawk: four digits, each 0-7.
+pub fn is_valid(code: u16) -> bool {
+    code <= 7777 && [code / 1000, code / 100 % 10, code / 10 % 10, code % 10].iter().all(|&d| d <= 7)
+}
+
+/// Parse a squawk written as four octal digits, e.g. `"0020"` or `"7700"`.
+pub fn parse(code: &str) -> Option<u16> {
+    let code = code.trim();
+    if code.len() != 4 || !code.chars().all(|c| ('0'..='7').contains(&c)) {
+        return None;
+    }
+    code.parse().ok()
+}
+
+/// List the special-purpose assignments of a squawk code, in every region where
+/// it has one. Discrete codes and invalid codes have none.
+///
+/// # Examples
+/// ```
+/// use flydent::squawk::{classify, Meaning, Region};
+///
+/// let emergency = classify(7700);
+/// assert_eq!(emergency[0].meaning, Meaning::Emergency);
+/// assert_eq!(emergency[0].region, Region::Worldwide);
+///
+/// // VFR conspicuity differs by region
+/// assert_eq!(classify(7000)[0].region, Region::Europe);
+/// assert!(classify(1200).iter().any(|a| a.region == Region::UnitedStates));
+///
+/// assert!(classify(4521).is_empty());
+/// ```
+pub fn classify(code: u16) -> Vec<Assignment> {
+    ASSIGNMENTS.iter().filter(|assignment| assignment.code == code).copied().collect()
+}
+
+/// The meaning of a squawk code in `region`, taking worldwide assignments into
+/// account.
+pub fn classify_in(code: u16, region: Region) -> Option<Meaning> {
+    ASSIGNMENTS
+        .iter()
+        .find(|a| a.code == code && (a.region == region || a.region == Region::Worldwide))
+        .map(|a| a.meaning)
+}
+
+/// Whether the code declares an emergency (7500, 7600 or 7700).
+pub fn is_emergency(code: u16) -> bool {
+    classify(code).iter().any(|assignment| assignment.meaning.is_emergency())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assignments_are_valid_and_ordered() {
+        assert!(ASSIGNMENTS.windows(2).all(|pair| pair[0].code <= pair[1].code));
+        for assignment in ASSIGNMENTS {
+            assert!(is_valid(assignment.code), "{}", assignment.code);
+        }
+    }
+
+    #[test]
+    fn emergency_codes() {
+        for code in [7500, 7600, 7700] {
+            assert!(is_emergency(code), "{}", code);
+        }
+        assert!(!is_emergency(7000));
+        assert!(!is_emergency(1200));
+        assert_eq!(classify(7500)[0].meaning.to_string(), "unlawful interference");
+    }
+
+    #[test]
+    fn regional_codes() {
+        assert_eq!(classify_in(7000, Region::Europe), Some(Meaning::VfrConspicuity));
+        assert_eq!(classify_in(7000, Region::UnitedStates), None);
+        assert_eq!(classify_in(1200, Region::Australia), Some(Meaning::VfrConspicuity));
+        assert_eq!(classify_in(7700, Region::Canada), Some(Meaning::Emergency));
+        assert_eq!(classify(1200).len(), 3);
+    }
+
+    #[test]
+    fn validity_and_parsing() {
+        assert!(is_valid(0));
+        assert!(is_valid(7777));
+        assert!(!is_valid(7778));
+        assert!(!is_valid(1890));
+        assert!(!is_valid(10000));
+        assert_eq!(parse("0020"), Some(20));
+        assert_eq!(parse(" 7700 "), Some(7700));
+        assert_eq!(parse("7800"), None);
+        assert_eq!(parse("770"), None);
+        assert!(classify(7800).is_empty());
+    }
+}
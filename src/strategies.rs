@@ -0,0 +1,147 @@
+//! Proptest strategies for flydent identifiers
+//!
+//! Requires the `proptest` feature. The strategies generate realistic values so
+//! downstream crates can property-test code that consumes ICAO addresses and
+//! registrations.
+
+use crate::icao::{self, Icao24};
+use crate::registration::{self, US_BASE, US_MAX};
+use proptest::arbitrary::Arbitrary;
+use proptest::prelude::*;
+use proptest::strategy::{BoxedStrategy, Union};
+
+/// Any 24-bit ICAO address, allocated or not.
+pub fn icao24() -> impl Strategy<Value = Icao24> {
+    (0u32..=0xFFFFFF).prop_map(|address| Icao24::new(address).unwrap())
+}
+
+/// Addresses allocated to a country, uniformly distributed over its ranges.
+///
+/// Returns `None` if the country has no allocation.
+///
+/// # Examples
+/// ```
+/// use flydent::strategies::allocated_icao24;
+/// use proptest::strategy::{Strategy, ValueTree};
+/// use proptest::test_runner::TestRunner;
+///
+/// let strategy = allocated_icao24("DE").unwrap();
+/// let address = strategy.new_tree(&mut TestRunner::default()).unwrap().current();
+/// assert_eq!(address.country(), Some("DE"));
+/// ```
+pub fn allocated_icao24(iso2: &str) -> Option<BoxedStrategy<Icao24>> {
+    let total = icao::allocation_size(iso2);
+    if total == 0 {
+        return None;
+    }
+
+    let iso2 = iso2.to_ascii_uppercase();
+    let strategy = (0..total).prop_map(move |offset| {
+        // The offset is below the allocation size, so the address always exists
+        Icao24::new(icao::nth_address(&iso2, offset).unwrap()).unwrap()
+    });
+    Some(strategy.boxed())
+}
+
+/// Valid US N-numbers, from `N1` to `N99999`.
+pub fn n_number() -> impl Strategy<Value = String> {
+    (US_BASE + 1..=US_MAX).prop_map(|address| {
+        // Every address in this range encodes exactly one N-number
+        let icao = Icao24::new(address).unwrap();
+        registration::icao_to_registration(icao.to_bytes()).unwrap()
+    })
+}
+
+/// Registrations matching a country's strict ITU patterns, such as `"D-ABCD"`.
+///
+/// Returns `None` if the dataset has no registration pattern for the country.
+///
+/// # Examples
+/// ```
+/// use flydent::strategies::registration;
+/// use flydent::{EntityResult, Parser};
+/// use proptest::strategy::{Strategy, ValueTree};
+/// use proptest::test_runner::TestRunner;
+///
+/// let strategy = registration("DE").unwrap();
+/// let reg = strategy.new_tree(&mut TestRunner::default()).unwrap().current();
+/// // Germany also holds the former East German Y2-Y9 series
+/// match Parser::new().parse(&reg, true, false) {
+///     Some(EntityResult::Country { iso2, .. }) => assert_eq!(iso2, "DE"),
+///     other => panic!("{} parsed as {:?}", reg, other),
+/// }
+/// ```
+pub fn registration(iso2: &str) -> Option<BoxedStrategy<String>> {
+    let strategies: Vec<BoxedStrategy<String>> = crate::registration_patterns(iso2)
+        .into_iter()
+        .filter_map(|pattern| {
+            // Generated strings are whole registrations, so the anchors are implicit
+            let body = pattern.trim_start_matches('^').trim_end_matches('$');
+            proptest::string::string_regex(body).ok().map(Strategy::boxed)
+        })
+        .collect();
+
+    if strategies.is_empty() {
+        None
+    } else {
+        Some(Union::new(strategies).boxed())
+    }
+}
+
+impl Arbitrary for Icao24 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Icao24>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        icao24().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityResult, Parser};
+    use regex::Regex;
+
+    proptest! {
+        #[test]
+        fn icao24_is_24_bit(address in icao24()) {
+            prop_assert!(address.value() <= 0xFFFFFF);
+        }
+
+        #[test]
+        fn allocated_icao24_stays_in_country(address in allocated_icao24("CN").unwrap()) {
+            // Hong Kong and Macao are carved out of the China block
+            prop_assert_eq!(address.country(), Some("CN"));
+        }
+
+        #[test]
+        fn n_numbers_round_trip(reg in n_number()) {
+            let icao = registration::registration_to_icao(&reg).unwrap();
+            prop_assert_eq!(registration::icao_to_registration(icao).unwrap(), reg);
+        }
+
+        #[test]
+        fn registrations_match_strict_patterns(reg in registration("AF").unwrap()) {
+            let matched = crate::registration_patterns("AF")
+                .iter()
+                .any(|pattern| Regex::new(pattern).unwrap().is_match(&reg));
+            prop_assert!(matched, "{} matches no AF pattern", reg);
+            match Parser::new().parse(&reg, true, false) {
+                Some(EntityResult::Country { iso2, .. }) => prop_assert_eq!(iso2, "AF"),
+                other => prop_assert!(false, "{} parsed as {:?}", reg, other),
+            }
+        }
+
+        #[test]
+        fn arbitrary_icao24_formats_as_hex(address in any::<Icao24>()) {
+            prop_assert_eq!(Icao24::parse_hex(&address.to_string()), Ok(address));
+        }
+    }
+
+    #[test]
+    fn unknown_country_has_no_strategy() {
+        assert!(allocated_icao24("XX").is_none());
+        assert!(registration("XX").is_none());
+    }
+}
@@ -0,0 +1,208 @@
+//! Aircraft Type Designators
+//!
+//! Maps ICAO Doc 8643 type designators such as "B38M" or "PC12" to the
+//! manufacturer, model, aircraft description (class, engine count and engine
+//! type) and wake turbulence category. The table is generated by `build.rs` from
+//! `data/aircraft-types.csv`.
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+/// Kind of aircraft, the first letter of a Doc 8643 description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AircraftClass {
+    /// `L`
+    LandPlane,
+    /// `S`
+    SeaPlane,
+    /// `A`
+    Amphibian,
+    /// `H`
+    Helicopter,
+    /// `G`
+    Gyrocopter,
+    /// `T`
+    Tiltrotor,
+}
+
+impl AircraftClass {
+    /// The description letter, e.g. `'L'` for a landplane
+    pub fn code(&self) -> char {
+        match self {
+            AircraftClass::LandPlane => 'L',
+            AircraftClass::SeaPlane => 'S',
+            AircraftClass::Amphibian => 'A',
+            AircraftClass::Helicopter => 'H',
+            AircraftClass::Gyrocopter => 'G',
+            AircraftClass::Tiltrotor => 'T',
+        }
+    }
+}
+
+/// Engine type, the last letter of a Doc 8643 description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineType {
+    /// `J`
+    Jet,
+    /// `T`: turboprop or turboshaft
+    Turboprop,
+    /// `P`
+    Piston,
+    /// `E`
+    Electric,
+    /// `R`
+    Rocket,
+}
+
+impl EngineType {
+    /// The description letter, e.g. `'J'` for a jet
+    pub fn code(&self) -> char {
+        match self {
+            EngineType::Jet => 'J',
+            EngineType::Turboprop => 'T',
+            EngineType::Piston => 'P',
+            EngineType::Electric => 'E',
+            EngineType::Rocket => 'R',
+        }
+    }
+}
+
+/// ICAO wake turbulence category, by maximum certificated take-off mass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WakeCategory {
+    /// `L`: 7 000 kg or less
+    Light,
+    /// `M`: between 7 000 kg and 136 000 kg
+    Medium,
+    /// `H`: 136 000 kg or more
+    Heavy,
+    /// `J`: super, the Airbus A380
+    Super,
+}
+
+impl WakeCategory {
+    /// The category letter, e.g. `'M'` for medium
+    pub fn code(&self) -> char {
+        match self {
+            WakeCategory::Light => 'L',
+            WakeCategory::Medium => 'M',
+            WakeCategory::Heavy => 'H',
+            WakeCategory::Super => 'J',
+        }
+    }
+}
+
+impl fmt::Display for WakeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// An aircraft type and its Doc 8643 description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AircraftType {
+    /// ICAO type designator, e.g. `"B38M"`
+    pub designator: &'static str,
+    /// Manufacturer as listed in Doc 8643, e.g. `"BOEING"`
+    pub manufacturer: &'static str,
+    /// Model name, e.g. `"737 MAX 8"`
+    pub model: &'static str,
+    /// Kind of aircraft
+    pub class: AircraftClass,
+    /// Number of engines
+    pub engine_count: u8,
+    /// Engine type
+    pub engine_type: EngineType,
+    /// Wake turbulence category
+    pub wake: WakeCategory,
+}
+
+impl AircraftType {
+    /// The Doc 8643 description code, e.g. `"L2J"` for a twin-engine landplane jet.
+    pub fn description(&self) -> String {
+        format!("{}{}{}", self.class.code(), self.engine_count, self.engine_type.code())
+    }
+
+    /// The description and wake turbulence category as shown in Doc 8643 and on
+    /// flight plans, e.g. `"L2J/M"`.
+    pub fn category(&self) -> String {
+        format!("{}/{}", self.description(), self.wake)
+    }
+}
+
+impl fmt::Display for AircraftType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.manufacturer, self.model)
+    }
+}
+
+// Aircraft types generated by `build.rs` from `data/aircraft-types.csv`, sorted
+// by designator.
+include!(concat!(env!("OUT_DIR"), "/aircraft_types.rs"));
+
+/// Look up an aircraft type by its ICAO type designator (case-insensitive).
+///
+/// # Examples
+/// ```
+/// use flydent::types::{lookup, AircraftClass, EngineType, WakeCategory};
+///
+/// let b38m = lookup("B38M").unwrap();
+/// assert_eq!(b38m.manufacturer, "BOEING");
+/// assert_eq!(b38m.model, "737 MAX 8");
+/// assert_eq!(b38m.class, AircraftClass::LandPlane);
+/// assert_eq!((b38m.engine_count, b38m.engine_type), (2, EngineType::Jet));
+/// assert_eq!(b38m.description(), "L2J");
+/// assert_eq!(b38m.wake, WakeCategory::Medium);
+/// assert_eq!(b38m.category(), "L2J/M");
+/// assert!(lookup("ZZZZ").is_none());
+/// ```
+pub fn lookup(designator: &str) -> Option<&'static AircraftType> {
+    let designator = designator.trim().to_ascii_uppercase();
+    AIRCRAFT_TYPES
+        .binary_search_by(|aircraft_type| aircraft_type.designator.cmp(&designator))
+        .ok()
+        .map(|idx| &AIRCRAFT_TYPES[idx])
+}
+
+/// All aircraft types in the table, sorted by designator.
+pub fn aircraft_types() -> &'static [AircraftType] {
+    AIRCRAFT_TYPES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_and_looked_up() {
+        assert!(AIRCRAFT_TYPES.windows(2).all(|pair| pair[0].designator < pair[1].designator));
+        for aircraft_type in aircraft_types() {
+            assert_eq!(lookup(aircraft_type.designator), Some(aircraft_type));
+        }
+    }
+
+    #[test]
+    fn known_types() {
+        let pc12 = lookup("pc12").unwrap();
+        assert_eq!(pc12.to_string(), "PILATUS PC-12");
+        assert_eq!(pc12.description(), "L1T");
+
+        assert_eq!(lookup("A21N").unwrap().model, "A-321neo");
+        assert_eq!(lookup("A388").unwrap().engine_count, 4);
+        assert_eq!(lookup("R44").unwrap().class, AircraftClass::Helicopter);
+        assert_eq!(lookup("V22").unwrap().class, AircraftClass::Tiltrotor);
+        assert_eq!(lookup("CL2T").unwrap().description(), "A2T");
+        assert!(lookup("").is_none());
+    }
+
+    #[test]
+    fn wake_categories() {
+        assert_eq!(lookup("A388").unwrap().wake, WakeCategory::Super);
+        assert_eq!(lookup("B77W").unwrap().wake, WakeCategory::Heavy);
+        assert_eq!(lookup("B752").unwrap().category(), "L2J/M");
+        assert_eq!(lookup("C172").unwrap().category(), "L1P/L");
+        assert_eq!(lookup("R44").unwrap().category(), "H1P/L");
+        assert!(WakeCategory::Light < WakeCategory::Super);
+    }
+}
@@ -0,0 +1,118 @@
+//! Drone (UAS) Registrations
+//!
+//! Recognizes the registration numbers national authorities issue to unmanned
+//! aircraft and their operators:
+//! - US FAA small-UAS registrations: `FA` followed by 10 letters or digits
+//! - UK CAA operator IDs: `GBR-OP-` followed by 12 letters or digits
+//! - EASA operator registration numbers: the ISO 3166-1 alpha-3 code of an EASA
+//!   member state followed by 13 letters or digits (12 plus a checksum
+//!   character), as displayed on the aircraft without the private suffix
+
+use alloc::format;
+use alloc::string::String;
+
+/// A recognized drone registration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UasRegistration {
+    /// ISO 3166-1 alpha-2 code of the registering state
+    pub iso2: &'static str,
+    /// Name of the registration scheme, e.g. `"FAA small UAS"`
+    pub scheme: &'static str,
+    /// The registration in its canonical form
+    pub registration: String,
+}
+
+/// EASA member states as (ISO3, ISO2) pairs, for operator registration numbers.
+const EASA_STATES: &[(&str, &str)] = &[
+    ("AUT", "AT"), ("BEL", "BE"), ("BGR", "BG"), ("CHE", "CH"), ("CYP", "CY"), ("CZE", "CZ"),
+    ("DEU", "DE"), ("DNK", "DK"), ("ESP", "ES"), ("EST", "EE"), ("FIN", "FI"), ("FRA", "FR"),
+    ("GRC", "GR"), ("HRV", "HR"), ("HUN", "HU"), ("IRL", "IE"), ("ISL", "IS"), ("ITA", "IT"),
+    ("LIE", "LI"), ("LTU", "LT"), ("LUX", "LU"), ("LVA", "LV"), ("MLT", "MT"), ("NLD", "NL"),
+    ("NOR", "NO"), ("POL", "PL"), ("PRT", "PT"), ("ROU", "RO"), ("SVK", "SK"), ("SVN", "SI"),
+    ("SWE", "SE"),
+];
+
+const FAA_SCHEME: &str = "FAA small UAS";
+const UK_SCHEME: &str = "UK CAA operator ID";
+const EASA_SCHEME: &str = "EASA operator registration";
+
+fn alphanumeric(s: &str, len: usize) -> bool {
+    s.len() == len && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Recognize a drone registration and the state that issued it.
+///
+/// Case and surrounding whitespace are ignored. FAA and UK registrations are
+/// returned uppercase; EASA numbers keep their country code uppercase and the
+/// rest lowercase, as printed by the issuing authorities.
+///
+/// # Examples
+/// ```
+/// use flydent::uas::parse_uas_registration;
+///
+/// let faa = parse_uas_registration("FA3A7B9C2D1E").unwrap();
+/// assert_eq!((faa.iso2, faa.scheme), ("US", "FAA small UAS"));
+///
+/// assert_eq!(parse_uas_registration("GBR-OP-ABCD12345678").unwrap().iso2, "GB");
+/// assert_eq!(parse_uas_registration("FIN87astrdge12k8").unwrap().iso2, "FI");
+/// assert!(parse_uas_registration("N8437D").is_none());
+/// ```
+pub fn parse_uas_registration(registration: &str) -> Option<UasRegistration> {
+    let registration = registration.trim();
+    let upper = registration.to_ascii_uppercase();
+
+    if let Some(rest) = upper.strip_prefix("GBR-OP-").filter(|rest| alphanumeric(rest, 12)) {
+        return Some(UasRegistration {
+            iso2: "GB",
+            scheme: UK_SCHEME,
+            registration: format!("GBR-OP-{}", rest),
+        });
+    }
+    if let Some(rest) = upper.strip_prefix("FA").filter(|rest| alphanumeric(rest, 10)) {
+        return Some(UasRegistration {
+            iso2: "US",
+            scheme: FAA_SCHEME,
+            registration: format!("FA{}", rest),
+        });
+    }
+
+    let (iso3, rest) = registration.split_at_checked(3)?;
+    let &(iso3, iso2) = EASA_STATES.iter().find(|(code, _)| code.eq_ignore_ascii_case(iso3))?;
+    alphanumeric(rest, 13).then(|| UasRegistration {
+        iso2,
+        scheme: EASA_SCHEME,
+        registration: format!("{}{}", iso3, rest.to_ascii_lowercase()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easa_states_are_sorted_and_unique() {
+        assert!(EASA_STATES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn uas_registrations() {
+        let cases = [
+            ("FA3A7B9C2D1E", "US", "FAA small UAS", "FA3A7B9C2D1E"),
+            (" fa3a7b9c2d1e ", "US", "FAA small UAS", "FA3A7B9C2D1E"),
+            ("gbr-op-abcd12345678", "GB", "UK CAA operator ID", "GBR-OP-ABCD12345678"),
+            ("FIN87astrdge12k8", "FI", "EASA operator registration", "FIN87astrdge12k8"),
+            ("deu1234ABCD5678x", "DE", "EASA operator registration", "DEU1234abcd5678x"),
+            ("CHE0000000000000", "CH", "EASA operator registration", "CHE0000000000000"),
+        ];
+        for (input, iso2, scheme, canonical) in cases {
+            let parsed = parse_uas_registration(input).unwrap_or_else(|| panic!("{}", input));
+            assert_eq!(parsed.iso2, iso2, "{}", input);
+            assert_eq!(parsed.scheme, scheme, "{}", input);
+            assert_eq!(parsed.registration, canonical, "{}", input);
+        }
+
+        for input in ["", "FA123", "FA3A7B9C2D1EEX", "FA3A7B9C-D1", "GBR-OP-ABC", "USA87astrdge12k8", "FIN87astrdge12k", "N8437D"] {
+            assert!(parse_uas_registration(input).is_none(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! WebAssembly Bindings
+//!
+//! JavaScript exports for browser front-ends such as virtual radar displays,
+//! which get offline lookups from the embedded tables without shipping the CSVs
+//! separately. Enabled with the `wasm` feature; build the module with
+//! `cargo rustc --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib`
+//! and generate the JavaScript glue with `wasm-bindgen`.
+//!
+//! - `parse(value, strict?, icao24bit?)`: country or organization object
+//! - `hexToCountry(hex)`: allocation block of an ICAO address
+//! - `registrationToHex(registration)`: six-digit hex address
+//!
+//! Each returns `undefined` when the input cannot be identified. Objects use
+//! the same members as the CLI's JSON output.
+
+use crate::icao::{self, Icao24};
+use crate::registration;
+use crate::{EntityResult, Parser};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+fn entity_json(entity: &EntityResult) -> Value {
+    match entity {
+        EntityResult::Country { nation, description, iso2, iso3, canonical_callsign } => json!({
+            "nation": nation,
+            "description": description,
+            "iso2": iso2,
+            "iso3": iso3,
+            "canonical_callsign": canonical_callsign,
+        }),
+        EntityResult::Organization { name, description, canonical_callsign } => json!({
+            "name": name,
+            "description": description,
+            "canonical_callsign": canonical_callsign,
+        }),
+    }
+}
+
+fn parse_json(value: &str, strict: bool, icao24bit: bool) -> Option<Value> {
+    Parser::new().parse(value, strict, icao24bit).map(|entity| entity_json(&entity))
+}
+
+fn hex_to_country_json(hex: &str) -> Option<Value> {
+    let icao = Icao24::parse_hex(hex).ok()?;
+    let allocation = icao::lookup(icao.value())?;
+    Some(json!({
+        "hex": icao.to_string(),
+        "iso2": allocation.iso2,
+        "name": allocation.name,
+        "special_use": allocation.special_use.map(|special_use| special_use.description()),
+        "start": allocation.start_hex(),
+        "end": allocation.end_hex(),
+    }))
+}
+
+fn registration_to_hex_string(registration: &str) -> Option<String> {
+    let bytes = registration::registration_to_icao(registration).ok()?;
+    Some(Icao24::from_bytes(bytes).to_string())
+}
+
+fn to_js(value: Option<Value>) -> JsValue {
+    match value {
+        // serde_json only produces valid JSON
+        Some(value) => js_sys::JSON::parse(&value.to_string()).unwrap_or(JsValue::UNDEFINED),
+        None => JsValue::UNDEFINED,
+    }
+}
+
+/// Country or organization of a registration, or of a hex address with
+/// `icao24bit`.
+#[wasm_bindgen]
+pub fn parse(value: &str, strict: Option<bool>, icao24bit: Option<bool>) -> JsValue {
+    to_js(parse_json(value, strict.unwrap_or(false), icao24bit.unwrap_or(false)))
+}
+
+/// Allocation block of a hex ICAO address: `hex`, `iso2` (null for special-use
+/// blocks), `name`, `special_use`, `start` and `end`.
+#[wasm_bindgen(js_name = hexToCountry)]
+pub fn hex_to_country(hex: &str) -> JsValue {
+    to_js(hex_to_country_json(hex))
+}
+
+/// Hex ICAO address of a registration, for countries with an algorithmic
+/// mapping.
+#[wasm_bindgen(js_name = registrationToHex)]
+pub fn registration_to_hex(registration: &str) -> Option<String> {
+    registration_to_hex_string(registration)
+}
+
+#[cfg(test)]
+mod tests {
+    // The exports need a JavaScript host; these cover the lookups behind them.
+    use super::*;
+
+    #[test]
+    fn parse_lookup() {
+        let entity = parse_json("PH-BHA", false, false).unwrap();
+        assert_eq!(entity["iso2"], "NL");
+        let entity = parse_json("AB8E4F", true, true).unwrap();
+        assert_eq!(entity["nation"], "United States");
+        let entity = parse_json("4Y-ABC", false, false).unwrap();
+        assert!(entity.get("iso2").is_none());
+        assert_eq!(parse_json("ZZZZZZZ", false, false), None);
+    }
+
+    #[test]
+    fn hex_lookup() {
+        let allocation = hex_to_country_json("3c65a1").unwrap();
+        assert_eq!(allocation["hex"], "3C65A1");
+        assert_eq!(allocation["iso2"], "DE");
+        assert_eq!(allocation["start"], "3C0000");
+        assert_eq!(hex_to_country_json("XYZ"), None);
+    }
+
+    #[test]
+    fn registration_lookup() {
+        assert_eq!(registration_to_hex_string("N8437D").as_deref(), Some("AB8E4F"));
+        assert_eq!(registration_to_hex_string("D-AIMA").as_deref(), Some("3C65A1"));
+        assert_eq!(registration_to_hex_string("G-EUPT"), None);
+    }
+}